@@ -0,0 +1,260 @@
+// Copyright (c) 2026 Google LLC All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Rendering of parse errors which re-prints the command line and underlines
+//! the argument each error is about.
+
+use {
+    crate::ParseError,
+    std::{ffi::OsStr, fmt::Write},
+};
+
+/// Renders `error` for the command `command` invoked with `args`, re-printing
+/// the command line beneath each error about a particular argument and
+/// underlining that argument:
+///
+/// ```text
+/// Error parsing option '--count' with value 'abc': invalid digit found in string
+///     mytool --count abc
+///                    ^^^
+/// ```
+///
+/// Arguments are quoted as they would need to be in a POSIX shell, and the
+/// underline is aligned by the display width of the text before it. Errors
+/// which are not about an argument in `args` are rendered as usual.
+///
+/// `args` should be the arguments that were parsed, which are those passed
+/// to [`FromArgs::try_parse`](crate::FromArgs::try_parse) after any response
+/// files are expanded.
+pub fn render_caret(command: &str, args: &[&OsStr], error: &ParseError) -> String {
+    let mut out = String::new();
+    match error {
+        ParseError::Multiple(errors) => {
+            for error in errors {
+                render_one(&mut out, command, args, error);
+            }
+        }
+        error => render_one(&mut out, command, args, error),
+    }
+    out
+}
+
+fn render_one(out: &mut String, command: &str, args: &[&OsStr], error: &ParseError) {
+    let message = error.to_string();
+    out.push_str(message.trim_end_matches('\n'));
+    out.push('\n');
+
+    let Some(index) = error.index().filter(|&index| index < args.len()) else { return };
+
+    let mut line = String::from("    ");
+    line.push_str(&shell_quote(command));
+    let mut underline = String::new();
+    for (i, arg) in args.iter().enumerate() {
+        line.push(' ');
+        let quoted = shell_quote(&arg.to_string_lossy());
+        if i == index {
+            let _ = write!(
+                underline,
+                "{:start$}{:^<width$}",
+                "",
+                "",
+                start = display_width(&line),
+                width = display_width(&quoted).max(1),
+            );
+        }
+        line.push_str(&quoted);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out.push_str(&underline);
+    out.push('\n');
+}
+
+/// Quotes `arg` so that a POSIX shell would read it back as a single word.
+fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_alphanumeric() || "-_./=:,+@%^".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        return arg.to_owned();
+    }
+
+    if arg.chars().any(char::is_control) {
+        // Control characters would break the alignment of the underline, so
+        // spell them out with the `$'...'` quoting most shells support.
+        let mut quoted = String::from("$'");
+        for c in arg.chars() {
+            match c {
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                '\'' | '\\' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                c if c.is_control() => {
+                    let _ = write!(quoted, "\\u{:04x}", c as u32);
+                }
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('\'');
+        return quoted;
+    }
+
+    ["'", &arg.replace('\'', "'\\''"), "'"].concat()
+}
+
+/// The number of terminal columns `s` takes up.
+///
+/// Combining marks and other zero width characters take up no columns, and
+/// East Asian wide characters and emoji take up two.
+fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    const ZERO_WIDTH: &[(u32, u32)] = &[
+        (0x0300, 0x036F),
+        (0x0483, 0x0489),
+        (0x0591, 0x05BD),
+        (0x0610, 0x061A),
+        (0x064B, 0x065F),
+        (0x0E31, 0x0E31),
+        (0x0E34, 0x0E3A),
+        (0x1AB0, 0x1AFF),
+        (0x1DC0, 0x1DFF),
+        (0x200B, 0x200F),
+        (0x20D0, 0x20FF),
+        (0xFE00, 0xFE0F),
+        (0xFE20, 0xFE2F),
+    ];
+    const WIDE: &[(u32, u32)] = &[
+        (0x1100, 0x115F),
+        (0x2E80, 0x303E),
+        (0x3041, 0x33FF),
+        (0x3400, 0x4DBF),
+        (0x4E00, 0x9FFF),
+        (0xA000, 0xA4CF),
+        (0xAC00, 0xD7A3),
+        (0xF900, 0xFAFF),
+        (0xFE30, 0xFE4F),
+        (0xFF00, 0xFF60),
+        (0xFFE0, 0xFFE6),
+        (0x1F300, 0x1F64F),
+        (0x1F900, 0x1F9FF),
+        (0x20000, 0x2FFFD),
+        (0x30000, 0x3FFFD),
+    ];
+    let in_ranges = |ranges: &[(u32, u32)]| {
+        ranges.iter().any(|&(start, end)| (start..=end).contains(&(c as u32)))
+    };
+
+    if c.is_control() || in_ranges(ZERO_WIDTH) {
+        0
+    } else if in_ranges(WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(shell_quote("--count=5"), "--count=5");
+        assert_eq!(shell_quote("naïve"), "naïve");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote("a\nb's"), r"$'a\nb\'s'");
+        assert_eq!(shell_quote("\u{1b}[0m"), r"$'\u001b[0m'");
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("🦀"), 2);
+    }
+
+    #[test]
+    fn render() {
+        let error = ParseError::InvalidValue {
+            index: 1,
+            arg: "--count".into(),
+            value: "abc".into(),
+            source: "invalid digit found in string".into(),
+        };
+        let args = [OsStr::new("--count"), OsStr::new("abc")];
+        assert_eq!(
+            render_caret("mytool", &args, &error),
+            concat!(
+                "Error parsing option '--count' with value 'abc': invalid digit found in string\n",
+                "    mytool --count abc\n",
+                "                   ^^^\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn render_aligns_quoted_and_wide_args() {
+        let error = ParseError::UnknownArgument {
+            index: 2,
+            arg: "--bogus".into(),
+            suggestion: None,
+            no_options: false,
+        };
+        let args = [OsStr::new("日本"), OsStr::new("a b"), OsStr::new("--bogus")];
+        assert_eq!(
+            render_caret("mytool", &args, &error),
+            concat!(
+                "Unrecognized argument: --bogus\n",
+                "    mytool 日本 'a b' --bogus\n",
+                "                      ^^^^^^^\n",
+            ),
+        );
+
+        let error = ParseError::MissingValue { index: 0, option: "--name".into() };
+        assert_eq!(
+            render_caret("my tool", &[OsStr::new("--name")], &error),
+            concat!(
+                "No value provided for option '--name'.\n",
+                "    'my tool' --name\n",
+                "              ^^^^^^\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn render_multiple() {
+        let error = ParseError::Multiple(vec![
+            ParseError::UnexpectedValue { index: 0, switch: "--verbose".into() },
+            ParseError::Message("Something else went wrong.\n".into()),
+        ]);
+        assert_eq!(
+            render_caret("mytool", &[OsStr::new("--verbose=1")], &error),
+            concat!(
+                "Switch '--verbose' does not take a value.\n",
+                "    mytool --verbose=1\n",
+                "           ^^^^^^^^^^^\n",
+                "Something else went wrong.\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn render_without_index() {
+        let error = ParseError::UnknownArgument {
+            index: 3,
+            arg: "x".into(),
+            suggestion: None,
+            no_options: false,
+        };
+        assert_eq!(render_caret("mytool", &[], &error), "Unrecognized argument: x\n");
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2026 Google LLC All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Configuration files layered beneath the command line and the environment.
+
+use {
+    crate::{delimited_elements, EarlyExit, Environment, FromArgs, ParseContext, ParseValueSlot},
+    std::{ffi::OsStr, path::Path},
+};
+
+/// A TOML configuration file that options fall back to when they are not
+/// provided on the command line or through the environment.
+///
+/// Keys are the long names of options, without the leading `--`. The options
+/// of a subcommand are read from the table named after it:
+///
+/// ```toml
+/// api-token = "hunter2"
+///
+/// [deploy]
+/// region = "us"
+/// tags = ["a", "b"]
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    path: String,
+    table: toml::Table,
+}
+
+impl Config {
+    /// Reads the configuration file at `path`. A file which does not exist
+    /// is treated as if it were empty.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(path, &contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self { path: path.display().to_string(), table: toml::Table::new() })
+            }
+            Err(e) => Err([
+                "Error reading config file '",
+                &path.display().to_string(),
+                "': ",
+                &e.to_string(),
+                "\n",
+            ]
+            .concat()),
+        }
+    }
+
+    /// Parses the contents of a configuration file. `path` is only used to
+    /// describe the file in error messages.
+    pub fn parse(path: impl AsRef<Path>, contents: &str) -> Result<Self, String> {
+        let path = path.as_ref().display().to_string();
+        match contents.parse() {
+            Ok(table) => Ok(Self { path, table }),
+            Err(e) => {
+                Err(["Error parsing config file '", &path, "': ", &e.to_string(), "\n"].concat())
+            }
+        }
+    }
+
+    /// Construct `T` from an input set of arguments as
+    /// [`FromArgs::from_args_with_env`] does, with this configuration layered
+    /// beneath the command line and `env`, so that options fall back to it
+    /// when they are not provided by either.
+    pub fn from_args<T: FromArgs>(
+        &self,
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+        env: &dyn Environment,
+    ) -> Result<T, EarlyExit> {
+        let context = ParseContext { config: Some(self), ..ParseContext::new(env) };
+        Ok(T::from_args_with_context(command_name, args, &context)?)
+    }
+
+    /// Returns the value for the option `name` of `command`, along with the
+    /// dotted key it was found at.
+    fn lookup(&self, command: &[&str], name: &str) -> Option<(String, &toml::Value)> {
+        let mut table = &self.table;
+        for subcommand in command {
+            table = table.get(*subcommand)?.as_table()?;
+        }
+        let name = name.trim_start_matches('-');
+        let key = command.iter().copied().chain(Some(name)).collect::<Vec<_>>().join(".");
+        Some((key, table.get(name)?))
+    }
+
+    /// Fills `slot` for the option `name` of the subcommand `command`, if the
+    /// configuration has a value for it. Each value is split at the option's
+    /// `delimiter`, if it has one.
+    pub(crate) fn fill(
+        &self,
+        slot: &mut dyn ParseValueSlot,
+        command: &[&str],
+        name: &str,
+        delimiter: Option<u8>,
+    ) -> Result<(), String> {
+        let (key, value) = match self.lookup(command, name) {
+            Some(found) => found,
+            None => return Ok(()),
+        };
+
+        let path = &self.path;
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                toml::Value::String(value) => value.clone(),
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                toml::Value::Datetime(value) => value.to_string(),
+                toml::Value::Array(_) | toml::Value::Table(_) => {
+                    return Err([
+                        "Error parsing config file '",
+                        path,
+                        "' key '",
+                        &key,
+                        "': expected a single value or an array of values\n",
+                    ]
+                    .concat());
+                }
+            };
+            for element in delimited_elements(OsStr::new(&value), delimiter) {
+                slot.fill_slot(name, &element).map_err(|s| {
+                    [
+                        "Error parsing config file '",
+                        path,
+                        "' key '",
+                        &key,
+                        "' with value '",
+                        &element.to_string_lossy(),
+                        "': ",
+                        &s,
+                        "\n",
+                    ]
+                    .concat()
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (c) 2026 Google LLC All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Subcommands which are separate executables, discovered the way `git`
+//! discovers `git-*` commands.
+//!
+//! ```rust,no_run
+//! use argh::{
+//!     external::{ExternalCommands, PathSubCommands},
+//!     FromArgs,
+//! };
+//!
+//! struct MyToolCommands;
+//!
+//! impl ExternalCommands for MyToolCommands {
+//!     const PREFIX: &'static str = "mytool-";
+//! }
+//!
+//! #[derive(FromArgs)]
+//! /// My tool.
+//! struct MyTool {
+//!     #[argh(subcommand)]
+//!     command: Command,
+//! }
+//!
+//! #[derive(FromArgs)]
+//! #[argh(subcommand)]
+//! enum Command {
+//!     #[argh(dynamic)]
+//!     External(PathSubCommands<MyToolCommands>),
+//! }
+//!
+//! let tool: MyTool = argh::from_env();
+//! match tool.command {
+//!     // `mytool deploy --region us` runs `mytool-deploy --region us`.
+//!     Command::External(external) => {
+//!         let status = external.command().status().expect("failed to run");
+//!         std::process::exit(status.code().unwrap_or(1));
+//!     }
+//! }
+//! ```
+
+use {
+    crate::{CommandInfo, DynamicSubCommand, EarlyExit},
+    std::{
+        any::TypeId,
+        env,
+        ffi::{OsStr, OsString},
+        fmt,
+        marker::PhantomData,
+        path::{Path, PathBuf},
+        process::Command,
+        sync::Mutex,
+    },
+};
+
+/// Where to look for the external subcommands of a command, and how their
+/// executables are named.
+pub trait ExternalCommands: 'static {
+    /// The prefix of the names of the executables, like `mytool-`. The
+    /// executable `mytool-deploy` is then the subcommand `deploy`.
+    const PREFIX: &'static str;
+
+    /// The directories to look for executables in, in order of precedence.
+    ///
+    /// Defaults to those of the `PATH` environment variable.
+    fn dirs() -> Vec<PathBuf> {
+        env::var_os("PATH").map(|path| env::split_paths(&path).collect()).unwrap_or_default()
+    }
+}
+
+/// A [`DynamicSubCommand`] for the executables named with the prefix of `C`,
+/// found in the directories of `C`.
+///
+/// The executables are listed as commands in help and completions. They are
+/// found once per `C`, when the commands are first needed, and are then
+/// looked up again when one is invoked.
+pub struct PathSubCommands<C> {
+    /// The name of the subcommand, without the prefix.
+    pub name: String,
+    /// The path of the executable.
+    pub path: PathBuf,
+    /// The arguments which followed the subcommand.
+    pub args: Vec<OsString>,
+    commands: PhantomData<fn() -> C>,
+}
+
+impl<C> PathSubCommands<C> {
+    /// Returns a [`Command`] running the executable with the arguments.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.path);
+        command.args(&self.args);
+        command
+    }
+}
+
+impl<C> fmt::Debug for PathSubCommands<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathSubCommands")
+            .field("name", &self.name)
+            .field("path", &self.path)
+            .field("args", &self.args)
+            .finish()
+    }
+}
+
+impl<C> Clone for PathSubCommands<C> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            args: self.args.clone(),
+            commands: PhantomData,
+        }
+    }
+}
+
+impl<C> PartialEq for PathSubCommands<C> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.name, &self.path, &self.args) == (&other.name, &other.path, &other.args)
+    }
+}
+
+impl<C> Eq for PathSubCommands<C> {}
+
+impl<C: ExternalCommands> DynamicSubCommand for PathSubCommands<C> {
+    fn commands() -> &'static [&'static CommandInfo] {
+        // Generic functions cannot have statics of their own, so the commands
+        // of each `C` are kept together.
+        static COMMANDS: Mutex<Vec<(TypeId, &'static [&'static CommandInfo])>> =
+            Mutex::new(Vec::new());
+
+        let mut commands = COMMANDS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(&(_, found)) = commands.iter().find(|(id, _)| *id == TypeId::of::<C>()) {
+            return found;
+        }
+
+        // `CommandInfo` needs to live for the static lifetime, and the
+        // commands are only found once per `C`.
+        let found: Vec<&'static CommandInfo> = find_all(C::PREFIX, &C::dirs())
+            .into_iter()
+            .map(|(name, _)| {
+                &*Box::leak(Box::new(CommandInfo {
+                    name: Box::leak(name.into_boxed_str()),
+                    ..Default::default()
+                }))
+            })
+            .collect();
+        let found = &*Box::leak(found.into_boxed_slice());
+        commands.push((TypeId::of::<C>(), found));
+        found
+    }
+
+    fn try_redact_arg_values(
+        command_name: &[&str],
+        _args: &[&str],
+    ) -> Option<Result<Vec<String>, EarlyExit>> {
+        // The arguments of an external command are unknown, so none are kept.
+        let name = command_name.last()?;
+        find(C::PREFIX, &C::dirs(), name)?;
+        Some(Ok(vec![(*name).to_owned()]))
+    }
+
+    fn try_from_args(command_name: &[&str], args: &[&str]) -> Option<Result<Self, EarlyExit>> {
+        let command_name: Vec<&OsStr> = command_name.iter().map(OsStr::new).collect();
+        let args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+        Self::try_from_args_os(&command_name, &args)
+    }
+
+    fn try_from_args_os(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+    ) -> Option<Result<Self, EarlyExit>> {
+        // The arguments are passed on to the executable as they are, even if
+        // they are not valid UTF-8.
+        let name = command_name.last()?.to_str()?;
+        let path = find(C::PREFIX, &C::dirs(), name)?;
+        Some(Ok(Self {
+            name: name.to_owned(),
+            path,
+            args: args.iter().map(|&arg| arg.to_owned()).collect(),
+            commands: PhantomData,
+        }))
+    }
+}
+
+/// Returns the names and paths of the executables in `dirs` named with
+/// `prefix`, sorted by name. Executables in earlier directories take
+/// precedence over those with the same name in later ones.
+pub fn find_all(prefix: &str, dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut found: Vec<(String, PathBuf)> = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = dir.read_dir() else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = command_name(prefix, &path) else { continue };
+            if is_executable(&path) && !found.iter().any(|(found, _)| *found == name) {
+                found.push((name, path));
+            }
+        }
+    }
+    found.sort_by(|(a, _), (b, _)| a.cmp(b));
+    found
+}
+
+/// Returns the path of the executable for the command `name`, which is named
+/// with `prefix`, from the first of `dirs` which has one.
+pub fn find(prefix: &str, dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .filter_map(|dir| dir.read_dir().ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .find(|path| command_name(prefix, path).as_deref() == Some(name) && is_executable(path))
+}
+
+/// Returns the name of the command whose executable is at `path`, if its
+/// file name starts with `prefix`.
+fn command_name(prefix: &str, path: &Path) -> Option<String> {
+    // Executables on Windows are named with an extension, like `.exe`.
+    let name = if cfg!(windows) { path.file_stem() } else { path.file_name() };
+    let name = name?.to_str()?.strip_prefix(prefix)?;
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_owned());
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    path.is_file()
+        && extension.is_some_and(|extension| {
+            extensions
+                .split(';')
+                .any(|known| known.trim_start_matches('.').eq_ignore_ascii_case(extension))
+        })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
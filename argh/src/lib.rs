@@ -52,10 +52,34 @@
 //! - `./some_bin --height 5`
 //! - `./some_bin -j --height 5`
 //! - `./some_bin --jump --height 5 --pilot-nickname Wes`
+//! - `./some_bin --height=5 --pilot-nickname=Wes`
 //!
 //! Switches, like `jump`, are optional and will be set to true if provided.
+//! Short switches may be grouped together, as in `-jv`, and a short option
+//! may have its value attached, as in `-n5`.
 //!
-//! Options, like `height` and `pilot_nickname`, can be either required,
+//! Switches marked `negatable` also accept a `--no-` form which turns them
+//! back off. This is useful for switches which default to on, or for
+//! `Option<bool>` switches which need to tell "off" apart from "not given":
+//!
+//! ```rust
+//! use argh::FromArgs;
+//!
+//! #[derive(FromArgs)]
+//! /// Print some text.
+//! struct Print {
+//!     /// whether to colorize the output (`--color` or `--no-color`)
+//!     #[argh(switch, negatable, default = "true")]
+//!     color: bool,
+//!
+//!     /// whether to page the output, or `None` to decide automatically
+//!     #[argh(switch, negatable)]
+//!     pager: Option<bool>,
+//! }
+//! ```
+//!
+//! Options, like `height` and `pilot_nickname`, take their value either from
+//! the following argument or attached with `=`, and can be either required,
 //! optional, or repeating, depending on whether they are contained in an
 //! `Option` or a `Vec`. Default values can be provided using the
 //! `#[argh(default = "<your_code_here>")]` attribute, and in this case an
@@ -90,6 +114,143 @@
 //! }
 //! ```
 //!
+//! Repeating options can also take several values at once with a
+//! `delimiter`, so that `#[argh(option, delimiter = ',')] tag: Vec<String>`
+//! accepts `--tag a,b --tag c`. Each element is parsed separately, and a
+//! delimiter preceded by a backslash is kept as part of the element. Values
+//! from environment variables and configuration files are split the same way.
+//!
+//! An option with a `default_missing_value`, like
+//! `#[argh(option, default_missing_value = "always")] color: Option<String>`,
+//! may be given without a value. `--color` is then parsed as if it were
+//! `--color=always`. Such options only take a value attached with `=`, and
+//! never the following argument.
+//!
+//! Options and switches accept additional names with `alias`, as in
+//! `#[argh(option, alias = "old-name", alias = "o")]`. An alias of a single
+//! character is used as `-o`, and a longer one as `--old-name`. Aliases are
+//! not shown in the help output; use `visible_alias` for names that should
+//! be. Subcommands take the same attributes, like
+//! `#[argh(subcommand, name = "remove", alias = "rm")]`.
+//!
+//! Options and switches shared by several commands can be kept in a struct of
+//! their own, and embedded in each command with `#[argh(flatten)]`, as in
+//! `#[argh(flatten)] common: CommonOptions`. The options of `CommonOptions`
+//! are then parsed, listed in the help and redacted as if they were those of
+//! the command. The flattened struct may not have positional arguments or
+//! subcommands, and using a name in both structs fails to compile.
+//!
+//! Options and switches marked `global`, as in
+//! `#[argh(switch, short = 'v', global)]`, are also accepted after the name of
+//! any subcommand, at any depth, and set the field of the command declaring
+//! them. The help of a subcommand lists them under "Global options", and a
+//! subcommand using the same name as a global option fails to compile.
+//!
+//! Wrappers around other tools can pass on the options they do not recognize
+//! by collecting them, in order, into a `Vec` field marked
+//! `#[argh(unknown)]`. A group of short options like `-jk` is collected if its
+//! first option is unknown. With `#[argh(unknown, with_values)]`, an unknown
+//! option without an attached value, like `-p` but not `-p22` or `--port=22`,
+//! also takes the next argument as its value unless it starts with `-`.
+//! Collected arguments are redacted as the name of the field.
+//!
+//! Options, switches, positional arguments, subcommands and `FromArgValue`
+//! variants can be marked with `#[argh(deprecated = "use --region instead")]`,
+//! or with a bare `#[argh(deprecated)]`. They are still parsed as usual, but
+//! using them records a warning which can be retrieved with
+//! [`take_warnings`], and which [`from_env`] prints to stderr. Deprecated
+//! items are marked as such in the help output.
+//!
+//! Options can also fall back to an environment variable using the
+//! `#[argh(option, env = "APP_TOKEN")]` attribute. A value given on the
+//! command line takes precedence over the environment variable, which in turn
+//! takes precedence over a `default`. The variable's value is parsed the same
+//! way as a command line value would be, and the variable is listed in the
+//! help output. `FromArgs::from_args_with_env` can be used to supply a fake
+//! environment in tests.
+//!
+//! To give every option an environment variable, add
+//! `#[argh(env_prefix = "MYTOOL_")]` to the struct instead. Each option
+//! without an `env` attribute then falls back to the prefix followed by its
+//! long name in upper case, like `MYTOOL_API_TOKEN` for `--api-token`.
+//! Subcommands without an `env_prefix` of their own inherit it with their
+//! name appended, like `MYTOOL_DEPLOY_REGION`. These variables are not listed
+//! in the help output, but can be found through `ArgsInfo` with
+//! `CommandInfoWithArgs::flag_env_var`.
+//!
+//! With the `config` feature enabled, `argh::from_env_with_config` also
+//! reads options from a TOML file, beneath the command line and the
+//! environment but above any `default`. Keys are the long names of options,
+//! and the options of a subcommand are read from the table named after it.
+//!
+//! Commands with long argument lists can accept response files by adding
+//! `#[argh(response_files)]` to the struct. Each `@path` argument before the
+//! first `--` is then replaced by the arguments in the file at `path`, which
+//! are separated by whitespace and may be quoted with `'` or `"`. Lines
+//! starting with `#` are comments, and response files may refer to further
+//! response files. Without the attribute, `@path` is an ordinary argument.
+//!
+//! By default, parsing stops at the first error. Adding
+//! `#[argh(collect_errors)]` to a top-level struct instead reports every
+//! unknown argument, invalid value and missing requirement at once, for the
+//! command and its subcommands. The errors are ordered by the position of the
+//! argument they are about, and are returned as `ParseError::Multiple` by
+//! `FromArgs::try_parse`.
+//!
+//! Mistyped options, subcommands and choice values get a suggestion, like
+//! `Did you mean "--verbose"?` for `--verbos`, when a known one is close
+//! enough. Suggestions are made by the default `suggestions` feature, which
+//! has no dependencies.
+//!
+//! Errors are printed as plain messages by default. Calling
+//! `argh::set_error_style(argh::ErrorStyle::Caret)` before `argh::from_env`
+//! instead re-prints the command line beneath each error and underlines the
+//! argument it is about.
+//!
+//! Usage errors exit with status 2, as GNU tools do, which
+//! `argh::set_usage_exit_code` changes. Commands document the other codes they
+//! exit with in their help with `#[argh(error_code(code, "description"))]`,
+//! and exit with one of them with `argh::exit_with::<Command>(code)`, or by
+//! returning `argh::exit_code::<Command>(code)` from a `main` which returns
+//! `std::process::ExitCode`. Both panic if `Command` does not declare `code`.
+//!
+//! Relationships between arguments are checked after parsing, and any
+//! violations are reported together:
+//!
+//! ```
+//! # use argh::FromArgs;
+//! #[derive(FromArgs)]
+//! /// Print the report.
+//! #[argh(group(name = "output", required, multiple = false))]
+//! struct Report {
+//!     /// print as json
+//!     #[argh(switch, group = "output", conflicts_with = "pretty")]
+//!     json: bool,
+//!
+//!     /// print using a format string
+//!     #[argh(option, group = "output")]
+//!     format: Option<String>,
+//!
+//!     /// pretty print
+//!     #[argh(switch)]
+//!     pretty: bool,
+//!
+//!     /// the private key
+//!     #[argh(option, requires = "cert")]
+//!     key: Option<String>,
+//!
+//!     /// the certificate
+//!     #[argh(option)]
+//!     cert: Option<String>,
+//! }
+//! ```
+//!
+//! `conflicts_with` and `requires` name other fields of the struct. Fields
+//! join a group declared on the struct with `group`. At least one field of a
+//! `required` group must be provided, and at most one field of a group with
+//! `multiple = false`. Such groups are shown together in the usage line, like
+//! `(--json | --format <format>)`.
+//!
 //! Custom option types can be deserialized so long as they implement the
 //! `FromArgValue` trait (automatically implemented for all `FromStr` types).
 //! If more customized parsing is required, you can supply a custom
@@ -111,6 +272,10 @@
 //! }
 //! ```
 //!
+//! Arguments are not required to be valid UTF-8. `PathBuf` and `OsString`
+//! fields are parsed with the `FromArgOsValue` trait, and receive their
+//! values as-is from `from_env` or `FromArgs::from_args_os`.
+//!
 //! `FromArgValue` can be automatically derived for `enum`s, with automatic
 //! error messages:
 //!
@@ -180,6 +345,32 @@
 //! before the rest of the arguments can be interpreted, and shouldn't be used
 //! for regular use as it might be confusing.
 //!
+//! To collect only the arguments after the first `--`, separately from the
+//! positional arguments, use a `Vec` field marked `#[argh(trailing)]`. Every
+//! argument after the `--` is collected, even ones like `--help`, and the
+//! field is shown at the end of the usage line as `[-- <passthrough>...]`:
+//!
+//! ```rust
+//! use argh::FromArgs;
+//! #[derive(FromArgs, PartialEq, Debug)]
+//! /// Run a binary.
+//! struct Run {
+//!     /// the binary to run
+//!     #[argh(positional)]
+//!     bin: String,
+//!     /// the arguments of the binary
+//!     #[argh(trailing)]
+//!     passthrough: Vec<String>,
+//! }
+//!
+//! let run = Run::from_args(&["run"], &["app", "--", "--verbose", "x"]).unwrap();
+//! assert_eq!(run.bin, "app");
+//! assert_eq!(run.passthrough, ["--verbose", "x"]);
+//! ```
+//!
+//! A command with a trailing field cannot also have a greedy positional
+//! argument.
+//!
 //! Subcommands are also supported. To use a subcommand, declare a separate
 //! `FromArgs` type for each subcommand as well as an enum that cases
 //! over each command:
@@ -220,6 +411,13 @@
 //! }
 //! ```
 //!
+//! A variant marked `#[argh(default)]` is parsed when no subcommand is given,
+//! so that `tool` behaves like `tool one`. It takes the arguments from the
+//! first one its parent command does not accept, or none if there is no such
+//! argument. A subcommand field can instead name the default itself, as in
+//! `#[argh(subcommand, default = "one")]`, which must be the name of one of
+//! the enum's subcommands. The help marks the default command.
+//!
 //! You can also discover subcommands dynamically at runtime. To do this,
 //! declare subcommands as usual and add a variant to the enum with the
 //! `dynamic` attribute. Instead of deriving `FromArgs`, the value inside the
@@ -278,6 +476,7 @@
 //!                 name: "dynamic_command",
 //!                 short: &'d',
 //!                 description: "A dynamic command",
+//!                 ..Default::default()
 //!             })));
 //!
 //!             commands
@@ -317,6 +516,10 @@
 //! }
 //! ```
 //!
+//! [`external::PathSubCommands`] is a ready-made dynamic subcommand for
+//! executables named like `mytool-deploy`, found on `PATH` or in other
+//! directories, as `git` does for `git-*` commands.
+//!
 //! You can define a complex help output that includes an **Examples** section.
 //! Use a `{command_name}` placeholder.
 //!
@@ -400,10 +603,38 @@
 
 #![deny(missing_docs)]
 
-use std::{ffi::OsString, str::FromStr};
+use std::{
+    borrow::{Borrow, Cow},
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    ffi::{OsStr, OsString},
+    hash::Hash,
+    path::PathBuf,
+    process::{ExitCode, Termination},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
 
 pub use argh_derive::{ArgsInfo, FromArgValue, FromArgs};
 
+mod caret;
+pub mod external;
+mod response_files;
+#[cfg(feature = "suggestions")]
+mod suggestions;
+
+#[cfg(feature = "config")]
+mod config;
+
+#[cfg(feature = "config")]
+pub use config::Config;
+
+pub use caret::render_caret;
+
+#[doc(hidden)]
+pub use response_files::expand_response_files;
+
 /// Information about a particular command used for output.
 pub type CommandInfo = argh_shared::CommandInfo<'static>;
 
@@ -415,9 +646,6 @@ pub type SubCommandInfo = argh_shared::SubCommandInfo<'static>;
 
 pub use argh_shared::{ErrorCodeInfo, FlagInfo, FlagInfoKind, Optionality, PositionalInfo};
 
-#[cfg(feature = "fuzzy_search")]
-use rust_fuzzy_search::fuzzy_search_best_n;
-
 /// Structured information about the command line arguments.
 pub trait ArgsInfo {
     /// Returns the argument info.
@@ -501,9 +729,8 @@ pub trait FromArgs: Sized {
     ///     &["help"],
     /// ).unwrap_err();
     /// assert_eq!(
-    ///     early_exit,
-    ///     argh::EarlyExit {
-    ///        output: r#"Usage: classroom <command> [<args>]
+    ///     early_exit.output,
+    ///     r#"Usage: classroom <command> [<args>]
     ///
     /// Command to manage a classroom.
     ///
@@ -513,10 +740,9 @@ pub trait FromArgs: Sized {
     /// Commands:
     ///   list              list all the classes.
     ///   add               add students to a class.
-    /// "#.to_string(),
-    ///        status: Ok(()),
-    ///     },
+    /// "#,
     /// );
+    /// assert_eq!(early_exit.status, Ok(()));
     ///
     /// // Help works with subcommands.
     /// let early_exit = ClassroomCmd::from_args(
@@ -524,35 +750,168 @@ pub trait FromArgs: Sized {
     ///     &["list", "help"],
     /// ).unwrap_err();
     /// assert_eq!(
-    ///     early_exit,
-    ///     argh::EarlyExit {
-    ///        output: r#"Usage: classroom list [--teacher-name <teacher-name>]
+    ///     early_exit.output,
+    ///     r#"Usage: classroom list [--teacher-name <teacher-name>]
     ///
     /// list all the classes.
     ///
     /// Options:
     ///   --teacher-name    list classes for only this teacher.
     ///   --help, help      display usage information
-    /// "#.to_string(),
-    ///        status: Ok(()),
-    ///     },
+    /// "#,
     /// );
+    /// assert_eq!(early_exit.status, Ok(()));
     ///
     /// // Incorrect arguments will error out.
     /// let err = ClassroomCmd::from_args(
     ///     &["classroom"],
-    ///     &["lisp"],
+    ///     &["remove"],
     /// ).unwrap_err();
     /// assert_eq!(
     ///    err,
     ///    argh::EarlyExit {
-    ///        output: "Unrecognized argument: lisp\n".to_string(),
+    ///        output: "Unrecognized argument: remove\n".to_string(),
     ///        status: Err(()),
     ///     },
     /// );
     /// ```
     fn from_args(command_name: &[&str], args: &[&str]) -> Result<Self, EarlyExit>;
 
+    /// Construct the type from an input set of arguments, returning a
+    /// [`ParseError`] rather than an [`EarlyExit`] if parsing exits early.
+    ///
+    /// This behaves like [FromArgs::from_args] otherwise, and suits programs
+    /// which need to tell errors apart, or find the argument which caused
+    /// one, without matching on the rendered output. Converting the error
+    /// into an `EarlyExit` gives the one `from_args` returns.
+    ///
+    /// The default implementation reports the errors of implementations
+    /// which only return an `EarlyExit` as a [`ParseError::Message`], or a
+    /// [`ParseError::HelpRequested`] for help.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use argh::FromArgs;
+    ///
+    /// /// Reach new heights.
+    /// #[derive(FromArgs, Debug)]
+    /// struct GoUp {
+    ///     /// how high to go.
+    ///     #[argh(option)]
+    ///     height: usize,
+    /// }
+    ///
+    /// let err = GoUp::try_parse(&["goup"], &["--height", "high"]).unwrap_err();
+    /// assert_eq!(
+    ///     err,
+    ///     argh::ParseError::InvalidValue {
+    ///         index: 1,
+    ///         arg: "--height".to_string(),
+    ///         value: "high".to_string(),
+    ///         source: "invalid digit found in string".to_string(),
+    ///     },
+    /// );
+    /// assert_eq!(
+    ///     argh::EarlyExit::from(err).output,
+    ///     "Error parsing option '--height' with value 'high': invalid digit found in string\n",
+    /// );
+    /// ```
+    fn try_parse(command_name: &[&str], args: &[&str]) -> Result<Self, ParseError> {
+        let context = ParseContext::new(&ProcessEnvironment);
+        Self::from_args_with_context(&str_args_to_os(command_name), &str_args_to_os(args), &context)
+    }
+
+    /// Construct the type from an input set of arguments which may not be
+    /// valid UTF-8.
+    ///
+    /// This behaves like [FromArgs::from_args], except that fields which
+    /// implement [FromArgOsValue], like `PathBuf` and `OsString`, receive
+    /// their values as-is. Option and subcommand names must still be valid
+    /// UTF-8 to be recognized, and command names which are not are converted
+    /// lossily for use in help output.
+    ///
+    /// The default implementation requires all arguments to be valid UTF-8
+    /// and forwards to [FromArgs::from_args].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use argh::FromArgs;
+    /// # use std::ffi::OsStr;
+    /// # use std::path::PathBuf;
+    ///
+    /// /// Copy a file.
+    /// #[derive(FromArgs)]
+    /// struct CopyCmd {
+    ///     /// the file to copy.
+    ///     #[argh(positional)]
+    ///     source: PathBuf,
+    /// }
+    ///
+    /// let cmd = CopyCmd::from_args_os(
+    ///     &[OsStr::new("copy")],
+    ///     &[OsStr::new("notes.txt")],
+    /// ).unwrap();
+    /// assert_eq!(cmd.source, PathBuf::from("notes.txt"));
+    /// ```
+    fn from_args_os(command_name: &[&OsStr], args: &[&OsStr]) -> Result<Self, EarlyExit> {
+        let command_name = os_args_to_lossy(command_name);
+        let command_name: Vec<&str> = command_name.iter().map(|s| s.as_ref()).collect();
+        Self::from_args(&command_name, &os_args_to_str(args)?)
+    }
+
+    /// Construct the type from an input set of arguments, reading the
+    /// fallback values of `#[argh(option, env = "...")]` fields from `env`
+    /// rather than from the process environment.
+    ///
+    /// This behaves like [FromArgs::from_args_os] otherwise, and is mostly
+    /// useful to test commands which read environment variables.
+    ///
+    /// The default implementation ignores `env` and forwards to
+    /// [FromArgs::from_args_os].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use argh::FromArgs;
+    /// # use std::ffi::OsStr;
+    ///
+    /// /// Deploy the service.
+    /// #[derive(FromArgs)]
+    /// struct DeployCmd {
+    ///     /// the token to deploy with.
+    ///     #[argh(option, env = "APP_TOKEN")]
+    ///     token: String,
+    /// }
+    ///
+    /// let cmd = DeployCmd::from_args_with_env(
+    ///     &[OsStr::new("deploy")],
+    ///     &[],
+    ///     &[("APP_TOKEN", "hunter2")],
+    /// ).unwrap();
+    /// assert_eq!(cmd.token, "hunter2");
+    /// ```
+    fn from_args_with_env(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+        env: &dyn Environment,
+    ) -> Result<Self, EarlyExit> {
+        let _ = env;
+        Self::from_args_os(command_name, args)
+    }
+
+    // Like `from_args_with_env`, for a subcommand or `#[argh(flatten)]` field
+    // which inherits the state of its parent command from `context`.
+    #[doc(hidden)]
+    fn from_args_with_context(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+        context: &ParseContext<'_>,
+    ) -> Result<Self, ParseError> {
+        Ok(Self::from_args_with_env(command_name, args, context.env)?)
+    }
+
     /// Get a String with just the argument names, e.g., options, flags, subcommands, etc, but
     /// without the values of the options and arguments. This can be useful as a means to capture
     /// anonymous usage statistics without revealing the content entered by the end user.
@@ -666,10 +1025,10 @@ pub trait FromArgs: Sized {
     /// );
     ///
     /// // `ClassroomCmd::redact_arg_values` will generate help messages.
+    /// let early_exit = ClassroomCmd::redact_arg_values(&["classroom"], &["help"]).unwrap_err();
     /// assert_eq!(
-    ///     ClassroomCmd::redact_arg_values(&["classroom"], &["help"]),
-    ///     Err(argh::EarlyExit {
-    ///         output: r#"Usage: classroom <command> [<args>]
+    ///     early_exit.output,
+    ///     r#"Usage: classroom <command> [<args>]
     ///
     /// Command to manage a classroom.
     ///
@@ -679,14 +1038,43 @@ pub trait FromArgs: Sized {
     /// Commands:
     ///   list              list all the classes.
     ///   add               add students to a class.
-    /// "#.to_string(),
-    ///         status: Ok(()),
-    ///     }),
+    /// "#,
     /// );
+    /// assert_eq!(early_exit.status, Ok(()));
     /// ```
     fn redact_arg_values(_command_name: &[&str], _args: &[&str]) -> Result<Vec<String>, EarlyExit> {
         Ok(vec!["<<REDACTED>>".into()])
     }
+
+    // Like `redact_arg_values`, for a subcommand which accepts the global
+    // options of its parents.
+    #[doc(hidden)]
+    fn redact_arg_values_with_globals(
+        command_name: &[&str],
+        args: &[&str],
+        global_args: Option<&GlobalArgs<'_>>,
+    ) -> Result<Vec<String>, ParseError> {
+        let _ = global_args;
+        Ok(Self::redact_arg_values(command_name, args)?)
+    }
+
+    /// The error codes this command declares with
+    /// `#[argh(error_code(code, "description"))]`, which [`exit_with`] and
+    /// [`exit_code`] accept.
+    ///
+    /// ```rust
+    /// # use argh::FromArgs;
+    /// /// Chop down trees.
+    /// #[derive(FromArgs)]
+    /// #[argh(error_code(2, "The blade is too dull."), error_code(3, "Out of fuel."))]
+    /// struct Chop {}
+    ///
+    /// let codes: Vec<i32> = Chop::error_codes().iter().map(|info| info.code).collect();
+    /// assert_eq!(codes, [2, 3]);
+    /// ```
+    fn error_codes() -> &'static [ErrorCodeInfo<'static>] {
+        &[]
+    }
 }
 
 impl<T: FromArgs> FromArgs for Box<T> {
@@ -694,9 +1082,78 @@ impl<T: FromArgs> FromArgs for Box<T> {
         T::from_args(command_name, args).map(Box::new)
     }
 
+    fn from_args_os(command_name: &[&OsStr], args: &[&OsStr]) -> Result<Self, EarlyExit> {
+        T::from_args_os(command_name, args).map(Box::new)
+    }
+
+    fn from_args_with_env(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+        env: &dyn Environment,
+    ) -> Result<Self, EarlyExit> {
+        T::from_args_with_env(command_name, args, env).map(Box::new)
+    }
+
+    fn from_args_with_context(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+        context: &ParseContext<'_>,
+    ) -> Result<Self, ParseError> {
+        T::from_args_with_context(command_name, args, context).map(Box::new)
+    }
+
     fn redact_arg_values(command_name: &[&str], args: &[&str]) -> Result<Vec<String>, EarlyExit> {
         T::redact_arg_values(command_name, args)
     }
+
+    fn redact_arg_values_with_globals(
+        command_name: &[&str],
+        args: &[&str],
+        global_args: Option<&GlobalArgs<'_>>,
+    ) -> Result<Vec<String>, ParseError> {
+        T::redact_arg_values_with_globals(command_name, args, global_args)
+    }
+
+    fn error_codes() -> &'static [ErrorCodeInfo<'static>] {
+        T::error_codes()
+    }
+}
+
+/// A set of environment variables that `#[argh(option, env = "...")]` fields
+/// fall back to when they are not provided on the command line.
+///
+/// [`ProcessEnvironment`] reads the environment of the current process. Fixed
+/// lists of variables, like `[("APP_TOKEN", "hunter2")]`, can be used to test
+/// commands with [`FromArgs::from_args_with_env`].
+pub trait Environment {
+    /// Returns the value of the environment variable `name`, if it is set.
+    fn var(&self, name: &str) -> Option<OsString>;
+}
+
+/// The environment of the current process, as returned by `std::env::var_os`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnvironment;
+
+impl Environment for ProcessEnvironment {
+    fn var(&self, name: &str) -> Option<OsString> {
+        std::env::var_os(name)
+    }
+}
+
+impl<const N: usize> Environment for [(&str, &str); N] {
+    fn var(&self, name: &str) -> Option<OsString> {
+        self.iter().rev().find(|(key, _)| *key == name).map(|(_, value)| value.into())
+    }
+}
+
+impl<K, V> Environment for HashMap<K, V>
+where
+    K: Borrow<str> + Eq + Hash,
+    V: AsRef<OsStr>,
+{
+    fn var(&self, name: &str) -> Option<OsString> {
+        self.get(name).map(|value| value.as_ref().to_owned())
+    }
 }
 
 /// A top-level `FromArgs` implementation that is not a subcommand.
@@ -711,20 +1168,55 @@ pub trait SubCommands: FromArgs {
     fn dynamic_commands() -> &'static [&'static CommandInfo] {
         &[]
     }
+
+    /// The name of the command which is parsed when none is given, if any.
+    const DEFAULT: Option<&'static str> = None;
+
+    // The names of the options and switches of each command, which the
+    // global options of the parent command may not use.
+    #[doc(hidden)]
+    const FLAGS: &'static [&'static FlattenedFlags] = &[];
 }
 
 impl<T: SubCommand> SubCommands for T {
     const COMMANDS: &'static [&'static CommandInfo] = &[T::COMMAND];
+    const FLAGS: &'static [&'static FlattenedFlags] = &[T::FLAGS];
 }
 
 /// A `FromArgs` implementation that represents a single subcommand.
 pub trait SubCommand: FromArgs {
     /// Information about the subcommand.
     const COMMAND: &'static CommandInfo;
+
+    // The names of the options and switches of the subcommand.
+    #[doc(hidden)]
+    const FLAGS: &'static FlattenedFlags = &FlattenedFlags::EMPTY;
 }
 
 impl<T: SubCommand> SubCommand for Box<T> {
     const COMMAND: &'static CommandInfo = T::COMMAND;
+    const FLAGS: &'static FlattenedFlags = T::FLAGS;
+}
+
+// A `#[derive(FromArgs)]` struct, whose options and switches can be embedded
+// in those of another with `#[argh(flatten)]`.
+#[doc(hidden)]
+pub trait Flatten: FromArgs {
+    // The names of the options and switches, including those of the struct's
+    // own `#[argh(flatten)]` fields.
+    const FLAGS: &'static FlattenedFlags;
+
+    // The options in the usage line of the help of a struct which flattens
+    // this one, each preceded by a space.
+    fn help_usage(command_name: &str) -> String;
+
+    // The descriptions of the options in the help of a struct which flattens
+    // this one, each preceded by a newline.
+    fn help_options(command_name: &str) -> String;
+
+    // The descriptions of the global options, in the same form, for the help
+    // of subcommands.
+    fn help_global_options(command_name: &str) -> String;
 }
 
 /// Trait implemented by values returned from a dynamic subcommand handler.
@@ -753,6 +1245,23 @@ pub trait DynamicSubCommand: Sized {
     /// it should return `Some`, and the value within the `Some` has the same
     /// semantics as the return of `FromArgs::from_args`.
     fn try_from_args(command_name: &[&str], args: &[&str]) -> Option<Result<Self, EarlyExit>>;
+
+    /// Perform the function of `FromArgs::from_args_os` for this dynamic
+    /// command, with arguments which may not be valid UTF-8.
+    ///
+    /// This has the same semantics as `try_from_args`, which it calls by
+    /// default after failing for any argument which is not valid UTF-8.
+    fn try_from_args_os(
+        command_name: &[&OsStr],
+        args: &[&OsStr],
+    ) -> Option<Result<Self, EarlyExit>> {
+        let strs = os_args_to_str(command_name)
+            .and_then(|command_name| os_args_to_str(args).map(|args| (command_name, args)));
+        match strs {
+            Ok((command_name, args)) => Self::try_from_args(&command_name, &args),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
 }
 
 /// Information to display to the user about why a `FromArgs` construction exited early.
@@ -778,6 +1287,243 @@ impl From<String> for EarlyExit {
     }
 }
 
+/// Renders the error as [`FromArgs::from_args`] does.
+impl From<ParseError> for EarlyExit {
+    fn from(error: ParseError) -> Self {
+        let status = match error {
+            ParseError::HelpRequested { .. } => Ok(()),
+            _ => Err(()),
+        };
+        Self { output: error.to_string(), status }
+    }
+}
+
+/// The reason why parsing arguments exited early.
+///
+/// Each variant renders, through its `Display` implementation, the same
+/// output that [`EarlyExit::output`] holds. Variants which are about a
+/// particular argument carry its `index` into the arguments passed to
+/// [`FromArgs::try_parse`], after any response files are expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// Help was requested with `--help` or another help trigger. This is not
+    /// an error, and `output` holds the help text.
+    HelpRequested {
+        /// The help text.
+        output: String,
+    },
+    /// An argument was not recognized as an option, subcommand or positional
+    /// argument.
+    UnknownArgument {
+        /// The index of the argument.
+        index: usize,
+        /// The argument, or the unrecognized part of a group of short options.
+        arg: String,
+        /// A known argument which was probably meant instead.
+        suggestion: Option<String>,
+        /// Whether `arg` is an option given to a command which accepts none,
+        /// in which case the message quotes it.
+        no_options: bool,
+    },
+    /// An argument followed a help trigger, as in `help --verbose`.
+    ArgumentAfterHelp {
+        /// The index of the argument.
+        index: usize,
+    },
+    /// An option was not followed by a value.
+    MissingValue {
+        /// The index of the option.
+        index: usize,
+        /// The option, like `--name`.
+        option: String,
+    },
+    /// A switch was given a value, as in `--verbose=yes`.
+    UnexpectedValue {
+        /// The index of the switch.
+        index: usize,
+        /// The switch, like `--verbose`.
+        switch: String,
+    },
+    /// The value of an option could not be parsed.
+    InvalidValue {
+        /// The index of the argument holding the value.
+        index: usize,
+        /// The option, like `--count`.
+        arg: String,
+        /// The value which could not be parsed.
+        value: String,
+        /// Why the value could not be parsed.
+        source: String,
+    },
+    /// An option which only takes a single value was given several.
+    DuplicateValue {
+        /// The index of the argument holding the second value.
+        index: usize,
+        /// The option, like `--count`.
+        arg: String,
+        /// The second value.
+        value: String,
+    },
+    /// The value of a positional argument could not be parsed.
+    InvalidPositional {
+        /// The index of the argument.
+        index: usize,
+        /// The name of the positional argument.
+        name: String,
+        /// The value which could not be parsed.
+        value: String,
+        /// Why the value could not be parsed.
+        source: String,
+    },
+    /// The value of the environment variable an option falls back to could
+    /// not be parsed.
+    InvalidEnvValue {
+        /// The environment variable.
+        var: String,
+        /// The value which could not be parsed.
+        value: String,
+        /// Why the value could not be parsed.
+        source: String,
+    },
+    /// An argument is not valid UTF-8, and its command needs it to be.
+    InvalidUtf8 {
+        /// The index of the argument.
+        index: usize,
+        /// The argument, with invalid parts replaced.
+        arg: String,
+    },
+    /// Required arguments were not provided, or the provided arguments
+    /// conflict with each other.
+    MissingRequirements(Box<MissingRequirements>),
+    /// Any other reason, described only by its message. This includes errors
+    /// from configuration files and response files, and those created with
+    /// `EarlyExit::from(String)`.
+    Message(String),
+    /// Several errors, reported together by commands with the
+    /// `#[argh(collect_errors)]` attribute. Errors about a particular argument
+    /// are ordered by its index, followed by the others.
+    Multiple(Vec<ParseError>),
+}
+
+impl ParseError {
+    /// The index of the argument this error is about, if any.
+    pub fn index(&self) -> Option<usize> {
+        match *self {
+            ParseError::UnknownArgument { index, .. }
+            | ParseError::ArgumentAfterHelp { index }
+            | ParseError::MissingValue { index, .. }
+            | ParseError::UnexpectedValue { index, .. }
+            | ParseError::InvalidValue { index, .. }
+            | ParseError::DuplicateValue { index, .. }
+            | ParseError::InvalidPositional { index, .. }
+            | ParseError::InvalidUtf8 { index, .. } => Some(index),
+            ParseError::Multiple(ref errors) => errors.iter().find_map(ParseError::index),
+            ParseError::HelpRequested { .. }
+            | ParseError::InvalidEnvValue { .. }
+            | ParseError::MissingRequirements(_)
+            | ParseError::Message(_) => None,
+        }
+    }
+
+    // Replaces the index of this error with `map(index)`, for errors from a
+    // subcommand or `#[argh(flatten)]` field, whose arguments are at other
+    // indices in those of its parent.
+    fn map_index(&mut self, map: &dyn Fn(usize) -> usize) {
+        match self {
+            ParseError::UnknownArgument { index, .. }
+            | ParseError::ArgumentAfterHelp { index }
+            | ParseError::MissingValue { index, .. }
+            | ParseError::UnexpectedValue { index, .. }
+            | ParseError::InvalidValue { index, .. }
+            | ParseError::DuplicateValue { index, .. }
+            | ParseError::InvalidPositional { index, .. }
+            | ParseError::InvalidUtf8 { index, .. } => *index = map(*index),
+            ParseError::Multiple(errors) => {
+                errors.iter_mut().for_each(|error| error.map_index(map))
+            }
+            ParseError::HelpRequested { .. }
+            | ParseError::InvalidEnvValue { .. }
+            | ParseError::MissingRequirements(_)
+            | ParseError::Message(_) => {}
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::HelpRequested { output } | ParseError::Message(output) => {
+                f.write_str(output)
+            }
+            ParseError::UnknownArgument { arg, suggestion: Some(suggestion), .. } => {
+                writeln!(f, "Unrecognized argument: \"{}\". Did you mean \"{}\"?", arg, suggestion)
+            }
+            ParseError::UnknownArgument { arg, suggestion: None, no_options: true, .. } => {
+                writeln!(f, "Unrecognized argument: \"{}\"", arg)
+            }
+            ParseError::UnknownArgument { arg, suggestion: None, no_options: false, .. } => {
+                writeln!(f, "Unrecognized argument: {}", arg)
+            }
+            ParseError::ArgumentAfterHelp { .. } => {
+                f.write_str("Trailing arguments are not allowed after `help`.")
+            }
+            ParseError::MissingValue { option, .. } => {
+                writeln!(f, "No value provided for option '{}'.", option)
+            }
+            ParseError::UnexpectedValue { switch, .. } => {
+                writeln!(f, "Switch '{}' does not take a value.", switch)
+            }
+            ParseError::InvalidValue { arg, value, source, .. } => {
+                writeln!(f, "Error parsing option '{}' with value '{}': {}", arg, value, source)
+            }
+            ParseError::DuplicateValue { arg, value, .. } => {
+                writeln!(
+                    f,
+                    "Error parsing option '{}' with value '{}': {}",
+                    arg, value, DUPLICATE_VALUES
+                )
+            }
+            ParseError::InvalidPositional { name, value, source, .. } => writeln!(
+                f,
+                "Error parsing positional argument '{}' with value '{}': {}",
+                name, value, source
+            ),
+            ParseError::InvalidEnvValue { var, value, source } => writeln!(
+                f,
+                "Error parsing environment variable '{}' with value '{}': {}",
+                var, value, source
+            ),
+            ParseError::InvalidUtf8 { arg, .. } => writeln!(f, "Invalid utf8: {}", arg),
+            ParseError::MissingRequirements(missing) => missing.fmt(f),
+            ParseError::Multiple(errors) => {
+                for error in errors {
+                    let rendered = error.to_string();
+                    f.write_str(&rendered)?;
+                    if !rendered.ends_with('\n') {
+                        f.write_str("\n")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Keeps the output of an `EarlyExit` returned by a `FromArgs` implementation
+/// which does not report a `ParseError`, as a [`ParseError::HelpRequested`] if
+/// its status is `Ok`, or a [`ParseError::Message`] otherwise.
+impl From<EarlyExit> for ParseError {
+    fn from(early_exit: EarlyExit) -> Self {
+        match early_exit.status {
+            Ok(()) => ParseError::HelpRequested { output: early_exit.output },
+            Err(()) => ParseError::Message(early_exit.output),
+        }
+    }
+}
+
 /// Extract the base cmd from a path
 fn cmd<'a>(default: &'a str, path: &'a str) -> &'a str {
     std::path::Path::new(path).file_name().and_then(|s| s.to_str()).unwrap_or(default)
@@ -786,6 +1532,9 @@ fn cmd<'a>(default: &'a str, path: &'a str) -> &'a str {
 /// Error returned by [`try_from_env`]
 pub enum FromEnvError {
     /// An argument contained an invalid Utf-8 character.
+    ///
+    /// [`try_from_env`] passes arguments on to [`FromArgs::from_args_os`], so
+    /// it no longer returns this error.
     Utf8(OsString),
     /// Parsing arguments led to a request for an early exit, either to display
     /// help or because the arguments were invalid.
@@ -795,87 +1544,371 @@ pub enum FromEnvError {
 impl FromEnvError {
     /// Handle an error returned from parsing options by printing information to
     /// the user and exiting with the appropriate status code.
+    ///
+    /// Help exits with status 0, and usage errors with the status set by
+    /// [`set_usage_exit_code`], which is 2 by default.
     pub fn handle(self) -> ! {
+        std::process::exit(self.print().into())
+    }
+
+    /// Prints information to the user and returns the status to exit with.
+    fn print(self) -> u8 {
         match self {
             FromEnvError::Utf8(arg) => {
                 eprintln!("Invalid utf8: {}", arg.to_string_lossy());
-                std::process::exit(1)
-            }
-            FromEnvError::EarlyExit(early_exit, cmd) => {
-                std::process::exit(match early_exit.status {
-                    Ok(()) => {
-                        println!("{}", early_exit.output);
-                        0
-                    }
-                    Err(()) => {
-                        eprintln!(
-                            "{}\nRun {} --help for more information.",
-                            early_exit.output, cmd
-                        );
-                        1
-                    }
-                })
+                usage_exit_code()
             }
+            FromEnvError::EarlyExit(early_exit, cmd) => match early_exit.status {
+                Ok(()) => {
+                    println!("{}", early_exit.output);
+                    0
+                }
+                Err(()) => {
+                    eprintln!("{}\nRun {} --help for more information.", early_exit.output, cmd);
+                    usage_exit_code()
+                }
+            },
         }
     }
 }
 
-/// Create a `FromArgs` type from the current process's `env::args`.
-///
-/// This function will return an error if argument parsing was unsuccessful or
-/// if information like `--help` was requested. The standard behavior in this
-/// case is usually some form of early exit, which can be performed by calling
-/// [`FromEnvError::handle`]. You can also call [`from_env`] to
-/// automatically handle the error and exit.
-pub fn try_from_env<T: TopLevelCommand>() -> Result<T, FromEnvError> {
-    let strings: Vec<String> = std::env::args_os()
-        .map(|s| s.into_string())
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(FromEnvError::Utf8)?;
-
-    if strings.is_empty() {
-        eprintln!("No program name, argv is empty");
-        std::process::exit(1)
+/// Returning a `FromEnvError` from `main` prints it as
+/// [`FromEnvError::handle`] does, and exits with the same status.
+impl Termination for FromEnvError {
+    fn report(self) -> ExitCode {
+        ExitCode::from(self.print())
     }
+}
 
-    let cmd = cmd(&strings[0], &strings[0]);
-    let strs: Vec<&str> = strings.iter().map(|s| s.as_str()).collect();
-    T::from_args(&[cmd], &strs[1..]).map_err(|e| FromEnvError::EarlyExit(e, cmd.to_owned()))
+static USAGE_EXIT_CODE: AtomicU8 = AtomicU8::new(2);
+
+/// Sets the status [`FromEnvError::handle`], and so [`from_env`], exit with
+/// on usage errors from now on.
+///
+/// The default is 2, as GNU tools use. Tools following the `sysexits.h`
+/// conventions can use its `EX_USAGE` instead:
+///
+/// ```no_run
+/// # use argh::FromArgs;
+/// #[derive(FromArgs)]
+/// /// Reach new heights.
+/// struct GoUp {
+///     /// how high to go
+///     #[argh(option)]
+///     height: usize,
+/// }
+///
+/// const EX_USAGE: u8 = 64;
+///
+/// argh::set_usage_exit_code(EX_USAGE);
+/// let up: GoUp = argh::from_env();
+/// ```
+pub fn set_usage_exit_code(code: u8) {
+    USAGE_EXIT_CODE.store(code, Ordering::Relaxed);
 }
 
-/// Create a `FromArgs` type from the current process's `env::args`.
+fn usage_exit_code() -> u8 {
+    USAGE_EXIT_CODE.load(Ordering::Relaxed)
+}
+
+/// Exits the current process with `code`, which must be 0 or one of the error
+/// codes `T` declares with `#[argh(error_code(code, "description"))]`.
 ///
-/// This function will exit early from the current process if argument parsing
-/// was unsuccessful or if information like `--help` was requested. Error messages will be printed
-/// to stderr, and `--help` output to stdout.
-pub fn from_env<T: TopLevelCommand>() -> T {
-    try_from_env().unwrap_or_else(|e| e.handle())
+/// ```no_run
+/// # use argh::FromArgs;
+/// #[derive(FromArgs)]
+/// /// Chop down trees.
+/// #[argh(error_code(3, "The blade is too dull."))]
+/// struct Chop {
+///     /// how sharp the blade is
+///     #[argh(option)]
+///     sharpness: u32,
+/// }
+///
+/// let chop: Chop = argh::from_env();
+/// if chop.sharpness < 10 {
+///     eprintln!("The blade is too dull.");
+///     argh::exit_with::<Chop>(3);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if `T` does not declare `code`.
+pub fn exit_with<T: FromArgs>(code: i32) -> ! {
+    check_error_code::<T>(code);
+    std::process::exit(code)
 }
 
-/// Create a `FromArgs` type from the current process's `env::args`.
+/// Returns the [`ExitCode`] for `code`, which must be 0 or one of the error
+/// codes `T` declares with `#[argh(error_code(code, "description"))]`, for
+/// returning from `main`.
 ///
-/// This special cases usages where argh is being used in an environment where cargo is
-/// driving the build. We skip the second env argument.
+/// ```no_run
+/// # use argh::FromArgs;
+/// use std::process::{ExitCode, Termination};
 ///
-/// This function will exit early from the current process if argument parsing
-/// was unsuccessful or if information like `--help` was requested. Error messages will be printed
-/// to stderr, and `--help` output to stdout.
-pub fn cargo_from_env<T: TopLevelCommand>() -> T {
-    let strings: Vec<String> = std::env::args().collect();
-    let cmd = cmd(&strings[1], &strings[1]);
-    let strs: Vec<&str> = strings.iter().map(|s| s.as_str()).collect();
-    T::from_args(&[cmd], &strs[2..]).unwrap_or_else(|early_exit| {
-        std::process::exit(match early_exit.status {
-            Ok(()) => {
+/// #[derive(FromArgs)]
+/// /// Chop down trees.
+/// #[argh(error_code(3, "The blade is too dull."))]
+/// struct Chop {
+///     /// how sharp the blade is
+///     #[argh(option)]
+///     sharpness: u32,
+/// }
+///
+/// fn main() -> ExitCode {
+///     let chop: Chop = match argh::try_from_env() {
+///         Ok(chop) => chop,
+///         Err(e) => return e.report(),
+///     };
+///     if chop.sharpness < 10 {
+///         eprintln!("The blade is too dull.");
+///         return argh::exit_code::<Chop>(3);
+///     }
+///     ExitCode::SUCCESS
+/// }
+/// ```
+///
+/// An `ExitCode` holds a single byte, so codes above 255 are returned as
+/// [`ExitCode::FAILURE`].
+///
+/// # Panics
+///
+/// Panics if `T` does not declare `code`.
+pub fn exit_code<T: FromArgs>(code: i32) -> ExitCode {
+    check_error_code::<T>(code);
+    u8::try_from(code).map_or(ExitCode::FAILURE, ExitCode::from)
+}
+
+fn check_error_code<T: FromArgs>(code: i32) {
+    assert!(
+        code == 0 || T::error_codes().iter().any(|info| info.code == code),
+        "error code {} is not declared by `{}` with `#[argh(error_code(..))]`",
+        code,
+        std::any::type_name::<T>(),
+    );
+}
+
+/// How [`try_from_env`], and so [`from_env`], render parse errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorStyle {
+    /// Print the error message alone, as [`FromArgs::from_args`] renders it.
+    /// This is the default, and suits output which is read by scripts.
+    #[default]
+    Plain,
+    /// Re-print the command line beneath the error message and underline the
+    /// argument it is about, as [`render_caret`] does.
+    Caret,
+}
+
+static CARET_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Sets how [`try_from_env`], and so [`from_env`], render parse errors from
+/// now on.
+///
+/// ```no_run
+/// # use argh::FromArgs;
+/// #[derive(FromArgs)]
+/// /// Reach new heights.
+/// struct GoUp {
+///     /// how high to go
+///     #[argh(option)]
+///     height: usize,
+/// }
+///
+/// argh::set_error_style(argh::ErrorStyle::Caret);
+/// let up: GoUp = argh::from_env();
+/// ```
+///
+/// With the caret style, `goup --height high` prints:
+///
+/// ```text
+/// Error parsing option '--height' with value 'high': invalid digit found in string
+///     goup --height high
+///                   ^^^^
+///
+/// Run goup --help for more information.
+/// ```
+///
+/// Arguments read from response files are re-printed in place of the
+/// `@path` arguments which named them, as they were parsed.
+pub fn set_error_style(style: ErrorStyle) {
+    CARET_ERRORS.store(style == ErrorStyle::Caret, Ordering::Relaxed);
+}
+
+fn error_style() -> ErrorStyle {
+    if CARET_ERRORS.load(Ordering::Relaxed) {
+        ErrorStyle::Caret
+    } else {
+        ErrorStyle::Plain
+    }
+}
+
+/// Renders `error`, from parsing `args` as the command `cmd`, in `style`.
+fn render_error(cmd: &str, args: &[&OsStr], error: ParseError, style: ErrorStyle) -> EarlyExit {
+    match (style, &error) {
+        (ErrorStyle::Caret, ParseError::HelpRequested { .. }) | (ErrorStyle::Plain, _) => {
+            error.into()
+        }
+        (ErrorStyle::Caret, _) => {
+            EarlyExit { output: render_caret(cmd, args, &error), status: Err(()) }
+        }
+    }
+}
+
+/// Create a `FromArgs` type from the current process's `env::args`.
+///
+/// This function will return an error if argument parsing was unsuccessful or
+/// if information like `--help` was requested. The standard behavior in this
+/// case is usually some form of early exit, which can be performed by calling
+/// [`FromEnvError::handle`]. You can also call [`from_env`] to
+/// automatically handle the error and exit.
+#[allow(clippy::result_large_err)]
+pub fn try_from_env<T: TopLevelCommand>() -> Result<T, FromEnvError> {
+    let strings: Vec<OsString> = std::env::args_os().collect();
+
+    if strings.is_empty() {
+        eprintln!("No program name, argv is empty");
+        std::process::exit(1)
+    }
+
+    let program = strings[0].to_string_lossy();
+    let cmd = cmd(&program, &program);
+    let strs: Vec<&OsStr> = strings.iter().map(|s| s.as_os_str()).collect();
+    parse_env_args(cmd, &strs[1..], ParseContext::new(&ProcessEnvironment), error_style())
+}
+
+/// Parses `args`, the process's arguments after the program name, as the
+/// command `cmd` with `context`. Errors are rendered in `style` against the
+/// arguments as they were parsed, after any response files were expanded.
+#[allow(clippy::result_large_err)]
+fn parse_env_args<T: FromArgs>(
+    cmd: &str,
+    args: &[&OsStr],
+    context: ParseContext<'_>,
+    style: ErrorStyle,
+) -> Result<T, FromEnvError> {
+    let parsed_args = RefCell::new(args.iter().map(|&arg| arg.to_owned()).collect());
+    let context = ParseContext { parsed_args: Some(&parsed_args), ..context };
+    T::from_args_with_context(&[OsStr::new(cmd)], args, &context).map_err(|error| {
+        let parsed_args = parsed_args.take();
+        let parsed_args: Vec<&OsStr> = parsed_args.iter().map(|arg| arg.as_os_str()).collect();
+        FromEnvError::EarlyExit(render_error(cmd, &parsed_args, error, style), cmd.to_owned())
+    })
+}
+
+/// Create a `FromArgs` type from the current process's `env::args`.
+///
+/// This function will exit early from the current process if argument parsing
+/// was unsuccessful or if information like `--help` was requested. Error messages will be printed
+/// to stderr, and `--help` output to stdout. Warnings, such as for the use of
+/// `deprecated` options, are also printed to stderr.
+pub fn from_env<T: TopLevelCommand>() -> T {
+    let args = try_from_env().unwrap_or_else(|e| e.handle());
+    print_warnings();
+    args
+}
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the warnings recorded while parsing arguments on the current
+/// thread, and clears them.
+///
+/// A warning is recorded when a `deprecated` option, switch, positional
+/// argument, subcommand or `FromArgValue` variant is used. Parsing still
+/// succeeds, and the application can report these warnings as it sees fit.
+/// [`from_env`] prints them to stderr.
+pub fn take_warnings() -> Vec<String> {
+    WARNINGS.with(|warnings| warnings.take())
+}
+
+// Record a warning to be returned by `take_warnings`.
+#[doc(hidden)]
+pub fn warn(warning: &str) {
+    WARNINGS.with(|warnings| warnings.borrow_mut().push(warning.to_owned()));
+}
+
+fn print_warnings() {
+    for warning in take_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Create a `FromArgs` type from the current process's `env::args`, with the
+/// configuration file at `path` layered beneath the command line and the
+/// environment.
+///
+/// Options which are not provided on the command line or through an
+/// environment variable are read from the file, before falling back to their
+/// `default`. See [`Config`] for the format of the file.
+///
+/// This function will return an error if the file could not be read or
+/// parsed, or in the same cases as [`try_from_env`].
+#[cfg(feature = "config")]
+#[allow(clippy::result_large_err)]
+pub fn try_from_env_with_config<T: TopLevelCommand>(
+    path: impl AsRef<std::path::Path>,
+) -> Result<T, FromEnvError> {
+    let strings: Vec<OsString> = std::env::args_os().collect();
+
+    if strings.is_empty() {
+        eprintln!("No program name, argv is empty");
+        std::process::exit(1)
+    }
+
+    let program = strings[0].to_string_lossy();
+    let cmd = cmd(&program, &program);
+    let config =
+        Config::read(path).map_err(|e| FromEnvError::EarlyExit(e.into(), cmd.to_owned()))?;
+    let strs: Vec<&OsStr> = strings.iter().map(|s| s.as_os_str()).collect();
+    let context = ParseContext { config: Some(&config), ..ParseContext::new(&ProcessEnvironment) };
+    parse_env_args(cmd, &strs[1..], context, error_style())
+}
+
+/// Create a `FromArgs` type from the current process's `env::args`, with the
+/// configuration file at `path` layered beneath the command line and the
+/// environment.
+///
+/// This function will exit early from the current process if the file could
+/// not be read or parsed, or in the same cases as [`from_env`].
+#[cfg(feature = "config")]
+pub fn from_env_with_config<T: TopLevelCommand>(path: impl AsRef<std::path::Path>) -> T {
+    let args = try_from_env_with_config(path).unwrap_or_else(|e| e.handle());
+    print_warnings();
+    args
+}
+
+/// Create a `FromArgs` type from the current process's `env::args`.
+///
+/// This special cases usages where argh is being used in an environment where cargo is
+/// driving the build. We skip the second env argument.
+///
+/// This function will exit early from the current process if argument parsing
+/// was unsuccessful or if information like `--help` was requested. Error messages will be printed
+/// to stderr, and `--help` output to stdout.
+pub fn cargo_from_env<T: TopLevelCommand>() -> T {
+    let strings: Vec<OsString> = std::env::args_os().collect();
+    let program = strings[1].to_string_lossy();
+    let cmd = cmd(&program, &program);
+    let strs: Vec<&OsStr> = strings.iter().map(|s| s.as_os_str()).collect();
+    let args = T::from_args_os(&[OsStr::new(cmd)], &strs[2..]).unwrap_or_else(|early_exit| {
+        std::process::exit(match early_exit.status {
+            Ok(()) => {
                 println!("{}", early_exit.output);
                 0
             }
             Err(()) => {
                 eprintln!("{}\nRun --help for more information.", early_exit.output);
-                1
+                usage_exit_code().into()
             }
         })
-    })
+    });
+    print_warnings();
+    args
 }
 
 /// Types which can be constructed from a single commandline value.
@@ -900,18 +1933,47 @@ where
     }
 }
 
+/// Types which can be constructed from a single commandline value that may
+/// not be valid UTF-8.
+///
+/// Fields of type `OsString` or `PathBuf` in a struct that derives `FromArgs`
+/// are parsed with this trait rather than [`FromArgValue`], so that they
+/// receive the argument as-is when parsed with [`FromArgs::from_args_os`].
+pub trait FromArgOsValue: Sized {
+    /// Construct the type from a commandline value, returning an error string
+    /// on failure.
+    fn from_arg_os_value(value: &OsStr) -> Result<Self, String>;
+}
+
+impl FromArgOsValue for OsString {
+    fn from_arg_os_value(value: &OsStr) -> Result<Self, String> {
+        Ok(value.to_owned())
+    }
+}
+
+impl FromArgOsValue for PathBuf {
+    fn from_arg_os_value(value: &OsStr) -> Result<Self, String> {
+        Ok(PathBuf::from(value))
+    }
+}
+
 // The following items are all used by the generated code, and should not be considered part
 // of this library's public API surface.
 
 #[doc(hidden)]
 pub trait ParseFlag {
     fn set_flag(&mut self, arg: &str);
+    fn clear_flag(&mut self, arg: &str);
 }
 
 impl<T: Flag> ParseFlag for T {
     fn set_flag(&mut self, _arg: &str) {
         <T as Flag>::set_flag(self);
     }
+
+    fn clear_flag(&mut self, _arg: &str) {
+        <T as Flag>::clear_flag(self);
+    }
 }
 
 #[doc(hidden)]
@@ -923,17 +1985,44 @@ impl ParseFlag for RedactFlag {
     fn set_flag(&mut self, arg: &str) {
         self.slot = Some(arg.to_string());
     }
+
+    fn clear_flag(&mut self, arg: &str) {
+        self.slot = Some(arg.to_string());
+    }
+}
+
+// Wraps a flag to record whether it was last set rather than cleared. Used for
+// switches that appear in `conflicts_with`, `requires` or `group` attributes.
+#[doc(hidden)]
+pub struct ParseFlagPresence<'a> {
+    pub flag: &'a mut dyn ParseFlag,
+    pub present: &'a mut bool,
+}
+
+impl ParseFlag for ParseFlagPresence<'_> {
+    fn set_flag(&mut self, arg: &str) {
+        self.flag.set_flag(arg);
+        *self.present = true;
+    }
+
+    fn clear_flag(&mut self, arg: &str) {
+        self.flag.clear_flag(arg);
+        *self.present = false;
+    }
 }
 
 // A trait for for slots that reserve space for a value and know how to parse that value
-// from a command-line `&str` argument.
+// from a command-line `&OsStr` argument.
 //
 // This trait is only implemented for the type `ParseValueSlotTy`. This indirection is
 // necessary to allow abstracting over `ParseValueSlotTy` instances with different
 // generic parameters.
 #[doc(hidden)]
 pub trait ParseValueSlot {
-    fn fill_slot(&mut self, arg: &str, value: &str) -> Result<(), String>;
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), String>;
+
+    // Whether any value has been parsed into the slot.
+    fn is_filled(&self) -> bool;
 }
 
 // The concrete type implementing the `ParseValueSlot` trait.
@@ -945,35 +2034,47 @@ pub struct ParseValueSlotTy<Slot, T> {
     // The slot for a parsed value.
     pub slot: Slot,
     // The function to parse the value from a string
-    pub parse_func: fn(&str, &str) -> Result<T, String>,
+    pub parse_func: fn(&str, &OsStr) -> Result<T, String>,
 }
 
 // `ParseValueSlotTy<Option<T>, T>` is used as the slot for all non-repeating
 // arguments, both optional and required.
 impl<T> ParseValueSlot for ParseValueSlotTy<Option<T>, T> {
-    fn fill_slot(&mut self, arg: &str, value: &str) -> Result<(), String> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), String> {
         if self.slot.is_some() {
-            return Err("duplicate values provided".to_string());
+            return Err(DUPLICATE_VALUES.to_string());
         }
         self.slot = Some((self.parse_func)(arg, value)?);
         Ok(())
     }
+
+    fn is_filled(&self) -> bool {
+        self.slot.is_some()
+    }
 }
 
 // `ParseValueSlotTy<Vec<T>, T>` is used as the slot for repeating arguments.
 impl<T> ParseValueSlot for ParseValueSlotTy<Vec<T>, T> {
-    fn fill_slot(&mut self, arg: &str, value: &str) -> Result<(), String> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), String> {
         self.slot.push((self.parse_func)(arg, value)?);
         Ok(())
     }
+
+    fn is_filled(&self) -> bool {
+        !self.slot.is_empty()
+    }
 }
 
 // `ParseValueSlotTy<Option<Vec<T>>, T>` is used as the slot for optional repeating arguments.
 impl<T> ParseValueSlot for ParseValueSlotTy<Option<Vec<T>>, T> {
-    fn fill_slot(&mut self, arg: &str, value: &str) -> Result<(), String> {
+    fn fill_slot(&mut self, arg: &str, value: &OsStr) -> Result<(), String> {
         self.slot.get_or_insert_with(Vec::new).push((self.parse_func)(arg, value)?);
         Ok(())
     }
+
+    fn is_filled(&self) -> bool {
+        self.slot.is_some()
+    }
 }
 
 /// A type which can be the receiver of a `Flag`.
@@ -985,6 +2086,17 @@ pub trait Flag {
 
     /// Sets the flag. This function is called when the flag is provided.
     fn set_flag(&mut self);
+
+    /// Clears the flag. This function is called when the negated form of a
+    /// `negatable` switch, such as `--no-foo`, is provided.
+    ///
+    /// By default, this resets the flag to `Flag::default()`.
+    fn clear_flag(&mut self)
+    where
+        Self: Sized,
+    {
+        *self = <Self as Flag>::default();
+    }
 }
 
 impl Flag for bool {
@@ -1004,6 +2116,10 @@ impl Flag for Option<bool> {
     fn set_flag(&mut self) {
         *self = Some(true);
     }
+
+    fn clear_flag(&mut self) {
+        *self = Some(false);
+    }
 }
 
 macro_rules! impl_flag_for_integers {
@@ -1031,206 +2147,1563 @@ impl_flag_for_integers![u8, u16, u32, u64, u128, i8, i16, i32, i64, i128,];
 /// `parse_positionals`: Helper to parse positional arguments.
 /// `parse_subcommand`: Helper to parse a subcommand.
 /// `help_func`: Generate a help message.
+/// `errors`: The errors of the command, which may be collected rather than
+/// returned.
 #[doc(hidden)]
 pub fn parse_struct_args(
     cmd_name: &[&str],
-    args: &[&str],
+    args: &[&OsStr],
     mut parse_options: ParseStructOptions<'_>,
     mut parse_positionals: ParseStructPositionals<'_>,
     mut parse_subcommand: Option<ParseStructSubCommand<'_>>,
     help_func: &dyn Fn() -> String,
-) -> Result<(), EarlyExit> {
+    errors: &mut CollectedErrors,
+) -> Result<(), ParseError> {
     let mut help = false;
     let mut remaining_args = args;
     let mut positional_index = 0;
     let mut options_ended = false;
+    let mut parsed_subcommand = false;
 
     'parse_args: while let Some(&next_arg) = remaining_args.first() {
+        let index = args.len() - remaining_args.len();
         remaining_args = &remaining_args[1..];
-        if (parse_options.help_triggers.contains(&next_arg)) && !options_ended {
+        let next_str = next_arg.to_str();
+        if next_str.is_some_and(|arg| parse_options.help_triggers.contains(&arg)) && !options_ended
+        {
             help = true;
             continue;
         }
 
-        if next_arg.starts_with('-') && !options_ended {
+        if next_arg.as_encoded_bytes().starts_with(b"-") && !options_ended {
             if next_arg == "--" {
+                // The arguments after `--` are all collected by the
+                // `#[argh(trailing)]` field, if there is one.
+                if let Some(trailing) = &mut parse_positionals.trailing {
+                    for (i, &arg) in remaining_args.iter().enumerate() {
+                        errors.check(trailing.parse(index + 1 + i, arg))?;
+                    }
+                    break 'parse_args;
+                }
+
                 options_ended = true;
                 continue;
             }
 
             if help {
-                return Err("Trailing arguments are not allowed after `help`.".to_string().into());
+                return Err(ParseError::ArgumentAfterHelp { index });
+            }
+
+            // Options of neither this command nor its parents are left to
+            // the default subcommand, if there is one, or else collected by
+            // the `#[argh(unknown)]` field, if there is one.
+            if !parse_options.accepts(next_arg) {
+                if parse_default_subcommand(
+                    &mut parse_subcommand,
+                    &mut parse_options,
+                    errors,
+                    cmd_name,
+                    index,
+                    &args[index..],
+                )? {
+                    parsed_subcommand = true;
+                    break 'parse_args;
+                }
+
+                if let Some(unknown) = &mut parse_options.unknown {
+                    errors.check(unknown.parse(index, next_arg, &mut remaining_args))?;
+                    continue;
+                }
             }
 
-            parse_options.parse(next_arg, &mut remaining_args)?;
+            parse_option(&mut parse_options, errors, index, next_arg, &mut remaining_args)?;
             continue;
         }
 
-        if let Some(ref mut parse_subcommand) = parse_subcommand {
-            if parse_subcommand.parse(help, cmd_name, next_arg, remaining_args)? {
-                // Unset `help`, since we handled it in the subcommand
-                help = false;
+        if let (Some(parse_subcommand), Some(next_str)) = (&mut parse_subcommand, next_str) {
+            let global_args = &parse_options.globals;
+            match parse_subcommand.parse(
+                help,
+                cmd_name,
+                index,
+                next_str,
+                remaining_args,
+                global_args,
+            ) {
+                Ok(false) => {}
+                parsed => {
+                    finish_subcommand(&mut parse_options, errors, parsed.map(drop))?;
+                    parsed_subcommand = true;
+
+                    // Unset `help`, since we handled it in the subcommand
+                    help = false;
+                    break 'parse_args;
+                }
+            }
+        }
+
+        // Arguments which this command has no positional argument left for
+        // are left to the default subcommand, if there is one, unless they
+        // look like a mistyped subcommand.
+        if !help && positional_index >= parse_positionals.positionals.len() {
+            if let (Some(parse_subcommand), Some(next_str), false) =
+                (&parse_subcommand, next_str, options_ended)
+            {
+                if parse_subcommand.default.is_some() {
+                    if let Some(suggestion) = suggest(next_str, parse_subcommand.names()) {
+                        return Err(ParseError::UnknownArgument {
+                            index,
+                            arg: next_str.to_owned(),
+                            suggestion: Some(suggestion.to_owned()),
+                            no_options: false,
+                        });
+                    }
+                }
+            }
+
+            // After `--`, the default subcommand is given a `--` of its own,
+            // standing just before the arguments it is given.
+            let ended_args;
+            let (offset, default_args) = if options_ended {
+                ended_args = [&[OsStr::new("--")], &args[index..]].concat();
+                (index - 1, &ended_args[..])
+            } else {
+                (index, &args[index..])
+            };
+            if parse_default_subcommand(
+                &mut parse_subcommand,
+                &mut parse_options,
+                errors,
+                cmd_name,
+                offset,
+                default_args,
+            )? {
+                parsed_subcommand = true;
                 break 'parse_args;
             }
         }
 
-        options_ended |= parse_positionals.parse(&mut positional_index, next_arg)?;
+        let position = positional_index;
+        match parse_positionals.parse(&mut positional_index, index, next_arg) {
+            Ok(ended) => options_ended |= ended,
+            Err(mut e) => {
+                match &mut e {
+                    ParseError::InvalidPositional { .. } => {
+                        errors.failed_positionals.push(position);
+                    }
+                    // The argument may be a mistyped subcommand or help trigger.
+                    ParseError::UnknownArgument { arg, suggestion, .. } => {
+                        let subcommands = parse_subcommand
+                            .iter()
+                            .flat_map(|sub| sub.names())
+                            .map(|name| -> &str { name });
+                        let help_triggers = parse_options.help_triggers.iter().copied();
+                        *suggestion =
+                            suggest(arg, subcommands.chain(help_triggers)).map(str::to_owned);
+                    }
+                    _ => {}
+                }
+                errors.check(Err(e))?
+            }
+        }
     }
 
     if help {
-        Err(EarlyExit { output: help_func(), status: Ok(()) })
-    } else {
-        Ok(())
+        return Err(ParseError::HelpRequested { output: help_func() });
     }
-}
-
-#[doc(hidden)]
-pub struct ParseStructOptions<'a> {
-    /// A mapping from option string literals to the entry
-    /// in the output table. This may contain multiple entries mapping to
-    /// the same location in the table if both a short and long version
-    /// of the option exist (`-z` and `--zoo`).
-    pub arg_to_slot: &'static [(&'static str, usize)],
 
-    /// The storage for argument output data.
-    pub slots: &'a mut [ParseStructOption<'a>],
+    if !parsed_subcommand {
+        parse_default_subcommand(
+            &mut parse_subcommand,
+            &mut parse_options,
+            errors,
+            cmd_name,
+            args.len(),
+            &[],
+        )?;
+    }
 
-    /// help triggers is a list of strings that trigger printing of help
-    pub help_triggers: &'a [&'a str],
+    Ok(())
 }
 
-impl ParseStructOptions<'_> {
-    /// Parse a commandline option.
-    ///
-    /// `arg`: the current option argument being parsed (e.g. `--foo`).
-    /// `remaining_args`: the remaining command line arguments. This slice
-    /// will be advanced forwards if the option takes a value argument.
-    fn parse(&mut self, arg: &str, remaining_args: &mut &[&str]) -> Result<(), String> {
-        let pos = self
-            .arg_to_slot
-            .iter()
-            .find_map(|&(name, pos)| if name == arg { Some(pos) } else { None })
-            .ok_or_else(|| unrecognized_argument(arg, self.arg_to_slot, self.help_triggers))?;
-
-        match self.slots[pos] {
-            ParseStructOption::Flag(ref mut b) => b.set_flag(arg),
-            ParseStructOption::Value(ref mut pvs) => {
-                let value = remaining_args
-                    .first()
-                    .ok_or_else(|| ["No value provided for option '", arg, "'.\n"].concat())?;
-                *remaining_args = &remaining_args[1..];
-                pvs.fill_slot(arg, value).map_err(|s| {
-                    ["Error parsing option '", arg, "' with value '", value, "': ", &s, "\n"]
-                        .concat()
-                })?;
-            }
+// Parses the default subcommand of a command, if it has one, from `args`,
+// which start at `index`. Returns whether it was parsed.
+fn parse_default_subcommand(
+    parse_subcommand: &mut Option<ParseStructSubCommand<'_>>,
+    parse_options: &mut ParseStructOptions<'_>,
+    errors: &mut CollectedErrors,
+    cmd_name: &[&str],
+    index: usize,
+    args: &[&OsStr],
+) -> Result<bool, ParseError> {
+    let Some(parse_subcommand) = parse_subcommand else { return Ok(false) };
+    match parse_subcommand.parse_default(cmd_name, index, args, &parse_options.globals) {
+        Ok(false) => Ok(false),
+        parsed => {
+            finish_subcommand(parse_options, errors, parsed.map(drop))?;
+            Ok(true)
         }
-
-        Ok(())
     }
 }
 
-fn unrecognized_argument(
-    given: &str,
-    arg_to_slot: &[(&str, usize)],
-    extra_suggestions: &[&str],
-) -> String {
-    // get the list of available arguments
-    let available = arg_to_slot
-        .iter()
-        .map(|(name, _pos)| *name)
-        .chain(extra_suggestions.iter().copied())
-        .collect::<Vec<&str>>();
+// Records the result of parsing a subcommand, then parses the global options
+// which were given after it.
+fn finish_subcommand(
+    parse_options: &mut ParseStructOptions<'_>,
+    errors: &mut CollectedErrors,
+    parsed: Result<(), ParseError>,
+) -> Result<(), ParseError> {
+    errors.failed_subcommand = parsed.is_err();
+    errors.check(parsed)?;
 
-    if available.is_empty() {
-        return format!("Unrecognized argument: \"{}\"\n", given);
+    let global_args = parse_options.globals.take_args();
+    let args: Vec<&OsStr> = global_args.iter().map(|(_, arg)| &**arg).collect();
+    let mut remaining_args = &args[..];
+    while let Some((&arg, rest)) = remaining_args.split_first() {
+        let (index, _) = global_args[args.len() - remaining_args.len()];
+        remaining_args = rest;
+        parse_option(parse_options, errors, index, arg, &mut remaining_args)?;
     }
+    Ok(())
+}
 
-    #[cfg(feature = "fuzzy_search")]
+// Parses the option or group of short options `arg`, at `index`, recording
+// the option as failed if its value could not be parsed.
+fn parse_option(
+    parse_options: &mut ParseStructOptions<'_>,
+    errors: &mut CollectedErrors,
+    index: usize,
+    arg: &OsStr,
+    remaining_args: &mut &[&OsStr],
+) -> Result<(), ParseError> {
+    let parsed = parse_options.parse(index, arg, remaining_args);
+    if let Err(
+        ParseError::MissingValue { option: name, .. } | ParseError::InvalidValue { arg: name, .. },
+    ) = &parsed
     {
-        let suggestions = fuzzy_search_best_n(given, &available, 1);
-        return format!(
-            "Unrecognized argument: \"{}\". Did you mean \"{}\"?\n",
-            given, suggestions[0].0
-        );
+        let slot = parse_options.arg_to_slot.iter().find(|(arg, _)| arg == name);
+        errors.failed_options.extend(slot.map(|&(_, pos)| pos));
     }
-
-    #[cfg(not(feature = "fuzzy_search"))]
-    ["Unrecognized argument: ", given, "\n"].concat()
+    errors.check(parsed)
 }
 
-// `--` or `-` options, including a mutable reference to their value.
+// The errors of a command. Commands with the `#[argh(collect_errors)]`
+// attribute, and their subcommands, collect them to report together, while
+// other commands return the first one.
+//
+// The arguments which were provided but could not be parsed are tracked too,
+// so that they are not also reported as missing.
 #[doc(hidden)]
-pub enum ParseStructOption<'a> {
-    // A flag which is set to `true` when provided.
-    Flag(&'a mut dyn ParseFlag),
-    // A value which is parsed from the string following the `--` argument,
-    // e.g. `--foo bar`.
-    Value(&'a mut dyn ParseValueSlot),
+pub struct CollectedErrors {
+    collect: bool,
+    errors: Vec<ParseError>,
+    // The slots of the options whose values could not be parsed.
+    failed_options: Vec<usize>,
+    // The positions of the positional arguments which could not be parsed.
+    failed_positionals: Vec<usize>,
+    failed_subcommand: bool,
 }
 
-#[doc(hidden)]
-pub struct ParseStructPositionals<'a> {
-    pub positionals: &'a mut [ParseStructPositional<'a>],
-    pub last_is_repeating: bool,
-    pub last_is_greedy: bool,
-}
+impl CollectedErrors {
+    #[doc(hidden)]
+    pub fn new(collect: bool) -> Self {
+        Self {
+            collect,
+            errors: Vec::new(),
+            failed_options: Vec::new(),
+            failed_positionals: Vec::new(),
+            failed_subcommand: false,
+        }
+    }
 
-impl ParseStructPositionals<'_> {
-    /// Parse the next positional argument.
-    ///
-    /// `arg`: the argument supplied by the user.
-    ///
-    /// Returns true if non-positional argument parsing should stop
-    /// after this one.
-    fn parse(&mut self, index: &mut usize, arg: &str) -> Result<bool, EarlyExit> {
-        if *index < self.positionals.len() {
-            self.positionals[*index].parse(arg)?;
+    // Whether the value of the option in slot `pos` could not be parsed.
+    #[doc(hidden)]
+    pub fn option_failed(&self, pos: usize) -> bool {
+        self.failed_options.contains(&pos)
+    }
 
-            if self.last_is_repeating && *index == self.positionals.len() - 1 {
-                // Don't increment position if we're at the last arg
-                // *and* the last arg is repeating. If it's also remainder,
-                // halt non-option processing after this.
-                Ok(self.last_is_greedy)
-            } else {
-                // If it is repeating, though, increment the index and continue
-                // processing options.
-                *index += 1;
-                Ok(false)
-            }
-        } else {
-            Err(EarlyExit { output: unrecognized_arg(arg), status: Err(()) })
+    // Whether the positional argument at `position` could not be parsed.
+    #[doc(hidden)]
+    pub fn positional_failed(&self, position: usize) -> bool {
+        self.failed_positionals.contains(&position)
+    }
+
+    // Whether a subcommand was provided but its arguments could not be parsed.
+    #[doc(hidden)]
+    pub fn subcommand_failed(&self) -> bool {
+        self.failed_subcommand
+    }
+
+    // Records the error of `result`, if any. Returns it instead if errors are
+    // not being collected, or if it is a request for help.
+    #[doc(hidden)]
+    pub fn check(&mut self, result: Result<(), ParseError>) -> Result<(), ParseError> {
+        let error = match result {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        if !self.collect || matches!(error, ParseError::HelpRequested { .. }) {
+            return Err(error);
+        }
+        match error {
+            ParseError::Multiple(errors) => self.errors.extend(errors),
+            error => self.errors.push(error),
+        }
+        Ok(())
+    }
+
+    // Returns the recorded errors, if any, ordered by the index of the
+    // argument they are about.
+    #[doc(hidden)]
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        // The sort is stable, so errors without an index keep the order they
+        // were recorded in after the others.
+        self.errors.sort_by_key(|error| (error.index().is_none(), error.index()));
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.remove(0)),
+            _ => Err(ParseError::Multiple(self.errors)),
         }
     }
 }
 
+/// Fill an option's slot from the environment variable `var` if it was not
+/// provided on the command line.
+///
+/// `name`: the long name of the option.
+/// `delimiter`: the `delimiter` of the option, which the value is split at.
 #[doc(hidden)]
-pub struct ParseStructPositional<'a> {
-    // The positional's name
-    pub name: &'static str,
+pub fn parse_env_fallback(
+    slot: &mut dyn ParseValueSlot,
+    name: &str,
+    var: &str,
+    delimiter: Option<u8>,
+    env: &dyn Environment,
+) -> Result<(), ParseError> {
+    if slot.is_filled() {
+        return Ok(());
+    }
 
-    // The function to parse the positional.
-    pub slot: &'a mut dyn ParseValueSlot,
+    if let Some(value) = env.var(var) {
+        for element in delimited_elements(&value, delimiter) {
+            slot.fill_slot(name, &element).map_err(|source| ParseError::InvalidEnvValue {
+                var: var.to_owned(),
+                value: element.to_string_lossy().into_owned(),
+                source,
+            })?;
+        }
+    }
+
+    Ok(())
 }
 
-impl ParseStructPositional<'_> {
-    /// Parse a positional argument.
-    ///
-    /// `arg`: the argument supplied by the user.
-    fn parse(&mut self, arg: &str) -> Result<(), EarlyExit> {
-        self.slot.fill_slot("", arg).map_err(|s| {
-            [
-                "Error parsing positional argument '",
-                self.name,
-                "' with value '",
-                arg,
-                "': ",
-                &s,
-                "\n",
-            ]
-            .concat()
-            .into()
-        })
+/// Fill an option's slot from the environment variable named after it under
+/// `env_prefix`, if there is one and the option was not provided on the
+/// command line.
+///
+/// `name`: the long name of the option.
+#[doc(hidden)]
+pub fn parse_prefixed_env_fallback(
+    slot: &mut dyn ParseValueSlot,
+    name: &str,
+    env_prefix: Option<&str>,
+    delimiter: Option<u8>,
+    env: &dyn Environment,
+) -> Result<(), ParseError> {
+    match env_prefix {
+        Some(env_prefix) => {
+            let var = argh_shared::env_var_name(env_prefix, name);
+            parse_env_fallback(slot, name, &var, delimiter, env)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Fill an option's slot from the configuration file of `context`, if there
+/// is one and the option was not provided otherwise.
+///
+/// `name`: the long name of the option.
+/// `delimiter`: the `delimiter` of the option, which each value is split at.
+/// `cmd_name`: the full command name, starting with the program name.
+#[cfg(feature = "config")]
+#[doc(hidden)]
+pub fn parse_config_fallback(
+    slot: &mut dyn ParseValueSlot,
+    name: &str,
+    delimiter: Option<u8>,
+    cmd_name: &[&str],
+    context: &ParseContext<'_>,
+) -> Result<(), ParseError> {
+    let Some(config) = context.config else { return Ok(()) };
+    if slot.is_filled() {
+        return Ok(());
+    }
+    let command = cmd_name.get(1..).unwrap_or_default();
+    config.fill(slot, command, name, delimiter).map_err(ParseError::Message)
+}
+
+// The state a command is parsed with: the environment, along with what a
+// subcommand or `#[argh(flatten)]` field inherits from its parent command.
+#[doc(hidden)]
+pub struct ParseContext<'a> {
+    env: &'a dyn Environment,
+    // The `env_prefix` inherited from the parent command, if any.
+    env_prefix: Option<String>,
+    // Whether the parent command collects its errors, as set by
+    // `#[argh(collect_errors)]`.
+    collect_errors: bool,
+    // The global options of the parent command, and through them those of
+    // its parents.
+    global_args: Option<&'a GlobalArgs<'a>>,
+    // The configuration file options fall back to, if any.
+    #[cfg(feature = "config")]
+    config: Option<&'a Config>,
+    // The arguments of the top-level command as they are parsed, with any
+    // response files expanded, if they are being recorded.
+    parsed_args: Option<&'a RefCell<Vec<OsString>>>,
+    // The index in `parsed_args` of the first argument of the command.
+    offset: usize,
+}
+
+impl<'a> ParseContext<'a> {
+    // The context of a top-level command, which inherits nothing.
+    #[doc(hidden)]
+    pub fn new(env: &'a dyn Environment) -> Self {
+        Self {
+            env,
+            env_prefix: None,
+            collect_errors: false,
+            global_args: None,
+            #[cfg(feature = "config")]
+            config: None,
+            parsed_args: None,
+            offset: 0,
+        }
+    }
+
+    // The context passed on to a subcommand, which inherits the `env_prefix`
+    // of its parent with the subcommand's name appended.
+    //
+    // `command`: the full command name, ending with the subcommand.
+    // `offset`: the index of the first argument of the subcommand in the
+    // arguments of the parent command.
+    // `global_args`: the global options of the parent command.
+    #[doc(hidden)]
+    pub fn subcommand<'b>(
+        &'b self,
+        env_prefix: Option<&str>,
+        collect_errors: bool,
+        command: &[&str],
+        offset: usize,
+        global_args: &'b GlobalArgs<'b>,
+    ) -> ParseContext<'b> {
+        let env_prefix = env_prefix.zip(command.last()).map(|(env_prefix, subcommand)| {
+            [&argh_shared::env_var_name(env_prefix, subcommand), "_"].concat()
+        });
+        ParseContext {
+            env: self.env,
+            env_prefix,
+            collect_errors,
+            global_args: Some(global_args),
+            #[cfg(feature = "config")]
+            config: self.config,
+            parsed_args: self.parsed_args,
+            offset: self.offset + offset,
+        }
+    }
+
+    // The context passed on to a `#[argh(flatten)]` field, which shares the
+    // `env_prefix` of the struct. Its arguments are some of those of the
+    // struct, so they are not recorded again.
+    fn flattened(&self, env_prefix: Option<&str>, collect_errors: bool) -> ParseContext<'a> {
+        ParseContext {
+            env: self.env,
+            env_prefix: env_prefix.map(str::to_owned),
+            collect_errors,
+            global_args: None,
+            #[cfg(feature = "config")]
+            config: self.config,
+            parsed_args: None,
+            offset: 0,
+        }
+    }
+
+    // Expands the response files in `args`, the arguments of the command, and
+    // records the arguments which are parsed in their place.
+    #[doc(hidden)]
+    pub fn expand_response_files(&self, args: &[&OsStr]) -> Result<Vec<OsString>, ParseError> {
+        let expanded = expand_response_files(args)?;
+        if let Some(parsed_args) = self.parsed_args {
+            if expanded != args {
+                let mut parsed_args = parsed_args.borrow_mut();
+                parsed_args.truncate(self.offset);
+                parsed_args.extend(expanded.iter().cloned());
+            }
+        }
+        Ok(expanded)
+    }
+
+    #[doc(hidden)]
+    pub fn env(&self) -> &'a dyn Environment {
+        self.env
+    }
+
+    #[doc(hidden)]
+    pub fn env_prefix(&self) -> Option<&str> {
+        self.env_prefix.as_deref()
+    }
+
+    #[doc(hidden)]
+    pub fn collect_errors(&self) -> bool {
+        self.collect_errors
+    }
+
+    #[doc(hidden)]
+    pub fn global_args(&self) -> Option<&GlobalArgs<'_>> {
+        self.global_args
+    }
+}
+
+// Sets the `env_prefix` of subcommands which don't declare their own, as
+// `ParseContext::subcommand` does while parsing.
+#[doc(hidden)]
+pub fn inherit_env_prefix(commands: &mut [SubCommandInfo], env_prefix: Option<&str>) {
+    let Some(env_prefix) = env_prefix else { return };
+    for subcommand in commands {
+        if subcommand.command.env_prefix.is_none() {
+            let inherited = [&argh_shared::env_var_name(env_prefix, subcommand.name), "_"].concat();
+            inherit_env_prefix(&mut subcommand.command.commands, Some(&inherited));
+            subcommand.command.env_prefix = Some(inherited.into());
+        }
+    }
+}
+
+// Converts command line arguments to `&OsStr`s.
+#[doc(hidden)]
+pub fn str_args_to_os<'a>(args: &[&'a str]) -> Vec<&'a OsStr> {
+    args.iter().map(|arg| OsStr::new(*arg)).collect()
+}
+
+// Converts command line arguments to `&str`s, failing if any of them are not
+// valid UTF-8.
+#[doc(hidden)]
+pub fn os_args_to_str<'a>(args: &[&'a OsStr]) -> Result<Vec<&'a str>, ParseError> {
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| {
+            arg.to_str().ok_or_else(|| ParseError::InvalidUtf8 {
+                index,
+                arg: arg.to_string_lossy().into_owned(),
+            })
+        })
+        .collect()
+}
+
+// Converts command line arguments to strings, replacing anything which is
+// not valid UTF-8.
+#[doc(hidden)]
+pub fn os_args_to_lossy<'a>(args: &[&'a OsStr]) -> Vec<Cow<'a, str>> {
+    args.iter().map(|arg| arg.to_string_lossy()).collect()
+}
+
+// Converts a command line value to a `&str` so it can be parsed with
+// `FromArgValue`.
+#[doc(hidden)]
+pub fn os_value_to_str(value: &OsStr) -> Result<&str, String> {
+    value.to_str().ok_or_else(|| "invalid utf8".to_string())
+}
+
+#[doc(hidden)]
+pub struct ParseStructOptions<'a> {
+    /// A mapping from option string literals to the entry
+    /// in the output table. This may contain multiple entries mapping to
+    /// the same location in the table if both a short and long version
+    /// of the option exist (`-z` and `--zoo`).
+    pub arg_to_slot: &'a [(&'static str, usize)],
+
+    /// The storage for argument output data.
+    pub slots: &'a mut [ParseStructOption<'a>],
+
+    /// help triggers is a list of strings that trigger printing of help
+    pub help_triggers: &'a [&'a str],
+
+    /// The global options of the command, which its subcommands pass back.
+    pub globals: GlobalArgs<'a>,
+
+    /// The field collecting the options which the command does not
+    /// recognize, if it has one.
+    pub unknown: Option<ParseStructUnknown<'a>>,
+}
+
+/// The `#[argh(unknown)]` field of a command.
+#[doc(hidden)]
+pub struct ParseStructUnknown<'a> {
+    /// The field, which is filled with each unknown option.
+    pub field: ParseStructPositional<'a>,
+
+    /// Whether an unknown option without an attached value also takes the
+    /// argument after it as its value, if that does not start with `-`.
+    pub with_values: bool,
+}
+
+impl ParseStructUnknown<'_> {
+    /// Collect the unknown option `arg`, at `index`, and its value if it
+    /// takes one from `remaining_args`.
+    fn parse(
+        &mut self,
+        index: usize,
+        arg: &OsStr,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), ParseError> {
+        self.field.parse(index, arg)?;
+
+        // Values can be attached to long options as in `--name=value`, and
+        // to short options as in `-n5`.
+        let bytes = arg.as_encoded_bytes();
+        let has_attached_value =
+            if bytes.starts_with(b"--") { bytes.contains(&b'=') } else { bytes.len() > 2 };
+        if let (true, false, Some((&value, rest))) =
+            (self.with_values, has_attached_value, remaining_args.split_first())
+        {
+            if !value.as_encoded_bytes().starts_with(b"-") {
+                *remaining_args = rest;
+                self.field.parse(index + 1, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ParseStructOptions<'_> {
+    /// Whether `arg` names an option of the command, or a global option of
+    /// one of its parents. Groups of short options are named by their first
+    /// option.
+    fn accepts(&self, arg: &OsStr) -> bool {
+        let arg = utf8_prefix(arg.as_encoded_bytes());
+        let name = if arg.starts_with("--") {
+            arg.split('=').next().unwrap_or(arg)
+        } else {
+            arg.char_indices().nth(2).map_or(arg, |(i, _)| &arg[..i])
+        };
+        self.arg_to_slot.iter().any(|&(slot_name, _)| slot_name == name)
+            || self.globals.inherited(name).is_some()
+    }
+
+    /// Parse a commandline option.
+    ///
+    /// `index`: the index of `arg` in the command line arguments.
+    /// `arg`: the current option argument being parsed (e.g. `--foo`,
+    /// `--foo=bar`, or a group of short options like `-vq` or `-n5`).
+    /// `remaining_args`: the remaining command line arguments. This slice
+    /// will be advanced forwards if the option takes a value argument that
+    /// was not attached to `arg`.
+    fn parse(
+        &mut self,
+        index: usize,
+        arg: &OsStr,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), ParseError> {
+        // Long options may carry their value inline, as in `--foo=bar`. Only
+        // the name needs to be valid UTF-8.
+        let bytes = arg.as_encoded_bytes();
+        let (name, inline_value) = match bytes.iter().position(|&b| b == b'=') {
+            Some(i) if bytes.starts_with(b"--") => (&bytes[..i], Some(os_str_after(arg, i + 1))),
+            _ => (bytes, None),
+        };
+
+        let name_str = std::str::from_utf8(name).ok();
+        let slot = name_str
+            .and_then(|name| self.arg_to_slot.iter().find(|&&(slot_name, _)| slot_name == name));
+        // The global options of parent commands are passed back to them.
+        if let Some(name) = name_str.filter(|_| slot.is_none()) {
+            if let Some(parent) = self.globals.inherited(name) {
+                parent.push(index, name, inline_value, remaining_args);
+                return Ok(());
+            }
+        }
+        match slot {
+            Some(&(name, pos)) => self.parse_slot(index, name, pos, inline_value, remaining_args),
+            None if is_short_group(bytes) => self.parse_short_group(index, arg, remaining_args),
+            None => Err(unrecognized_argument(
+                index,
+                &String::from_utf8_lossy(name),
+                self.arg_to_slot,
+                self.globals.flags,
+                self.help_triggers,
+            )),
+        }
+    }
+
+    /// Parse a group of short options like `-vq` or `-n5`.
+    ///
+    /// Each character is looked up as its own short option. Switches are set
+    /// as they are encountered, and the first option which takes a value
+    /// consumes the rest of the group, or the next argument if the group
+    /// ends with it.
+    fn parse_short_group(
+        &mut self,
+        index: usize,
+        arg: &OsStr,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), ParseError> {
+        // Only the leading UTF-8 part of the group can name short options. The
+        // rest can only be the value of one of them.
+        let group = utf8_prefix(arg.as_encoded_bytes());
+        let (arg_to_slot, flags, help_triggers) =
+            (self.arg_to_slot, self.globals.flags, self.help_triggers);
+        let unrecognized =
+            |given: &str| unrecognized_argument(index, given, arg_to_slot, flags, help_triggers);
+
+        for (i, c) in group.char_indices().skip(1) {
+            let short = &group[i..i + c.len_utf8()];
+            let slot =
+                self.arg_to_slot.iter().find(|&&(name, _)| name.strip_prefix('-') == Some(short));
+            let Some(&(name, pos)) = slot else {
+                let name = ["-", short].concat();
+                let parent = self.globals.inherited(&name).ok_or_else(|| {
+                    // If not even the first character is known, report the
+                    // whole argument rather than a single short option.
+                    if i == 1 {
+                        unrecognized(&arg.to_string_lossy())
+                    } else {
+                        unrecognized(&name)
+                    }
+                })?;
+                if parent.takes_value(&name) {
+                    let rest = os_str_after(arg, i + c.len_utf8());
+                    let inline_value = if rest.is_empty() { None } else { Some(rest) };
+                    parent.push(index, &name, inline_value, remaining_args);
+                    return Ok(());
+                }
+                parent.push(index, &name, None, remaining_args);
+                continue;
+            };
+
+            let takes_value = match &self.slots[pos] {
+                ParseStructOption::Value(_) | ParseStructOption::DelimitedValue(..) => true,
+                ParseStructOption::Flattened(args) => args.takes_value(name),
+                _ => false,
+            };
+            if takes_value {
+                let rest = os_str_after(arg, i + c.len_utf8());
+                let inline_value = if rest.is_empty() { None } else { Some(rest) };
+                return self.parse_slot(index, name, pos, inline_value, remaining_args);
+            }
+            self.parse_slot(index, name, pos, None, remaining_args)?;
+        }
+
+        if group.len() < arg.len() {
+            return Err(unrecognized(&arg.to_string_lossy()));
+        }
+
+        Ok(())
+    }
+
+    /// Fill the slot at `pos` for the option `name`.
+    ///
+    /// `index`: the index of the argument holding the option.
+    /// `inline_value`: the value attached to the option, if any. Options
+    /// without one take their value from `remaining_args`.
+    fn parse_slot(
+        &mut self,
+        index: usize,
+        name: &str,
+        pos: usize,
+        inline_value: Option<&OsStr>,
+        remaining_args: &mut &[&OsStr],
+    ) -> Result<(), ParseError> {
+        let unexpected_value = || ParseError::UnexpectedValue { index, switch: name.to_owned() };
+        let (pvs, delimiter) = match self.slots[pos] {
+            ParseStructOption::Flag(ref mut b) => {
+                if inline_value.is_some() {
+                    return Err(unexpected_value());
+                }
+                b.set_flag(name);
+                return Ok(());
+            }
+            ParseStructOption::NegatableFlag(ref mut b, negated) => {
+                if inline_value.is_some() {
+                    return Err(unexpected_value());
+                }
+                if name == negated {
+                    b.clear_flag(name)
+                } else {
+                    b.set_flag(name)
+                }
+                return Ok(());
+            }
+            ParseStructOption::OptionalValue(ref mut pvs, default_missing_value) => {
+                let value = inline_value.unwrap_or_else(|| OsStr::new(default_missing_value));
+                return fill_option(&mut **pvs, index, name, value);
+            }
+            ParseStructOption::Flattened(ref mut args) => {
+                args.push(index, name, inline_value, remaining_args);
+                return Ok(());
+            }
+            ParseStructOption::Value(ref mut pvs) => (&mut **pvs, None),
+            ParseStructOption::DelimitedValue(ref mut pvs, delimiter) => {
+                (&mut **pvs, Some(delimiter))
+            }
+        };
+
+        let (value_index, value) = if let Some(value) = inline_value {
+            (index, value)
+        } else {
+            let value = remaining_args
+                .first()
+                .ok_or_else(|| ParseError::MissingValue { index, option: name.to_owned() })?;
+            *remaining_args = &remaining_args[1..];
+            (index + 1, *value)
+        };
+
+        match delimiter {
+            Some(delimiter) => {
+                for element in split_delimited(value, delimiter) {
+                    fill_option(pvs, value_index, name, &element)?;
+                }
+                Ok(())
+            }
+            None => fill_option(pvs, value_index, name, value),
+        }
+    }
+}
+
+/// The error `ParseValueSlot::fill_slot` returns when a slot which only takes
+/// a single value is filled twice.
+const DUPLICATE_VALUES: &str = "duplicate values provided";
+
+/// Parse `value`, held by the argument at `index`, into the slot for the
+/// option `name`.
+fn fill_option(
+    pvs: &mut dyn ParseValueSlot,
+    index: usize,
+    name: &str,
+    value: &OsStr,
+) -> Result<(), ParseError> {
+    pvs.fill_slot(name, value).map_err(|source| {
+        let (arg, value) = (name.to_owned(), value.to_string_lossy().into_owned());
+        if source == DUPLICATE_VALUES {
+            ParseError::DuplicateValue { index, arg, value }
+        } else {
+            ParseError::InvalidValue { index, arg, value, source }
+        }
+    })
+}
+
+/// The elements of a value for an option with the `delimiter`, if any, or
+/// else the value alone.
+fn delimited_elements(value: &OsStr, delimiter: Option<u8>) -> Vec<OsString> {
+    match delimiter {
+        Some(delimiter) => split_delimited(value, delimiter),
+        None => vec![value.to_owned()],
+    }
+}
+
+/// Splits `value` at each `delimiter` which is not preceded by a backslash.
+/// A backslash followed by `delimiter` is replaced by the `delimiter` alone.
+///
+/// `delimiter` must be an ASCII character.
+fn split_delimited(value: &OsStr, delimiter: u8) -> Vec<OsString> {
+    let bytes = value.as_encoded_bytes();
+    let mut elements = vec![];
+    let mut element = OsString::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&delimiter) {
+            // Keep the escaped delimiter as the start of the next piece.
+            element.push(os_str_slice(value, start, i));
+            start = i + 1;
+            i += 2;
+        } else if bytes[i] == delimiter {
+            element.push(os_str_slice(value, start, i));
+            elements.push(std::mem::take(&mut element));
+            start = i + 1;
+            i += 1;
+        } else {
+            i += 1;
+        }
+    }
+    element.push(os_str_slice(value, start, bytes.len()));
+    elements.push(element);
+    elements
+}
+
+/// Whether `arg` could be a group of several short options, like `-vq`.
+fn is_short_group(arg: &[u8]) -> bool {
+    arg.starts_with(b"-") && !arg.starts_with(b"--") && arg.len() > 2
+}
+
+/// Returns the longest prefix of `bytes` which is valid UTF-8.
+fn utf8_prefix(bytes: &[u8]) -> &str {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+/// Returns the part of `arg` after its first `index` bytes.
+///
+/// `index` must directly follow a UTF-8 character in `arg`.
+fn os_str_after(arg: &OsStr, index: usize) -> &OsStr {
+    // SAFETY: the bytes come from `OsStr::as_encoded_bytes`, and are split
+    // directly after a valid UTF-8 character.
+    unsafe { OsStr::from_encoded_bytes_unchecked(&arg.as_encoded_bytes()[index..]) }
+}
+
+/// Returns the bytes of `arg` from `start` up to `end`.
+///
+/// `start` and `end` must each be an end of `arg`, or be next to an ASCII
+/// character in `arg`.
+fn os_str_slice(arg: &OsStr, start: usize, end: usize) -> &OsStr {
+    // SAFETY: the bytes come from `OsStr::as_encoded_bytes`, and are split
+    // at the ends or next to ASCII characters.
+    unsafe { OsStr::from_encoded_bytes_unchecked(&arg.as_encoded_bytes()[start..end]) }
+}
+
+fn unrecognized_argument(
+    index: usize,
+    given: &str,
+    arg_to_slot: &[(&str, usize)],
+    flags: &FlattenedFlags,
+    extra_suggestions: &[&str],
+) -> ParseError {
+    // get the list of available arguments, leaving out the aliases which are
+    // kept out of help
+    let available = arg_to_slot
+        .iter()
+        .map(|(name, _pos)| *name)
+        .filter(|name| !flags.is_hidden_alias(name))
+        .chain(extra_suggestions.iter().copied())
+        .collect::<Vec<&str>>();
+    let suggestion = suggest(given, available.iter().copied()).map(str::to_owned);
+
+    ParseError::UnknownArgument {
+        index,
+        arg: given.to_owned(),
+        suggestion,
+        no_options: available.is_empty(),
+    }
+}
+
+// Returns the candidate which `given` is most likely a typo of, if any. Only
+// the `suggestions` feature makes suggestions.
+#[doc(hidden)]
+pub fn suggest<'a>(given: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    #[cfg(feature = "suggestions")]
+    {
+        suggestions::best_match(given, candidates)
+    }
+
+    #[cfg(not(feature = "suggestions"))]
+    {
+        let _ = (given, candidates);
+        None
+    }
+}
+
+// `--` or `-` options, including a mutable reference to their value.
+#[doc(hidden)]
+pub enum ParseStructOption<'a> {
+    // A flag which is set to `true` when provided.
+    Flag(&'a mut dyn ParseFlag),
+    // A flag which is set to `true` when provided, or cleared when provided
+    // in its negated form, e.g. `--no-foo`. The second field is the negated
+    // form of the flag.
+    NegatableFlag(&'a mut dyn ParseFlag, &'static str),
+    // A value which is parsed from the string following the `--` argument,
+    // e.g. `--foo bar`.
+    Value(&'a mut dyn ParseValueSlot),
+    // A value which is split at a delimiter, with each element parsed
+    // separately, e.g. `--foo a,b`. The second field is the ASCII delimiter.
+    DelimitedValue(&'a mut dyn ParseValueSlot, u8),
+    // A value which may only be attached to the argument, e.g. `--foo=bar`.
+    // The second field is the value used when none is attached, e.g. `--foo`.
+    OptionalValue(&'a mut dyn ParseValueSlot, &'static str),
+    // Any of the options and switches of a `#[argh(flatten)]` field, which
+    // are parsed by the field's type once the struct's own have been.
+    Flattened(&'a mut FlattenedArgs),
+}
+
+// The names of the options and switches of a `Flatten` struct.
+#[doc(hidden)]
+pub struct FlattenedFlags {
+    // The names of the struct's own options and switches, with whether each
+    // takes its value from the following argument.
+    pub flags: &'static [(&'static str, bool)],
+    // Those of its `#[argh(flatten)]` fields, in order.
+    pub flattened: &'static [&'static FlattenedFlags],
+    // The names of the struct's own `global` options and switches.
+    pub globals: &'static [&'static str],
+    // The struct's own aliases which are not shown in help, and so are not
+    // suggested for mistyped arguments either.
+    pub hidden_aliases: &'static [&'static str],
+    // Whether the struct has only options and switches, and so can be
+    // flattened.
+    pub flattenable: bool,
+}
+
+impl FlattenedFlags {
+    // The flags of a subcommand which doesn't declare them.
+    #[doc(hidden)]
+    pub const EMPTY: FlattenedFlags = FlattenedFlags {
+        flags: &[],
+        flattened: &[],
+        globals: &[],
+        hidden_aliases: &[],
+        flattenable: true,
+    };
+
+    // Fails to compile, when used as the value of `Flatten::FLAGS`, if a
+    // flattened struct can't be flattened, or a name is used more than once.
+    #[doc(hidden)]
+    pub const fn new(
+        flags: &'static [(&'static str, bool)],
+        flattened: &'static [&'static FlattenedFlags],
+        globals: &'static [&'static str],
+        hidden_aliases: &'static [&'static str],
+        flattenable: bool,
+    ) -> Self {
+        let mut i = 0;
+        while i < flattened.len() {
+            if !flattened[i].flattenable {
+                panic!(
+                    "`#[argh(flatten)]` fields may not have positional arguments, subcommands \
+                     or `#[argh(unknown)]` fields"
+                );
+            }
+            let mut flag = 0;
+            while flag < flags.len() {
+                flattened[i].check_unused(flags[flag].0);
+                flag += 1;
+            }
+            let mut j = i + 1;
+            while j < flattened.len() {
+                flattened[j].check_disjoint(flattened[i]);
+                j += 1;
+            }
+            i += 1;
+        }
+        Self { flags, flattened, globals, hidden_aliases, flattenable }
+    }
+
+    // Fails to compile, when evaluated in a constant, if a global option of
+    // the struct has the name of an option of one of its `subcommands`.
+    #[doc(hidden)]
+    pub const fn check_globals(&self, subcommands: &[&FlattenedFlags]) {
+        let mut global = 0;
+        while global < self.globals.len() {
+            let mut i = 0;
+            while i < subcommands.len() {
+                if subcommands[i].uses(self.globals[global]) {
+                    name_error(self.globals[global], GLOBAL_NAME_IN_SUBCOMMAND);
+                }
+                i += 1;
+            }
+            global += 1;
+        }
+        let mut i = 0;
+        while i < self.flattened.len() {
+            self.flattened[i].check_globals(subcommands);
+            i += 1;
+        }
+    }
+
+    // Checks that none of the names of `other` are used by this struct.
+    const fn check_disjoint(&self, other: &FlattenedFlags) {
+        let mut flag = 0;
+        while flag < other.flags.len() {
+            self.check_unused(other.flags[flag].0);
+            flag += 1;
+        }
+        let mut i = 0;
+        while i < other.flattened.len() {
+            self.check_disjoint(other.flattened[i]);
+            i += 1;
+        }
+    }
+
+    // Checks that `name` is not used by this struct.
+    const fn check_unused(&self, name: &str) {
+        if self.uses(name) {
+            name_error(name, NAME_IN_FLATTENED);
+        }
+    }
+
+    // Whether `name` is used by this struct.
+    const fn uses(&self, name: &str) -> bool {
+        let mut flag = 0;
+        while flag < self.flags.len() {
+            if const_str_eq(self.flags[flag].0, name) {
+                return true;
+            }
+            flag += 1;
+        }
+        let mut i = 0;
+        while i < self.flattened.len() {
+            if self.flattened[i].uses(name) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    // Whether `name` is a global option or switch of this struct.
+    fn is_global(&self, name: &str) -> bool {
+        self.globals.contains(&name)
+            || self.flattened.iter().any(|flattened| flattened.is_global(name))
+    }
+
+    // Whether `name` is an alias of this struct which is not shown in help.
+    fn is_hidden_alias(&self, name: &str) -> bool {
+        self.hidden_aliases.contains(&name)
+            || self.flattened.iter().any(|flattened| flattened.is_hidden_alias(name))
+    }
+
+    // Whether the option or switch `name` takes its value from the following
+    // argument.
+    fn takes_value(&self, name: &str) -> bool {
+        self.flags.iter().any(|&(flag, takes_value)| flag == name && takes_value)
+            || self.flattened.iter().any(|flattened| flattened.takes_value(name))
+    }
+
+    fn names(&self) -> Box<dyn Iterator<Item = &'static str>> {
+        let flattened = self.flattened.iter().flat_map(|flattened| flattened.names());
+        Box::new(self.flags.iter().map(|&(name, _)| name).chain(flattened))
+    }
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Fails to compile, when evaluated in a constant, if an alias of one of
+// `commands` is also a name, alias or short name of it or of another of them.
+// Collisions between names and short names alone are left as they are.
+#[doc(hidden)]
+pub const fn check_command_names(commands: &[&CommandInfo]) {
+    let mut i = 0;
+    while i < commands.len() {
+        let mut name = 0;
+        while let Some(used) = command_name_at(commands[i], name) {
+            let mut j = i;
+            while j < commands.len() {
+                // The names of the same command are only compared with those
+                // after them.
+                let mut other = if j == i { name + 1 } else { 0 };
+                while let Some(other_used) = command_name_at(commands[j], other) {
+                    if (name > 0 || other > 0) && const_str_eq(used, other_used) {
+                        name_error(used, NAME_IN_SUBCOMMANDS);
+                    }
+                    other += 1;
+                }
+                if name > 0 && j != i && is_short_name(commands[j], used) {
+                    name_error(used, NAME_IN_SUBCOMMANDS);
+                }
+                j += 1;
+            }
+            name += 1;
+        }
+        i += 1;
+    }
+}
+
+// Fails to compile, when evaluated in a constant, if `default`, the default
+// subcommand named by a `#[argh(subcommand)]` field, is not the name of one of
+// `commands`.
+#[doc(hidden)]
+pub const fn check_default_command(commands: &[&CommandInfo], default: &str) {
+    let mut i = 0;
+    while i < commands.len() {
+        if const_str_eq(commands[i].name, default) {
+            return;
+        }
+        i += 1;
+    }
+    name_error(default, UNKNOWN_DEFAULT_SUBCOMMAND);
+}
+
+// The name of `command` at `index`, counting its name followed by its
+// aliases and visible aliases.
+const fn command_name_at(command: &CommandInfo, index: usize) -> Option<&str> {
+    let (aliases, visible_aliases) = (command.aliases, command.visible_aliases);
+    if index == 0 {
+        Some(command.name)
+    } else if index <= aliases.len() {
+        Some(aliases[index - 1])
+    } else if index <= aliases.len() + visible_aliases.len() {
+        Some(visible_aliases[index - 1 - aliases.len()])
+    } else {
+        None
+    }
+}
+
+// Whether `name` is the short name of `command`.
+const fn is_short_name(command: &CommandInfo, name: &str) -> bool {
+    let short = *command.short;
+    short != '\0' && short.is_ascii() && name.len() == 1 && name.as_bytes()[0] == short as u8
+}
+
+const NAME_IN_FLATTENED: &str =
+    "is used by more than one field, including `#[argh(flatten)]` fields";
+const GLOBAL_NAME_IN_SUBCOMMAND: &str = "of a global option is also used by a subcommand";
+const NAME_IN_SUBCOMMANDS: &str = "is used by more than one subcommand";
+const UNKNOWN_DEFAULT_SUBCOMMAND: &str = "of the default subcommand matches no subcommand";
+
+// Fails to compile with a message naming `name`, followed by `reason`.
+const fn name_error(name: &str, reason: &str) -> ! {
+    // Messages of const panics can't be formatted, so the message is built
+    // by hand.
+    const PREFIX: &[u8] = b"The name \"";
+    const MAX_LEN: usize = 256;
+    let mut message = [0u8; MAX_LEN];
+    let (name, reason) = (name.as_bytes(), reason.as_bytes());
+    if PREFIX.len() + name.len() + 2 + reason.len() > MAX_LEN {
+        panic!("A name is used more than once");
+    }
+    let parts = [PREFIX, name, b"\" ", reason];
+    let (mut len, mut part) = (0, 0);
+    while part < parts.len() {
+        let mut i = 0;
+        while i < parts[part].len() {
+            message[len] = parts[part][i];
+            len += 1;
+            i += 1;
+        }
+        part += 1;
+    }
+    match std::str::from_utf8(message.split_at(len).0) {
+        Ok(message) => panic!("{}", message),
+        Err(_) => panic!("A name is used more than once"),
+    }
+}
+
+// The options and switches given for a `#[argh(flatten)]` field, to be parsed
+// by its type once those of the struct have been.
+#[doc(hidden)]
+pub struct FlattenedArgs {
+    flags: &'static FlattenedFlags,
+    // The arguments, each with the index of the argument it came from.
+    args: Vec<(usize, OsString)>,
+}
+
+impl FlattenedArgs {
+    #[doc(hidden)]
+    pub fn new(flags: &'static FlattenedFlags) -> Self {
+        Self { flags, args: Vec::new() }
+    }
+
+    fn takes_value(&self, name: &str) -> bool {
+        self.flags.takes_value(name)
+    }
+
+    fn push(
+        &mut self,
+        index: usize,
+        name: &str,
+        inline_value: Option<&OsStr>,
+        remaining_args: &mut &[&OsStr],
+    ) {
+        let takes_value = self.takes_value(name);
+        record_arg(&mut self.args, index, name, takes_value, inline_value, remaining_args);
+    }
+
+    fn args(&self) -> Vec<&OsStr> {
+        self.args.iter().map(|(_, arg)| arg.as_os_str()).collect()
+    }
+
+    // Moves the indices of the errors of `T`, which are those of the
+    // recorded arguments, to those of the arguments they came from.
+    fn map_error(&self, mut error: ParseError) -> ParseError {
+        error.map_index(&|index| match self.args.get(index) {
+            Some(&(original, _)) => original,
+            None => index,
+        });
+        error
+    }
+
+    // Parses the recorded arguments as the `#[argh(flatten)]` field's type
+    // `T`, which shares the environment of the struct.
+    //
+    // Returns `None` if the arguments could not be parsed, but errors are
+    // being collected. Missing requirements are added to those of the struct
+    // rather than reported separately.
+    #[doc(hidden)]
+    pub fn parse<T: FromArgs>(
+        &self,
+        cmd_name: &[&str],
+        context: &ParseContext<'_>,
+        env_prefix: Option<&str>,
+        errors: &mut CollectedErrors,
+        missing: &mut MissingRequirements,
+    ) -> Result<Option<T>, ParseError> {
+        let context = context.flattened(env_prefix, errors.collect);
+        let cmd_name = str_args_to_os(cmd_name);
+        let error = match T::from_args_with_context(&cmd_name, &self.args(), &context) {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => self.map_error(error),
+        };
+        match error {
+            ParseError::MissingRequirements(flattened) => missing.merge(*flattened),
+            ParseError::Multiple(flattened_errors) => {
+                for error in flattened_errors {
+                    match error {
+                        ParseError::MissingRequirements(flattened) => missing.merge(*flattened),
+                        error => errors.check(Err(error))?,
+                    }
+                }
+            }
+            error => errors.check(Err(error))?,
+        }
+        Ok(None)
+    }
+
+    // Redacts the recorded arguments as the `#[argh(flatten)]` field's type
+    // `T`, without the command name.
+    #[doc(hidden)]
+    pub fn redact<T: FromArgs>(&self, cmd_name: &[&str]) -> Result<Vec<String>, ParseError> {
+        let args = os_args_to_str(&self.args()).map_err(|e| self.map_error(e))?;
+        let mut redacted = T::redact_arg_values_with_globals(cmd_name, &args, None)
+            .map_err(|e| self.map_error(e))?;
+        redacted.remove(0);
+        Ok(redacted)
+    }
+}
+
+// Records the option or switch `name` at `index` in `args`, with its
+// `inline_value` or, for options which take one, the value following it.
+fn record_arg(
+    args: &mut Vec<(usize, OsString)>,
+    index: usize,
+    name: &str,
+    takes_value: bool,
+    inline_value: Option<&OsStr>,
+    remaining_args: &mut &[&OsStr],
+) {
+    let mut arg = OsString::from(name);
+    if let Some(value) = inline_value {
+        // Short options, as in `-n5`, take their value without an `=`.
+        if name.starts_with("--") {
+            arg.push("=");
+        }
+        arg.push(value);
+        args.push((index, arg));
+        return;
+    }
+    args.push((index, arg));
+    if takes_value {
+        if let Some((value, rest)) = remaining_args.split_first() {
+            args.push((index + 1, value.into()));
+            *remaining_args = rest;
+        }
+    }
+}
+
+// The `global` options and switches of a command, which its subcommands
+// accept too. A subcommand records those it is given, for the command to
+// parse once the subcommand has been parsed.
+#[doc(hidden)]
+pub struct GlobalArgs<'a> {
+    flags: &'static FlattenedFlags,
+    help: fn(&str) -> String,
+    // The global options of the command's parent, if it is a subcommand.
+    inherited: Option<&'a GlobalArgs<'a>>,
+    // The recorded arguments, each with the index of the argument it came
+    // from.
+    args: RefCell<Vec<(usize, OsString)>>,
+}
+
+impl<'a> GlobalArgs<'a> {
+    // `flags`: the flags of the command, from `Flatten::FLAGS`.
+    // `help`: the descriptions of its global options, from
+    // `Flatten::help_global_options`.
+    #[doc(hidden)]
+    pub fn new(
+        flags: &'static FlattenedFlags,
+        help: fn(&str) -> String,
+        inherited: Option<&'a GlobalArgs<'a>>,
+    ) -> Self {
+        Self { flags, help, inherited, args: RefCell::new(Vec::new()) }
+    }
+
+    // Whether `name` is a global option of this command or of its parents.
+    fn is_global(&self, name: &str) -> bool {
+        self.flags.is_global(name) || self.inherited.is_some_and(|parent| parent.is_global(name))
+    }
+
+    // The global options of the parent command if `name` is one of them or
+    // of its own parents, to record `name` in.
+    fn inherited(&self, name: &str) -> Option<&'a GlobalArgs<'a>> {
+        self.inherited.filter(|parent| parent.is_global(name))
+    }
+
+    // Whether the global option `name` takes its value from the following
+    // argument.
+    fn takes_value(&self, name: &str) -> bool {
+        if self.flags.is_global(name) {
+            self.flags.takes_value(name)
+        } else {
+            self.inherited.is_some_and(|parent| parent.takes_value(name))
+        }
+    }
+
+    fn push(
+        &self,
+        index: usize,
+        name: &str,
+        inline_value: Option<&OsStr>,
+        remaining_args: &mut &[&OsStr],
+    ) {
+        let takes_value = self.takes_value(name);
+        let mut args = self.args.borrow_mut();
+        record_arg(&mut args, index, name, takes_value, inline_value, remaining_args);
+    }
+
+    // Moves the indices of the recorded arguments, which are those of a
+    // subcommand's arguments, by `offset`.
+    fn offset_indices(&self, offset: usize) {
+        self.args.borrow_mut().iter_mut().for_each(|(index, _)| *index += offset);
+    }
+
+    fn take_args(&self) -> Vec<(usize, OsString)> {
+        self.args.take()
+    }
+}
+
+// The "Global options" section of the help of a subcommand, listing those of
+// its parents, if any.
+#[doc(hidden)]
+pub fn global_options_help(global_args: Option<&GlobalArgs<'_>>, command_name: &str) -> String {
+    let mut options = String::new();
+    let mut global_args = global_args;
+    while let Some(parent) = global_args {
+        options.push_str(&(parent.help)(command_name));
+        global_args = parent.inherited;
+    }
+    if options.is_empty() {
+        return options;
+    }
+    ["\n\nGlobal options:", &options].concat()
+}
+
+// Adds the names of the options and switches of the `#[argh(flatten)]`
+// fields of a struct, described by its `flags`, to its own `arg_to_slot`.
+// `slots` are those of the fields, in order.
+#[doc(hidden)]
+pub fn flattened_arg_to_slot(
+    arg_to_slot: &[(&'static str, usize)],
+    flags: &'static FlattenedFlags,
+    slots: &[usize],
+) -> Vec<(&'static str, usize)> {
+    let flattened = flags
+        .flattened
+        .iter()
+        .zip(slots)
+        .flat_map(|(flattened, &slot)| flattened.names().map(move |name| (name, slot)));
+    arg_to_slot.iter().copied().chain(flattened).collect()
+}
+
+// Joins the flags of a struct and those of its `#[argh(flatten)]` fields, for
+// `ArgsInfo::get_args_info`.
+//
+// `CommandInfoWithArgs` holds the flags in a `'static` slice, so they are
+// leaked; the derived `ArgsInfo` keeps them to leak them only once per type.
+// The implicit `--help` flags of the flattened structs are left out.
+#[doc(hidden)]
+pub fn flattened_flag_info(
+    flags: &'static [FlagInfo<'static>],
+    flattened: &[&'static [FlagInfo<'static>]],
+) -> &'static [FlagInfo<'static>] {
+    let flattened = flattened.iter().flat_map(|flags| flags.iter());
+    let flags = flags.iter().chain(flattened.filter(|flag| flag.long != "--help"));
+    flags.cloned().collect::<Vec<_>>().leak()
+}
+
+#[doc(hidden)]
+pub struct ParseStructPositionals<'a> {
+    pub positionals: &'a mut [ParseStructPositional<'a>],
+    pub last_is_repeating: bool,
+    pub last_is_greedy: bool,
+    // The field collecting the arguments after `--`, if any.
+    pub trailing: Option<ParseStructPositional<'a>>,
+}
+
+impl ParseStructPositionals<'_> {
+    /// Parse the next positional argument.
+    ///
+    /// `index`: the index of the next positional argument to fill.
+    /// `arg_index`: the index of `arg` in the command line arguments.
+    /// `arg`: the argument supplied by the user.
+    ///
+    /// Returns true if non-positional argument parsing should stop
+    /// after this one.
+    fn parse(
+        &mut self,
+        index: &mut usize,
+        arg_index: usize,
+        arg: &OsStr,
+    ) -> Result<bool, ParseError> {
+        if *index < self.positionals.len() {
+            // Move on to the next positional argument even if this one could
+            // not be parsed, so that commands which collect their errors
+            // parse the following arguments into the right slots.
+            let parsed = self.positionals[*index].parse(arg_index, arg);
+
+            if self.last_is_repeating && *index == self.positionals.len() - 1 {
+                // Don't increment position if we're at the last arg
+                // *and* the last arg is repeating. If it's also remainder,
+                // halt non-option processing after this.
+                parsed.map(|()| self.last_is_greedy)
+            } else {
+                // If it is repeating, though, increment the index and continue
+                // processing options.
+                *index += 1;
+                parsed.map(|()| false)
+            }
+        } else {
+            Err(ParseError::UnknownArgument {
+                index: arg_index,
+                arg: arg.to_string_lossy().into_owned(),
+                suggestion: None,
+                no_options: false,
+            })
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct ParseStructPositional<'a> {
+    // The positional's name
+    pub name: &'static str,
+
+    // The function to parse the positional.
+    pub slot: &'a mut dyn ParseValueSlot,
+}
+
+impl ParseStructPositional<'_> {
+    /// Parse a positional argument.
+    ///
+    /// `index`: the index of `arg` in the command line arguments.
+    /// `arg`: the argument supplied by the user.
+    fn parse(&mut self, index: usize, arg: &OsStr) -> Result<(), ParseError> {
+        self.slot.fill_slot("", arg).map_err(|source| ParseError::InvalidPositional {
+            index,
+            name: self.name.to_owned(),
+            value: arg.to_string_lossy().into_owned(),
+            source,
+        })
     }
 }
 
@@ -1245,74 +3718,192 @@ pub struct ParseStructSubCommand<'a> {
 
     pub dynamic_subcommands: &'a [&'static CommandInfo],
 
-    // The function to parse the subcommand arguments.
+    // The name of the subcommand parsed when none is given, if any.
+    pub default: Option<&'static str>,
+
+    // The function to parse the subcommand arguments, which start at the
+    // given index in the command line arguments.
     #[allow(clippy::type_complexity)]
-    pub parse_func: &'a mut dyn FnMut(&[&str], &[&str]) -> Result<(), EarlyExit>,
+    pub parse_func:
+        &'a mut dyn FnMut(&[&str], &[&OsStr], usize, &GlobalArgs<'_>) -> Result<(), ParseError>,
 }
 
 impl ParseStructSubCommand<'_> {
+    /// `index`: the index of `arg`, the subcommand, in the command line
+    /// arguments.
+    /// `global_args`: the global options of the command, which the subcommand
+    /// records with their indices in the command line arguments.
     fn parse(
         &mut self,
         help: bool,
         cmd_name: &[&str],
+        index: usize,
         arg: &str,
-        remaining_args: &[&str],
-    ) -> Result<bool, EarlyExit> {
-        for subcommand in self.subcommands.iter().chain(self.dynamic_subcommands.iter()) {
-            if subcommand.name == arg
-                || arg.chars().count() == 1 && arg.chars().next().unwrap() == *subcommand.short
-            {
-                let mut command = cmd_name.to_owned();
-                command.push(subcommand.name);
-                let prepended_help;
-                let remaining_args = if help {
-                    prepended_help = prepend_help(remaining_args);
-                    &prepended_help
-                } else {
-                    remaining_args
-                };
+        remaining_args: &[&OsStr],
+        global_args: &GlobalArgs<'_>,
+    ) -> Result<bool, ParseError> {
+        let Some(subcommand) = self.find(|subcommand| subcommand.is_named(arg)) else {
+            return Ok(false);
+        };
 
-                (self.parse_func)(&command, remaining_args)?;
+        let prepended_help;
+        let remaining_args = if help {
+            prepended_help = prepend_help(remaining_args);
+            &prepended_help
+        } else {
+            remaining_args
+        };
 
-                return Ok(true);
-            }
-        }
+        // The subcommand's arguments start after it, where a prepended
+        // `help` stands in for the subcommand itself.
+        let offset = if help { index } else { index + 1 };
+        self.parse_command(cmd_name, subcommand, offset, remaining_args, global_args)?;
+        Ok(true)
+    }
+
+    /// Parse the default subcommand, if there is one, from `args`, which
+    /// start at `index` in the command line arguments.
+    fn parse_default(
+        &mut self,
+        cmd_name: &[&str],
+        index: usize,
+        args: &[&OsStr],
+        global_args: &GlobalArgs<'_>,
+    ) -> Result<bool, ParseError> {
+        let Some(default) = self.default else { return Ok(false) };
+        let Some(subcommand) = self.find(|subcommand| subcommand.name == default) else {
+            return Err(ParseError::Message(
+                ["The default subcommand '", default, "' matches no subcommand.\n"].concat(),
+            ));
+        };
+
+        self.parse_command(cmd_name, subcommand, index, args, global_args)?;
+        Ok(true)
+    }
+
+    /// The names and visible aliases of the subcommands.
+    fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.subcommands.iter().chain(self.dynamic_subcommands).flat_map(|command| {
+            std::iter::once(command.name).chain(command.visible_aliases.iter().copied())
+        })
+    }
+
+    fn find(&self, predicate: impl Fn(&CommandInfo) -> bool) -> Option<&'static CommandInfo> {
+        self.subcommands.iter().chain(self.dynamic_subcommands).copied().find(|&s| predicate(s))
+    }
 
-        Ok(false)
+    /// `offset`: the index of the first of `args` in the command line
+    /// arguments.
+    fn parse_command(
+        &mut self,
+        cmd_name: &[&str],
+        subcommand: &CommandInfo,
+        offset: usize,
+        args: &[&OsStr],
+        global_args: &GlobalArgs<'_>,
+    ) -> Result<(), ParseError> {
+        let mut command = cmd_name.to_owned();
+        command.push(subcommand.name);
+        let parsed = (self.parse_func)(&command, args, offset, global_args);
+        global_args.offset_indices(offset);
+        parsed.map_err(|mut error| {
+            error.map_index(&|index| index + offset);
+            error
+        })
     }
 }
 
 // Prepend `help` to a list of arguments.
 // This is used to pass the `help` argument on to subcommands.
-fn prepend_help<'a>(args: &[&'a str]) -> Vec<&'a str> {
-    [&["help"], args].concat()
+fn prepend_help<'a>(args: &[&'a OsStr]) -> Vec<&'a OsStr> {
+    [&[OsStr::new("help")], args].concat()
 }
 
 #[doc(hidden)]
-pub fn print_subcommands<'a>(commands: impl Iterator<Item = &'a CommandInfo>) -> String {
+pub fn print_subcommands<'a>(
+    commands: impl Iterator<Item = &'a CommandInfo>,
+    default: Option<&str>,
+) -> String {
     let mut out = String::new();
     for cmd in commands {
-        argh_shared::write_description(&mut out, cmd);
+        if Some(cmd.name) == default {
+            let description = [cmd.description, " [default]"].concat();
+            let description = description.trim_start();
+            argh_shared::write_description(
+                &mut out,
+                &argh_shared::CommandInfo { description, ..*cmd },
+            );
+        } else {
+            argh_shared::write_description(&mut out, cmd);
+        }
     }
     out
 }
 
-fn unrecognized_arg(arg: &str) -> String {
-    ["Unrecognized argument: ", arg, "\n"].concat()
-}
-
-// An error string builder to report missing required options and subcommands.
-#[doc(hidden)]
-#[derive(Default)]
+/// The required arguments which were not provided, and the constraints
+/// between arguments which were violated, when parsing a command.
+///
+/// Reported through [`ParseError::MissingRequirements`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MissingRequirements {
     options: Vec<&'static str>,
-    subcommands: Option<Vec<&'static CommandInfo>>,
+    subcommands: Option<Vec<&'static str>>,
     positional_args: Vec<&'static str>,
+    conflicts: Vec<(&'static str, &'static str)>,
+    required_by: Vec<(&'static str, &'static str)>,
+    missing_groups: Vec<Vec<&'static str>>,
+    exclusive_groups: Vec<Vec<&'static str>>,
 }
 
 const NEWLINE_INDENT: &str = "\n    ";
 
 impl MissingRequirements {
+    /// The required options which were not provided.
+    pub fn options(&self) -> &[&'static str] {
+        &self.options
+    }
+
+    /// The names of the subcommands one of which must be present, if no
+    /// subcommand was provided.
+    pub fn subcommands(&self) -> Option<&[&'static str]> {
+        self.subcommands.as_deref()
+    }
+
+    /// The required positional arguments which were not provided.
+    pub fn positional_args(&self) -> &[&'static str] {
+        &self.positional_args
+    }
+
+    /// Pairs of provided arguments which cannot be used together.
+    pub fn conflicts(&self) -> &[(&'static str, &'static str)] {
+        &self.conflicts
+    }
+
+    /// Pairs of a missing argument and the provided argument which requires it.
+    pub fn required_by(&self) -> &[(&'static str, &'static str)] {
+        &self.required_by
+    }
+
+    /// Required groups of arguments of which none were provided.
+    pub fn missing_groups(&self) -> &[Vec<&'static str>] {
+        &self.missing_groups
+    }
+
+    /// The provided arguments of each group which allows only one of them.
+    pub fn exclusive_groups(&self) -> &[Vec<&'static str>] {
+        &self.exclusive_groups
+    }
+
+    // Add the missing requirements of a `#[argh(flatten)]` field.
+    #[doc(hidden)]
+    pub fn merge(&mut self, other: MissingRequirements) {
+        self.options.extend(other.options);
+        self.conflicts.extend(other.conflicts);
+        self.required_by.extend(other.required_by);
+        self.missing_groups.extend(other.missing_groups);
+        self.exclusive_groups.extend(other.exclusive_groups);
+    }
+
     // Add a missing required option.
     #[doc(hidden)]
     pub fn missing_option(&mut self, name: &'static str) {
@@ -1322,7 +3913,7 @@ impl MissingRequirements {
     // Add a missing required subcommand.
     #[doc(hidden)]
     pub fn missing_subcommands(&mut self, commands: impl Iterator<Item = &'static CommandInfo>) {
-        self.subcommands = Some(commands.collect());
+        self.subcommands = Some(commands.map(|command| command.name).collect());
     }
 
     // Add a missing positional argument.
@@ -1331,15 +3922,55 @@ impl MissingRequirements {
         self.positional_args.push(name)
     }
 
-    // If any missing options or subcommands were provided, returns an error string
+    // Add two provided arguments which conflict with each other.
+    #[doc(hidden)]
+    pub fn conflicting_args(&mut self, name: &'static str, other: &'static str) {
+        self.conflicts.push((name, other))
+    }
+
+    // Add a missing argument which is required by a provided argument.
+    #[doc(hidden)]
+    pub fn missing_required_by(&mut self, name: &'static str, required_by: &'static str) {
+        self.required_by.push((name, required_by))
+    }
+
+    // Check a group of arguments, given as pairs of names and whether each was
+    // provided. Records an error if the group is `required` and none of its
+    // arguments were provided, or if it does not allow `multiple` arguments and
+    // more than one was provided.
+    #[doc(hidden)]
+    pub fn check_group(&mut self, args: &[(&'static str, bool)], required: bool, multiple: bool) {
+        let provided: Vec<_> =
+            args.iter().filter(|(_, provided)| *provided).map(|(name, _)| *name).collect();
+        if required && provided.is_empty() {
+            self.missing_groups.push(args.iter().map(|(name, _)| *name).collect());
+        }
+        if !multiple && provided.len() > 1 {
+            self.exclusive_groups.push(provided);
+        }
+    }
+
+    // If any missing options or subcommands were provided, returns an error
     // describing the missing args.
     #[doc(hidden)]
-    pub fn err_on_any(&self) -> Result<(), String> {
-        if self.options.is_empty() && self.subcommands.is_none() && self.positional_args.is_empty()
+    pub fn err_on_any(&self) -> Result<(), ParseError> {
+        if self.options.is_empty()
+            && self.subcommands.is_none()
+            && self.positional_args.is_empty()
+            && self.conflicts.is_empty()
+            && self.required_by.is_empty()
+            && self.missing_groups.is_empty()
+            && self.exclusive_groups.is_empty()
         {
             return Ok(());
         }
 
+        Err(ParseError::MissingRequirements(Box::new(self.clone())))
+    }
+}
+
+impl std::fmt::Display for MissingRequirements {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut output = String::new();
 
         if !self.positional_args.is_empty() {
@@ -1370,18 +4001,70 @@ impl MissingRequirements {
             output.push_str("help");
             for subcommand in missing_subcommands {
                 output.push_str(NEWLINE_INDENT);
-                output.push_str(subcommand.name);
+                output.push_str(subcommand);
+            }
+        }
+
+        if !self.conflicts.is_empty() {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str("Conflicting arguments provided:");
+            for (name, other) in &self.conflicts {
+                output.push_str(NEWLINE_INDENT);
+                output.push_str(name);
+                output.push_str(" cannot be used with ");
+                output.push_str(other);
+            }
+        }
+
+        if !self.required_by.is_empty() {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str("Required arguments not provided:");
+            for (name, required_by) in &self.required_by {
+                output.push_str(NEWLINE_INDENT);
+                output.push_str(name);
+                output.push_str(" (required by ");
+                output.push_str(required_by);
+                output.push(')');
+            }
+        }
+
+        for group in &self.missing_groups {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str("One of the following arguments must be provided:");
+            for name in group {
+                output.push_str(NEWLINE_INDENT);
+                output.push_str(name);
+            }
+        }
+
+        for group in &self.exclusive_groups {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str("Only one of the following arguments may be provided:");
+            for name in group {
+                output.push_str(NEWLINE_INDENT);
+                output.push_str(name);
             }
         }
 
         output.push('\n');
 
-        Err(output)
+        f.write_str(&output)
     }
 }
 
 #[cfg(test)]
 mod test {
+    // Lets the derived commands below name this crate as `argh`.
+    extern crate self as argh;
+
     use super::*;
 
     #[test]
@@ -1391,4 +4074,40 @@ mod test {
         let cmd = cmd(&path, &path);
         assert_eq!(expected, cmd);
     }
+
+    #[test]
+    fn caret_errors_show_expanded_args() {
+        #[derive(FromArgs)]
+        #[argh(response_files)]
+        /// Short description
+        struct Cmd {
+            #[argh(switch)]
+            /// the first switch
+            _a: bool,
+            #[argh(switch)]
+            /// the second switch
+            _b: bool,
+        }
+
+        let path = std::env::temp_dir()
+            .join(format!("argh-caret-response-file-{}.txt", std::process::id()));
+        std::fs::write(&path, "--b --c").unwrap();
+        let at = format!("@{}", path.display());
+
+        let parsed = parse_env_args::<Cmd>(
+            "cmd",
+            &[OsStr::new("--a"), OsStr::new(&at)],
+            ParseContext::new(&ProcessEnvironment),
+            ErrorStyle::Caret,
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let Err(FromEnvError::EarlyExit(early_exit, _)) = parsed else {
+            panic!("expected an early exit")
+        };
+        assert_eq!(
+            early_exit.output,
+            "Unrecognized argument: --c\n    cmd --a --b --c\n                ^^^\n"
+        );
+    }
 }
@@ -0,0 +1,155 @@
+// Copyright (c) 2026 Google LLC All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! Expansion of `@path` response files for commands with the
+//! `#[argh(response_files)]` attribute.
+
+use {
+    crate::ParseError,
+    std::{
+        ffi::{OsStr, OsString},
+        path::Path,
+    },
+};
+
+/// How deeply response files may include other response files.
+const MAX_DEPTH: usize = 16;
+
+// Replaces each `@path` argument before the first `--` with the arguments
+// read from the file at `path`. Response files may themselves contain
+// `@path` arguments, up to a depth of `MAX_DEPTH`, and a `--` read from one
+// ends the expansion as well.
+#[doc(hidden)]
+pub fn expand_response_files(args: &[&OsStr]) -> Result<Vec<OsString>, ParseError> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut options_ended = false;
+    for &arg in args {
+        expand_arg(arg, 0, &mut options_ended, &mut expanded)?;
+    }
+    Ok(expanded)
+}
+
+// Expands `arg` into `out`, unless `options_ended` was set by an earlier `--`.
+fn expand_arg(
+    arg: &OsStr,
+    depth: usize,
+    options_ended: &mut bool,
+    out: &mut Vec<OsString>,
+) -> Result<(), ParseError> {
+    let path = match arg.as_encoded_bytes().strip_prefix(b"@") {
+        Some(path) if !path.is_empty() && !*options_ended => Path::new(crate::os_str_after(arg, 1)),
+        _ => {
+            *options_ended |= arg == "--";
+            out.push(arg.to_owned());
+            return Ok(());
+        }
+    };
+
+    let path_display = path.display().to_string();
+    if depth >= MAX_DEPTH {
+        return Err(ParseError::Message(
+            ["Response file '", &path_display, "' is nested too deeply.\n"].concat(),
+        ));
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ParseError::Message(
+            ["Error reading response file '", &path_display, "': ", &e.to_string(), "\n"].concat(),
+        )
+    })?;
+    let args = split_response_file(&contents).map_err(|e| {
+        ParseError::Message(
+            ["Error parsing response file '", &path_display, "': ", e, "\n"].concat(),
+        )
+    })?;
+    for arg in args {
+        expand_arg(OsStr::new(&arg), depth + 1, options_ended, out)?;
+    }
+    Ok(())
+}
+
+/// Splits the contents of a response file into arguments.
+///
+/// Arguments are separated by whitespace, and may be quoted with `'` or `"`
+/// to include whitespace. Inside double quotes, and outside of quotes, a
+/// backslash escapes the following character. A `#` at the start of an
+/// argument begins a comment which lasts until the end of the line.
+fn split_response_file(contents: &str) -> Result<Vec<String>, &'static str> {
+    let mut args = Vec::new();
+    let mut chars = contents.chars();
+    // The argument being built, if one has been started.
+    let mut current: Option<String> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => args.extend(current.take()),
+            '#' if current.is_none() => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '\'' => {
+                let current = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated quote"),
+                    }
+                }
+            }
+            '"' => {
+                let current = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated quote"),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated quote"),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => current.get_or_insert_with(String::new).push(c),
+                None => return Err("trailing backslash"),
+            },
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let contents = r#"
+# a comment
+--name 'single quoted'  "double \"quoted\" \d"
+es\ caped a#b ''
+  # another comment
+--last
+"#;
+        assert_eq!(
+            split_response_file(contents).unwrap(),
+            ["--name", "single quoted", r#"double "quoted" \d"#, "es caped", "a#b", "", "--last"]
+        );
+    }
+
+    #[test]
+    fn split_errors() {
+        assert_eq!(split_response_file("'abc"), Err("unterminated quote"));
+        assert_eq!(split_response_file("\"abc\\\""), Err("unterminated quote"));
+        assert_eq!(split_response_file("abc\\"), Err("trailing backslash"));
+    }
+}
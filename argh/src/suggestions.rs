@@ -0,0 +1,189 @@
+// Copyright (c) 2026 Google LLC All rights reserved.
+// Use of this source code is governed by a BSD-style
+// license that can be found in the LICENSE file.
+
+//! "Did you mean" suggestions for mistyped arguments, tuned for command line
+//! tokens like `--verbose`, `-v` and subcommand names.
+
+use std::cmp::Ordering;
+
+/// Returns the candidate which `given` is most likely a typo of, if any.
+///
+/// Names are compared without their leading dashes and ignoring case, so
+/// `-verbose` and `--Verbose` both suggest `--verbose`. A candidate is only
+/// suggested if its Damerau-Levenshtein distance to `given` is at most a third
+/// of the length of the longer of the two. Short options, whose names are a
+/// single character, are therefore only suggested for a difference in case or
+/// dashes, rather than for every other single character. Candidates at the
+/// same distance are ranked by whether their dashes match those of `given`,
+/// then by Jaro-Winkler similarity, then by their order.
+pub(crate) fn best_match<'a>(
+    given: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let (given_dashes, given_name) = split_dashes(given);
+    if given_name.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(&str, Rank)> = None;
+    for candidate in candidates {
+        let (dashes, name) = split_dashes(candidate);
+        let distance = damerau_levenshtein(&given_name, &name);
+        if distance > given_name.len().max(name.len()) / 3 {
+            continue;
+        }
+        let rank = Rank {
+            distance,
+            dashes_differ: dashes != given_dashes,
+            similarity: jaro_winkler(&given_name, &name),
+        };
+        let is_better = match &best {
+            Some((_, best)) => rank.cmp(best) == Ordering::Less,
+            None => true,
+        };
+        if is_better {
+            best = Some((candidate, rank));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+/// How well a candidate matches, where lesser ranks are better matches.
+struct Rank {
+    distance: usize,
+    dashes_differ: bool,
+    similarity: f64,
+}
+
+impl Rank {
+    fn cmp(&self, other: &Rank) -> Ordering {
+        self.distance
+            .cmp(&other.distance)
+            .then(self.dashes_differ.cmp(&other.dashes_differ))
+            .then(other.similarity.total_cmp(&self.similarity))
+    }
+}
+
+/// Splits `arg` into its number of leading dashes and its lowercased name.
+fn split_dashes(arg: &str) -> (usize, Vec<char>) {
+    let name = arg.trim_start_matches('-');
+    (arg.len() - name.len(), name.to_lowercase().chars().collect())
+}
+
+/// The number of insertions, deletions, substitutions and transpositions of
+/// adjacent characters needed to turn `a` into `b`.
+///
+/// This is the restricted form of the distance, which does not edit any
+/// substring more than once.
+fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+    // Distances from the prefixes of `a` to the prefixes of `b`, for the
+    // current row and the two before it.
+    let mut two_ago = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && ca == b[j - 1] && a[i - 1] == cb {
+                distance = distance.min(two_ago[j - 1] + 1);
+            }
+            current[j + 1] = distance;
+        }
+        std::mem::swap(&mut two_ago, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// The Jaro similarity of `a` and `b`, from 0 for no similarity to 1 for
+/// equal strings, boosted for strings sharing a prefix of up to 4 characters.
+fn jaro_winkler(a: &[char], b: &[char]) -> f64 {
+    let jaro = jaro(a, b);
+    let prefix = a.iter().zip(b).take(4).take_while(|(a, b)| a == b).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return if a.is_empty() && b.is_empty() { 1.0 } else { 0.0 };
+    }
+
+    // Characters match if they are equal and not too far apart.
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, &ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == ca {
+                b_matched[j] = true;
+                a_matches.push(ca);
+                break;
+            }
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, &matched)| matched).map(|(&cb, _)| cb);
+    let transpositions = a_matches.iter().zip(b_matches).filter(|&(&ca, cb)| ca != cb).count() / 2;
+
+    let matches = a_matches.len() as f64;
+    (matches / a.len() as f64
+        + matches / b.len() as f64
+        + (matches - transpositions as f64) / matches)
+        / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn distance() {
+        let distance = |a: &str, b: &str| damerau_levenshtein(&chars(a), &chars(b));
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("abc", ""), 3);
+        assert_eq!(distance("verbos", "verbose"), 1);
+        assert_eq!(distance("deplyo", "deploy"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn similarity() {
+        let similarity = |a: &str, b: &str| jaro_winkler(&chars(a), &chars(b));
+        assert_eq!(similarity("same", "same"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert!((similarity("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((similarity("dixon", "dicksonx") - 0.813).abs() < 0.001);
+    }
+
+    #[test]
+    fn matches() {
+        let options = ["-v", "--verbose", "--version", "-h", "--help", "help"];
+        let best = |given| best_match(given, options.iter().copied());
+        assert_eq!(best("--verbos"), Some("--verbose"));
+        assert_eq!(best("--versoin"), Some("--version"));
+        assert_eq!(best("-verbose"), Some("--verbose"));
+        assert_eq!(best("--VERBOSE"), Some("--verbose"));
+        assert_eq!(best("-V"), Some("-v"));
+        assert_eq!(best("helpp"), Some("help"));
+        assert_eq!(best("--hlep"), Some("--help"));
+        assert_eq!(best("-x"), None);
+        assert_eq!(best("-q"), None);
+        assert_eq!(best("--quiet"), None);
+        assert_eq!(best("--"), None);
+    }
+}
@@ -18,6 +18,11 @@ const HELP_FLAG: FlagInfo<'_> = FlagInfo {
     optionality: Optionality::Optional,
     long: "--help",
     short: None,
+    negated_long: None,
+    env: None,
+    aliases: &[],
+    visible_aliases: &[],
+    deprecated: None,
     description: "display usage information",
     hidden: false,
 };
@@ -68,6 +73,11 @@ fn args_info_test_subcommand() {
                 optionality: Optionality::Required,
                 long: "--x",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "how many x",
                 hidden: false,
             },
@@ -78,12 +88,16 @@ fn args_info_test_subcommand() {
     assert_args_info::<TopLevel>(&CommandInfoWithArgs {
         name: "TopLevel",
         short: &'\0',
+        aliases: &[],
+        visible_aliases: &[],
+        deprecated: None,
         description: "Top-level command.",
         examples: &[],
         flags: &[HELP_FLAG],
         notes: &[],
         positionals: &[],
         error_codes: &[],
+        env_prefix: None,
         commands: vec![
             SubCommandInfo { name: "one", command: command_one.clone() },
             SubCommandInfo {
@@ -99,6 +113,11 @@ fn args_info_test_subcommand() {
                             optionality: Optionality::Optional,
                             long: "--fooey",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description: "whether to fooey",
                             hidden: false,
                         },
@@ -135,6 +154,11 @@ fn args_info_test_multiline_doc_comment() {
                     optionality: Optionality::Optional,
                     long: "--s",
                     short: None,
+                    negated_long: None,
+                    env: None,
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
                     description: "a switch with a description that is spread across a number of lines of comments.",
                     hidden:false
                 }
@@ -176,6 +200,11 @@ fn args_info_test_basic_args() {
                 optionality: Optionality::Optional,
                 long: "--help",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "display usage information",
                 hidden: false,
             },
@@ -184,6 +213,11 @@ fn args_info_test_basic_args() {
                 optionality: Optionality::Optional,
                 long: "--power",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "should the power be on. \"Quoted value\" should work too.",
                 hidden: false,
             },
@@ -192,6 +226,11 @@ fn args_info_test_basic_args() {
                 optionality: Optionality::Required,
                 long: "--required",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "option that is required because of no default and not Option<>.",
                 hidden: false,
             },
@@ -200,6 +239,11 @@ fn args_info_test_basic_args() {
                 optionality: Optionality::Optional,
                 long: "--speed",
                 short: Some('s'),
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "optional speed if not specified it is None.",
                 hidden: false,
             },
@@ -208,6 +252,11 @@ fn args_info_test_basic_args() {
                 optionality: Optionality::Repeating,
                 long: "--link",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "repeatable option.",
                 hidden: false,
             },
@@ -216,6 +265,287 @@ fn args_info_test_basic_args() {
     });
 }
 
+#[test]
+fn args_info_test_negatable_switch() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Command with a negatable switch.
+    struct Cmd {
+        /// colorize output.
+        #[argh(switch, negatable, default = "true")]
+        color: bool,
+    }
+    assert_args_info::<Cmd>(&CommandInfoWithArgs {
+        name: "Cmd",
+        description: "Command with a negatable switch.",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::Switch,
+                optionality: Optionality::Optional,
+                long: "--color",
+                short: None,
+                negated_long: Some("--no-color"),
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+                description: "colorize output.",
+                hidden: false,
+            },
+        ],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn args_info_test_env() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Command with an env fallback.
+    struct Cmd {
+        /// the api token.
+        #[argh(option, env = "APP_TOKEN")]
+        token: String,
+    }
+    assert_args_info::<Cmd>(&CommandInfoWithArgs {
+        name: "Cmd",
+        description: "Command with an env fallback.",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::Option { arg_name: "token" },
+                optionality: Optionality::Required,
+                long: "--token",
+                short: None,
+                negated_long: None,
+                env: Some("APP_TOKEN"),
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+                description: "the api token.",
+                hidden: false,
+            },
+        ],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn args_info_test_optional_value() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Command with an optional-value option.
+    struct Cmd {
+        /// when to colorize.
+        #[argh(option, default_missing_value = "always")]
+        color: Option<String>,
+    }
+    assert_args_info::<Cmd>(&CommandInfoWithArgs {
+        name: "Cmd",
+        description: "Command with an optional-value option.",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::OptionalValue {
+                    arg_name: "color",
+                    default_missing_value: "always",
+                },
+                optionality: Optionality::Optional,
+                long: "--color",
+                short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+                description: "when to colorize.",
+                hidden: false,
+            },
+        ],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn args_info_test_aliases() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Top-level command.
+    struct TopLevel {
+        /// the name.
+        #[argh(option, alias = "old-name", visible_alias = "x")]
+        name: Option<String>,
+        #[argh(subcommand)]
+        nested: Nested,
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    #[argh(subcommand)]
+    enum Nested {
+        Remove(RemoveCmd),
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Remove.
+    #[argh(subcommand, name = "remove", alias = "rm", visible_alias = "delete")]
+    struct RemoveCmd {}
+
+    assert_args_info::<TopLevel>(&CommandInfoWithArgs {
+        name: "TopLevel",
+        description: "Top-level command.",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::Option { arg_name: "name" },
+                optionality: Optionality::Optional,
+                long: "--name",
+                short: None,
+                negated_long: None,
+                env: None,
+                aliases: &["--old-name"],
+                visible_aliases: &["-x"],
+                deprecated: None,
+                description: "the name.",
+                hidden: false,
+            },
+        ],
+        commands: vec![SubCommandInfo {
+            name: "remove",
+            command: CommandInfoWithArgs {
+                name: "remove",
+                aliases: &["rm"],
+                visible_aliases: &["delete"],
+                deprecated: None,
+                description: "Remove.",
+                flags: &[HELP_FLAG],
+                ..Default::default()
+            },
+        }],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn args_info_test_deprecated() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Top-level command.
+    struct TopLevel {
+        /// the zone.
+        #[argh(option, deprecated = "use --region instead")]
+        zone: Option<String>,
+        /// the file.
+        #[argh(positional, deprecated)]
+        file: Option<String>,
+        #[argh(subcommand)]
+        nested: Nested,
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    #[argh(subcommand)]
+    enum Nested {
+        Old(OldCmd),
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Old.
+    #[argh(subcommand, name = "old", deprecated = "use new instead")]
+    struct OldCmd {}
+
+    assert_args_info::<TopLevel>(&CommandInfoWithArgs {
+        name: "TopLevel",
+        description: "Top-level command.",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::Option { arg_name: "zone" },
+                optionality: Optionality::Optional,
+                long: "--zone",
+                short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: Some("use --region instead"),
+                description: "the zone.",
+                hidden: false,
+            },
+        ],
+        positionals: &[PositionalInfo {
+            name: "file",
+            description: "the file.",
+            optionality: Optionality::Optional,
+            deprecated: Some(""),
+            hidden: false,
+        }],
+        commands: vec![SubCommandInfo {
+            name: "old",
+            command: CommandInfoWithArgs {
+                name: "old",
+                deprecated: Some("use new instead"),
+                description: "Old.",
+                flags: &[HELP_FLAG],
+                ..Default::default()
+            },
+        }],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn args_info_test_env_prefix() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Top-level command.
+    #[argh(env_prefix = "MYTOOL_")]
+    struct TopLevel {
+        /// the api token.
+        #[argh(option)]
+        api_token: String,
+        /// be verbose.
+        #[argh(switch)]
+        verbose: bool,
+        #[argh(subcommand)]
+        nested: Nested,
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    #[argh(subcommand)]
+    enum Nested {
+        Deploy(DeployCmd),
+    }
+
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Deploy.
+    #[argh(subcommand, name = "deploy")]
+    struct DeployCmd {
+        /// the region.
+        #[argh(option)]
+        region: String,
+        /// the log level.
+        #[argh(option, env = "LOG_LEVEL")]
+        log_level: String,
+    }
+
+    let info = TopLevel::get_args_info();
+    let env_vars: Vec<_> = info.flags.iter().filter_map(|f| info.flag_env_var(f)).collect();
+    assert_eq!(env_vars, ["MYTOOL_API_TOKEN"]);
+
+    let deploy = &info.commands[0].command;
+    assert_eq!(deploy.env_prefix.as_deref(), Some("MYTOOL_DEPLOY_"));
+    let env_vars: Vec<_> = deploy.flags.iter().filter_map(|f| deploy.flag_env_var(f)).collect();
+    assert_eq!(env_vars, ["MYTOOL_DEPLOY_REGION", "LOG_LEVEL"]);
+
+    assert_eq!(DeployCmd::get_args_info().env_prefix, None);
+}
+
 #[test]
 fn args_info_test_positional_args() {
     #[allow(dead_code)]
@@ -244,18 +574,21 @@ fn args_info_test_positional_args() {
                 name: "root",
                 description: "the \"root\" position.",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "trunk",
                 description: "trunk value",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "leaves",
                 description: "leaves. There can be many leaves.",
                 optionality: Optionality::Repeating,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -291,6 +624,11 @@ fn args_info_test_optional_positional_args() {
             optionality: Optionality::Optional,
             long: "--help",
             short: None,
+            negated_long: None,
+            env: None,
+            aliases: &[],
+            visible_aliases: &[],
+            deprecated: None,
             description: "display usage information",
             hidden: false,
         }],
@@ -299,18 +637,21 @@ fn args_info_test_optional_positional_args() {
                 name: "root",
                 description: "the \"root\" position.",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "trunk",
                 description: "trunk value",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "leaves",
                 description: "leaves. There can be an optional leaves.",
                 optionality: Optionality::Optional,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -347,18 +688,21 @@ fn args_info_test_default_positional_args() {
                 name: "root",
                 description: "the \"root\" position.",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "trunk",
                 description: "trunk value",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "leaves",
                 description: "leaves. There can be one leaf, defaults to hello.",
                 optionality: Optionality::Optional,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -411,6 +755,7 @@ fn args_info_test_notes_examples_errors() {
                         name: "files",
                         description: "the \"root\" position.",
                         optionality: Optionality::Repeating,
+                        deprecated: None,
                         hidden:false
                     }
                 ],
@@ -499,6 +844,11 @@ fn args_info_test_subcommands() {
                 optionality: Optionality::Optional,
                 long: "--verbose",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "show verbose output",
                 hidden: false,
             },
@@ -517,18 +867,21 @@ fn args_info_test_subcommands() {
                             name: "root",
                             description: "the \"root\" position.",
                             optionality: Optionality::Required,
+                            deprecated: None,
                             hidden: false,
                         },
                         PositionalInfo {
                             name: "trunk",
                             description: "trunk value",
                             optionality: Optionality::Required,
+                            deprecated: None,
                             hidden: false,
                         },
                         PositionalInfo {
                             name: "leaves",
                             description: "leaves. There can be zero leaves, defaults to hello.",
                             optionality: Optionality::Optional,
+                            deprecated: None,
                             hidden: false,
                         },
                     ],
@@ -548,6 +901,11 @@ fn args_info_test_subcommands() {
                             optionality: Optionality::Optional,
                             long: "--power",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description:
                                 "should the power be on. \"Quoted value\" should work too.",
                             hidden: false,
@@ -557,6 +915,11 @@ fn args_info_test_subcommands() {
                             optionality: Optionality::Required,
                             long: "--required",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description:
                                 "option that is required because of no default and not Option<>.",
                             hidden: false,
@@ -566,6 +929,11 @@ fn args_info_test_subcommands() {
                             optionality: Optionality::Optional,
                             long: "--speed",
                             short: Some('s'),
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description: "optional speed if not specified it is None.",
                             hidden: false,
                         },
@@ -574,6 +942,11 @@ fn args_info_test_subcommands() {
                             optionality: Optionality::Repeating,
                             long: "--link",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description: "repeatable option.",
                             hidden: false,
                         },
@@ -659,18 +1032,21 @@ fn args_info_test_subcommand_notes_examples() {
                 name: "root",
                 description: "the \"root\" position.",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "trunk",
                 description: "trunk value",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "leaves",
                 description: "leaves. There can be many leaves.",
                 optionality: Optionality::Repeating,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -754,14 +1130,14 @@ fn args_info_test_example() {
                 description: "Destroy the contents of <file> with a specific \"method of destruction\".",
                 examples: &["Scribble 'abc' and then run |grind|.\n$ {command_name} -s 'abc' grind old.txt taxes.cp"],
                 flags: &[HELP_FLAG,
-                FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--force", short: Some('f'), description: "force, ignore minor errors. This description is so long that it wraps to the next line.",
+                FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--force", short: Some('f'), negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "force, ignore minor errors. This description is so long that it wraps to the next line.",
                 hidden:false },
-                FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--really-really-really-long-name-for-pat", short: None, description: "documentation",
+                FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--really-really-really-long-name-for-pat", short: None, negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "documentation",
                 hidden:false },
                 FlagInfo { kind: FlagInfoKind::Option { arg_name: "scribble"},
-                 optionality: Optionality::Required, long: "--scribble", short: Some('s'), description: "write <scribble> repeatedly",
+                 optionality: Optionality::Required, long: "--scribble", short: Some('s'), negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "write <scribble> repeatedly",
                  hidden:false },
-                  FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--verbose", short: Some('v'), description: "say more. Defaults to $BLAST_VERBOSE.",
+                  FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--verbose", short: Some('v'), negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "say more. Defaults to $BLAST_VERBOSE.",
                   hidden:false }
                 ],
                 notes: &["Use `{command_name} help <command>` for details on [<args>] for a subcommand."],
@@ -771,7 +1147,7 @@ fn args_info_test_example() {
                   short: &'\0',
                   description: "explosively separate", 
                   flags:& [HELP_FLAG,
-                   FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--safely", short: None, description: "blow up bombs safely",
+                   FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--safely", short: None, negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "blow up bombs safely",
                    hidden:false }
                    ],
                 ..Default::default()
@@ -782,7 +1158,7 @@ fn args_info_test_example() {
                      name: "grind",
                      description: "make smaller by many small cuts",
                      flags: &[HELP_FLAG,
-                      FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--safely", short: None, description: "wear a visor while grinding" ,hidden:false}],
+                      FlagInfo { kind: FlagInfoKind::Switch, optionality: Optionality::Optional, long: "--safely", short: None, negated_long: None, env: None, aliases: &[], visible_aliases: &[], deprecated: None, description: "wear a visor while grinding" ,hidden:false}],
                       ..Default::default()
                      }
                 }],
@@ -822,6 +1198,11 @@ fn positional_greedy() {
                 optionality: Optionality::Optional,
                 long: "--b",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "woo",
                 hidden: false,
             },
@@ -830,6 +1211,11 @@ fn positional_greedy() {
                 optionality: Optionality::Optional,
                 long: "--c",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "stuff",
                 hidden: false,
             },
@@ -839,12 +1225,52 @@ fn positional_greedy() {
                 name: "a",
                 description: "fooey",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
             PositionalInfo {
                 name: "d",
                 description: "fooey",
                 optionality: Optionality::Greedy,
+                deprecated: None,
+                hidden: false,
+            },
+        ],
+        ..Default::default()
+    });
+}
+
+#[test]
+fn trailing() {
+    #[allow(dead_code)]
+    #[derive(FromArgs, ArgsInfo)]
+    /// Woot
+    struct Run {
+        #[argh(positional)]
+        /// the binary to run
+        pub bin: String,
+        #[argh(trailing)]
+        /// the arguments of the binary
+        pub passthrough: Vec<String>,
+    }
+    assert_args_info::<Run>(&CommandInfoWithArgs {
+        name: "Run",
+        short: &'\0',
+        description: "Woot",
+        flags: &[HELP_FLAG],
+        positionals: &[
+            PositionalInfo {
+                name: "bin",
+                description: "the binary to run",
+                optionality: Optionality::Required,
+                deprecated: None,
+                hidden: false,
+            },
+            PositionalInfo {
+                name: "passthrough",
+                description: "the arguments of the binary",
+                optionality: Optionality::Trailing,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -878,6 +1304,11 @@ fn hidden_help_attribute() {
                 optionality: Optionality::Required,
                 long: "--three",
                 short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
                 description: "this one should be hidden",
                 hidden: true,
             },
@@ -887,12 +1318,14 @@ fn hidden_help_attribute() {
                 name: "one",
                 description: "this one should be hidden",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: true,
             },
             PositionalInfo {
                 name: "two",
                 description: "this one is real",
                 optionality: Optionality::Required,
+                deprecated: None,
                 hidden: false,
             },
         ],
@@ -900,6 +1333,66 @@ fn hidden_help_attribute() {
     });
 }
 
+#[test]
+fn flatten() {
+    #[derive(FromArgs, ArgsInfo)]
+    /// Shared options
+    struct Common {
+        /// be loud
+        #[argh(switch, short = 'v')]
+        _verbose: bool,
+    }
+
+    #[derive(FromArgs, ArgsInfo)]
+    /// Short description
+    struct Cmd {
+        /// the input file
+        #[argh(option)]
+        _input: String,
+        /// shared options
+        #[argh(flatten)]
+        _common: Common,
+    }
+
+    assert_args_info::<Cmd>(&CommandInfoWithArgs {
+        name: "Cmd",
+        description: "Short description",
+        flags: &[
+            HELP_FLAG,
+            FlagInfo {
+                kind: FlagInfoKind::Option { arg_name: "input" },
+                optionality: Optionality::Required,
+                long: "--input",
+                short: None,
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+                description: "the input file",
+                hidden: false,
+            },
+            FlagInfo {
+                kind: FlagInfoKind::Switch,
+                optionality: Optionality::Optional,
+                long: "--verbose",
+                short: Some('v'),
+                negated_long: None,
+                env: None,
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+                description: "be loud",
+                hidden: false,
+            },
+        ],
+        ..Default::default()
+    });
+
+    // The joined flags are only leaked once.
+    assert!(std::ptr::eq(Cmd::get_args_info().flags, Cmd::get_args_info().flags));
+}
+
 #[test]
 fn test_dynamic_subcommand() {
     #[derive(PartialEq, Debug)]
@@ -910,9 +1403,30 @@ fn test_dynamic_subcommand() {
     impl argh::DynamicSubCommand for DynamicSubCommandImpl {
         fn commands() -> &'static [&'static argh::CommandInfo] {
             &[
-                &argh::CommandInfo { name: "three", short: &'\0', description: "Third command" },
-                &argh::CommandInfo { name: "four", short: &'\0', description: "Fourth command" },
-                &argh::CommandInfo { name: "five", short: &'\0', description: "Fifth command" },
+                &argh::CommandInfo {
+                    name: "three",
+                    short: &'\0',
+                    description: "Third command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
+                &argh::CommandInfo {
+                    name: "four",
+                    short: &'\0',
+                    description: "Fourth command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
+                &argh::CommandInfo {
+                    name: "five",
+                    short: &'\0',
+                    description: "Fifth command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
             ]
         }
 
@@ -995,6 +1509,11 @@ fn test_dynamic_subcommand() {
                             optionality: Optionality::Required,
                             long: "--x",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description: "how many x",
                             hidden: false,
                         },
@@ -1015,6 +1534,11 @@ fn test_dynamic_subcommand() {
                             optionality: Optionality::Optional,
                             long: "--fooey",
                             short: None,
+                            negated_long: None,
+                            env: None,
+                            aliases: &[],
+                            visible_aliases: &[],
+                            deprecated: None,
                             description: "whether to fooey",
                             hidden: false,
                         },
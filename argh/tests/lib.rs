@@ -204,9 +204,30 @@ fn dynamic_subcommand_example() {
     impl argh::DynamicSubCommand for DynamicSubCommandImpl {
         fn commands() -> &'static [&'static argh::CommandInfo] {
             &[
-                &argh::CommandInfo { name: "three", short: &'\0', description: "Third command" },
-                &argh::CommandInfo { name: "four", short: &'\0', description: "Fourth command" },
-                &argh::CommandInfo { name: "five", short: &'\0', description: "Fifth command" },
+                &argh::CommandInfo {
+                    name: "three",
+                    short: &'\0',
+                    description: "Third command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
+                &argh::CommandInfo {
+                    name: "four",
+                    short: &'\0',
+                    description: "Fourth command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
+                &argh::CommandInfo {
+                    name: "five",
+                    short: &'\0',
+                    description: "Fifth command",
+                    aliases: &[],
+                    visible_aliases: &[],
+                    deprecated: None,
+                },
             ]
         }
 
@@ -359,6 +380,29 @@ Options:
     );
 }
 
+#[test]
+#[cfg(feature = "help")]
+fn braces_in_descriptions_are_literal() {
+    #[derive(FromArgs)]
+    /// Runs as {command_name}.
+    struct Cmd {
+        #[argh(option)]
+        /// shown as {command_name} in {{the}} help
+        _name: Option<String>,
+    }
+
+    assert_help_string::<Cmd>(
+        r###"Usage: test_arg_0 [--name <name>]
+
+Runs as {command_name}.
+
+Options:
+  --name            shown as {command_name} in {{the}} help
+  --help, help      display usage information
+"###,
+    );
+}
+
 #[test]
 fn explicit_long_value_for_option() {
     #[derive(FromArgs, Debug)]
@@ -407,213 +451,2945 @@ fn default_number() {
         x: u8,
     }
 
-    let cmd = Cmd::from_args(&["cmdname"], &[]).unwrap();
-    assert_eq!(cmd.x, 5);
-}
+    let cmd = Cmd::from_args(&["cmdname"], &[]).unwrap();
+    assert_eq!(cmd.x, 5);
+}
+
+#[test]
+fn default_function() {
+    const MSG: &str = "hey I just met you";
+    fn call_me_maybe() -> String {
+        MSG.to_owned()
+    }
+
+    #[derive(FromArgs)]
+    /// Short description
+    struct Cmd {
+        #[argh(option, default = "call_me_maybe()")]
+        /// fooey
+        msg: String,
+    }
+
+    let cmd = Cmd::from_args(&["cmdname"], &[]).unwrap();
+    assert_eq!(cmd.msg, MSG);
+}
+
+#[test]
+fn missing_option_value() {
+    #[derive(FromArgs, Debug)]
+    /// Short description
+    struct Cmd {
+        #[argh(option)]
+        /// fooey
+        _msg: String,
+    }
+
+    let e = Cmd::from_args(&["cmdname"], &["--msg"])
+        .expect_err("Parsing missing option value should fail");
+    assert_eq!(e.output, "No value provided for option \'--msg\'.\n");
+    assert!(e.status.is_err());
+}
+
+#[cfg(feature = "help")]
+fn assert_help_string<T: FromArgs>(help_str: &str) {
+    match T::from_args(&["test_arg_0"], &["--help"]) {
+        Ok(_) => panic!("help was parsed as args"),
+        Err(e) => {
+            assert_eq!(help_str, e.output);
+            e.status.expect("help returned an error");
+        }
+    }
+}
+
+fn assert_output<T: FromArgs + Debug + PartialEq>(args: &[&str], expected: T) {
+    let t = T::from_args(&["cmd"], args).expect("failed to parse");
+    assert_eq!(t, expected);
+}
+
+fn assert_error<T: FromArgs + Debug>(args: &[&str], err_msg: &str) {
+    let e = T::from_args(&["cmd"], args).expect_err("unexpectedly succeeded parsing");
+    assert_eq!(err_msg, e.output);
+    e.status.expect_err("error had a positive status");
+}
+
+mod options {
+    use super::*;
+
+    #[derive(argh::FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Parsed {
+        #[argh(option, short = 'n')]
+        /// fooey
+        n: usize,
+    }
+
+    #[test]
+    fn parsed() {
+        assert_output(&["-n", "5"], Parsed { n: 5 });
+        assert_error::<Parsed>(
+            &["-n", "x"],
+            r###"Error parsing option '-n' with value 'x': invalid digit found in string
+"###,
+        );
+    }
+
+    #[test]
+    fn parsed_equals() {
+        assert_output(&["--n=5"], Parsed { n: 5 });
+        assert_error::<Parsed>(
+            &["--n=x"],
+            r###"Error parsing option '--n' with value 'x': invalid digit found in string
+"###,
+        );
+        assert_error::<Parsed>(
+            &["--n="],
+            r###"Error parsing option '--n' with value '': cannot parse integer from empty string
+"###,
+        );
+    }
+
+    #[test]
+    fn short_attached_value() {
+        assert_output(&["-n5"], Parsed { n: 5 });
+        assert_error::<Parsed>(
+            &["-n5x"],
+            r###"Error parsing option '-n' with value '5x': invalid digit found in string
+"###,
+        );
+        assert_output(&["-n-x", "-nfoo"], Repeating { n: vec!["-x".into(), "foo".into()] });
+    }
+
+    #[derive(argh::FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct SwitchesAndOption {
+        #[argh(switch, short = 'v')]
+        /// verbosity
+        verbose: u8,
+        #[argh(switch, short = 'q')]
+        /// quiet
+        quiet: bool,
+        #[argh(option, short = 'n')]
+        /// count
+        n: Option<usize>,
+    }
+
+    #[test]
+    fn short_group_with_value() {
+        assert_output(&["-vvq"], SwitchesAndOption { verbose: 2, quiet: true, n: None });
+        assert_output(&["-vn5"], SwitchesAndOption { verbose: 1, quiet: false, n: Some(5) });
+        assert_output(&["-vqn", "5"], SwitchesAndOption { verbose: 1, quiet: true, n: Some(5) });
+        assert_error::<SwitchesAndOption>(
+            &["-nvq"],
+            r###"Error parsing option '-n' with value 'vq': invalid digit found in string
+"###,
+        );
+        assert_error::<SwitchesAndOption>(&["-vn"], "No value provided for option '-n'.\n");
+    }
+
+    #[test]
+    fn equals_value_may_start_with_dash() {
+        assert_output(
+            &["--n=-5", "--n", "x", "--n=a=b"],
+            Repeating { n: vec!["-5".into(), "x".into(), "a=b".into()] },
+        );
+    }
+
+    #[test]
+    fn equals_rejected_for_switch() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            #[argh(switch)]
+            /// fooey
+            s: bool,
+        }
+
+        assert_output(&["--s"], Cmd { s: true });
+        assert_error::<Cmd>(&["--s=true"], "Switch '--s' does not take a value.\n");
+    }
+
+    #[derive(argh::FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Repeating {
+        #[argh(option, short = 'n')]
+        /// fooey
+        n: Vec<String>,
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn repeating() {
+        assert_help_string::<Repeating>(
+            r###"Usage: test_arg_0 [-n <n...>]
+
+Woot
+
+Options:
+  -n, --n           fooey
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[derive(argh::FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Delimited {
+        #[argh(option, short = 't', delimiter = ',')]
+        /// tags
+        tag: Vec<String>,
+        #[argh(option, delimiter = ':', default = "vec![1]")]
+        /// ports
+        port: Vec<u16>,
+    }
+
+    #[test]
+    fn delimited() {
+        assert_output(&[], Delimited { tag: vec![], port: vec![1] });
+        assert_output(
+            &["--tag", "a,b", "-tc", "--tag=d,,e", "--port", "80:443"],
+            Delimited {
+                tag: vec!["a".into(), "b".into(), "c".into(), "d".into(), "".into(), "e".into()],
+                port: vec![80, 443],
+            },
+        );
+        assert_output(
+            &["--tag", r"a\,b,c\d"],
+            Delimited { tag: vec!["a,b".into(), r"c\d".into()], port: vec![1] },
+        );
+        assert_error::<Delimited>(
+            &["--port", "80:x:443"],
+            r###"Error parsing option '--port' with value 'x': invalid digit found in string
+"###,
+        );
+    }
+
+    #[test]
+    fn delimited_redact() {
+        let actual =
+            Delimited::redact_arg_values(&["program-name"], &["--tag", "a,b", "-t", "c"]).unwrap();
+        assert_eq!(actual, &["program-name", "--tag", "-t"]);
+    }
+
+    #[derive(argh::FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct WithArgName {
+        #[argh(option, arg_name = "name")]
+        /// fooey
+        option_name: Option<String>,
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn with_arg_name() {
+        assert_help_string::<WithArgName>(
+            r###"Usage: test_arg_0 [--option-name <name>]
+
+Woot
+
+Options:
+  --option-name     fooey
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    /// Test choices
+    #[derive(FromArgs, PartialEq, Debug)]
+    struct WithChoices {
+        /// first choice with a default
+        #[argh(option, default = "TwoChoices::Chao")]
+        choice1: TwoChoices,
+        /// second choice.
+        #[argh(option)]
+        choice2: ThreeChoices,
+    }
+
+    #[derive(FromArgValue, PartialEq, Debug)]
+    enum TwoChoices {
+        Hola,
+        Chao,
+    }
+
+    #[derive(FromArgValue, PartialEq, Debug)]
+    enum ThreeChoices {
+        FirstChoice,
+        #[argh(name = "に")]
+        Two,
+        Three,
+    }
+
+    #[test]
+    fn with_choices() {
+        assert_output(
+            &["--choice2", "three"],
+            WithChoices { choice1: TwoChoices::Chao, choice2: ThreeChoices::Three },
+        );
+    }
+
+    #[test]
+    fn with_choices_snake_case() {
+        assert_output(
+            &["--choice2", "first_choice"],
+            WithChoices { choice1: TwoChoices::Chao, choice2: ThreeChoices::FirstChoice },
+        )
+    }
+
+    #[test]
+    fn override_default() {
+        assert_output(
+            &["--choice2", "first_choice", "--choice1", "hola"],
+            WithChoices { choice1: TwoChoices::Hola, choice2: ThreeChoices::FirstChoice },
+        )
+    }
+
+    #[test]
+    fn with_name_override() {
+        assert_output(
+            &["--choice2", "に", "--choice1", "hola"],
+            WithChoices { choice1: TwoChoices::Hola, choice2: ThreeChoices::Two },
+        )
+    }
+
+    #[test]
+    fn invalid_choice() {
+        assert_error::<WithChoices>(
+            &["--choice2", "something"],
+            r###"Error parsing option '--choice2' with value 'something': expected "first_choice", "に" or "three"
+"###,
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn choice_help() {
+        assert_help_string::<WithChoices>(
+            r###"Usage: test_arg_0 [--choice1 <choice1>] --choice2 <choice2>
+
+Test choices
+
+Options:
+  --choice1         first choice with a default
+  --choice2         second choice.
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+mod optional_value {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, short = 'c', default_missing_value = "always", default = "\"auto\".into()")]
+        /// when to colorize
+        color: String,
+        #[argh(option, default_missing_value = "1", arg_name = "level")]
+        /// how much to optimize
+        opt: Vec<u8>,
+        #[argh(positional)]
+        /// inputs
+        inputs: Vec<String>,
+    }
+
+    #[test]
+    fn value_is_optional() {
+        assert_output(&[], Cmd { color: "auto".into(), opt: vec![], inputs: vec![] });
+        assert_output(&["--color"], Cmd { color: "always".into(), opt: vec![], inputs: vec![] });
+        assert_output(&["-c"], Cmd { color: "always".into(), opt: vec![], inputs: vec![] });
+        assert_output(
+            &["--color=never", "--opt", "--opt=3"],
+            Cmd { color: "never".into(), opt: vec![1, 3], inputs: vec![] },
+        );
+    }
+
+    #[test]
+    fn never_takes_next_argument() {
+        assert_output(
+            &["--color", "never"],
+            Cmd { color: "always".into(), opt: vec![], inputs: vec!["never".into()] },
+        );
+        assert_output(
+            &["-c", "--opt", "2"],
+            Cmd { color: "always".into(), opt: vec![1], inputs: vec!["2".into()] },
+        );
+    }
+
+    #[test]
+    fn invalid_or_duplicate_values_are_errors() {
+        assert_error::<Cmd>(
+            &["--opt=x"],
+            "Error parsing option '--opt' with value 'x': invalid digit found in string\n",
+        );
+        assert_error::<Cmd>(
+            &["--color", "--color=never"],
+            "Error parsing option '--color' with value 'never': duplicate values provided\n",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_shows_optional_values() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [--color[=<color>]] [--opt[=<level...>]] [--] [<inputs...>]
+
+Woot
+
+Positional Arguments:
+  inputs            inputs
+
+Options:
+  -c, --color       when to colorize
+  --opt             how much to optimize
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[test]
+    fn redact_keeps_options_without_values() {
+        let actual =
+            Cmd::redact_arg_values(&["program-name"], &["--color=never", "--opt", "x"]).unwrap();
+        assert_eq!(actual, &["program-name", "--color", "--opt", "inputs"]);
+    }
+}
+
+mod aliases {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, short = 'n', alias = "old-name", visible_alias = "x")]
+        /// the name
+        name: Option<String>,
+        #[argh(switch, alias = "q", visible_alias = "silent")]
+        /// say less
+        quiet: bool,
+        #[argh(subcommand)]
+        sub: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum SubCommandEnum {
+        Remove(Remove),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Remove a file.
+    #[argh(subcommand, name = "remove", alias = "rm", visible_alias = "delete")]
+    struct Remove {
+        #[argh(positional)]
+        /// the file
+        file: String,
+    }
+
+    #[test]
+    fn aliases_are_parsed_as_the_argument() {
+        for args in [&["--name", "a"][..], &["-n", "a"], &["--old-name", "a"], &["-x", "a"]] {
+            assert_output(args, Cmd { name: Some("a".into()), quiet: false, sub: None });
+        }
+        for args in [&["--quiet"][..], &["-q"], &["--silent"]] {
+            assert_output(args, Cmd { name: None, quiet: true, sub: None });
+        }
+    }
+
+    #[test]
+    fn subcommand_aliases_are_parsed() {
+        for name in ["remove", "rm", "delete"] {
+            assert_output(
+                &[name, "f"],
+                Cmd {
+                    name: None,
+                    quiet: false,
+                    sub: Some(SubCommandEnum::Remove(Remove { file: "f".into() })),
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn subcommands_sharing_names_without_aliases() {
+        // Only collisions which involve an alias fail to compile.
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Tool {
+            #[argh(subcommand)]
+            sub: ToolCommand,
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argh(subcommand)]
+        enum ToolCommand {
+            Run(Run),
+            Reset(Reset),
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Run it.
+        #[argh(subcommand, name = "run", short = 'r')]
+        struct Run {}
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Reset it.
+        #[argh(subcommand, name = "reset", short = 'r')]
+        struct Reset {}
+
+        assert_output(&["r"], Tool { sub: ToolCommand::Run(Run {}) });
+        assert_output(&["reset"], Tool { sub: ToolCommand::Reset(Reset {}) });
+    }
+
+    #[test]
+    fn duplicates_across_aliases() {
+        assert_error::<Cmd>(
+            &["--name", "a", "--old-name", "b"],
+            "Error parsing option '--old-name' with value 'b': duplicate values provided\n",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "suggestions")]
+    fn hidden_aliases_are_not_suggested() {
+        assert_error::<Cmd>(&["--old-nam", "a"], "Unrecognized argument: --old-nam\n");
+        assert_error::<Cmd>(
+            &["--silen"],
+            "Unrecognized argument: \"--silen\". Did you mean \"--silent\"?\n",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_shows_visible_aliases() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [-n <name>] [--quiet] [<command>] [<args>]
+
+Woot
+
+Options:
+  -n, --name, -x    the name
+  --quiet, --silent say less
+  --help, help      display usage information
+
+Commands:
+  remove, delete    Remove a file.
+"###,
+        );
+    }
+
+    #[test]
+    fn redact_keeps_option_aliases() {
+        let actual =
+            Cmd::redact_arg_values(&["program-name"], &["--old-name", "a", "rm", "f"]).unwrap();
+        assert_eq!(actual, &["program-name", "--old-name", "remove", "file"]);
+    }
+}
+
+mod deprecated {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, deprecated = "use --region instead")]
+        /// the zone
+        zone: Option<String>,
+        #[argh(option)]
+        /// the region
+        region: Option<String>,
+        #[argh(switch, deprecated)]
+        /// use the old API
+        legacy: bool,
+        #[argh(option, default = "Mode::Fast")]
+        /// the mode
+        mode: Mode,
+        #[argh(subcommand)]
+        sub: Option<SubCommandEnum>,
+    }
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        #[argh(deprecated = "use fast instead")]
+        Quick,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum SubCommandEnum {
+        Old(Old),
+        New(New),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// The old way.
+    #[argh(subcommand, name = "old", deprecated = "use new instead")]
+    struct Old {}
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// The new way.
+    #[argh(subcommand, name = "new")]
+    struct New {}
+
+    #[test]
+    fn warnings_are_recorded_for_deprecated_arguments() {
+        argh::take_warnings();
+
+        let cmd = Cmd::from_args(&["cmdname"], &["--region", "us", "new"]).unwrap();
+        assert_eq!(cmd.region.as_deref(), Some("us"));
+        assert_eq!(argh::take_warnings(), Vec::<String>::new());
+
+        let cmd =
+            Cmd::from_args(&["cmdname"], &["--zone", "us-1", "--legacy", "--mode", "quick", "old"])
+                .unwrap();
+        assert_eq!(
+            cmd,
+            Cmd {
+                zone: Some("us-1".into()),
+                region: None,
+                legacy: true,
+                mode: Mode::Quick,
+                sub: Some(SubCommandEnum::Old(Old {})),
+            }
+        );
+        assert_eq!(
+            argh::take_warnings(),
+            [
+                "Value 'quick' is deprecated: use fast instead",
+                "Command 'old' is deprecated: use new instead",
+                "Option '--zone' is deprecated: use --region instead",
+                "Switch '--legacy' is deprecated",
+            ]
+        );
+        assert_eq!(argh::take_warnings(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn no_warnings_when_parsing_fails() {
+        #[derive(FromArgs, Debug)]
+        /// Woot
+        struct Required {
+            #[argh(option, deprecated = "use --region instead")]
+            /// the zone
+            _zone: Option<String>,
+            #[argh(option)]
+            /// the user
+            _user: String,
+        }
+
+        argh::take_warnings();
+        assert!(Required::from_args(&["cmdname"], &["--zone", "us-1"]).is_err());
+        assert_eq!(argh::take_warnings(), Vec::<String>::new());
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_shows_deprecation_notes() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [--zone <zone>] [--region <region>] [--legacy] [--mode <mode>] [<command>] [<args>]
+
+Woot
+
+Options:
+  --zone            the zone [deprecated: use --region instead]
+  --region          the region
+  --legacy          use the old API [deprecated]
+  --mode            the mode
+  --help, help      display usage information
+
+Commands:
+  old               The old way. [deprecated: use new instead]
+  new               The new way.
+"###,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn braces_in_notes_are_literal() {
+        #[derive(FromArgs)]
+        #[allow(dead_code)]
+        /// Woot
+        struct Braces {
+            #[argh(option, deprecated = "pass --name={value} instead")]
+            /// the user
+            user: Option<String>,
+            #[argh(positional, deprecated = "use {} instead")]
+            /// the target
+            target: Option<String>,
+        }
+
+        assert_help_string::<Braces>(
+            r###"Usage: test_arg_0 [--user <user>] [--] [<target>]
+
+Woot
+
+Positional Arguments:
+  target            the target [deprecated: use {} instead]
+
+Options:
+  --user            the user [deprecated: pass --name={value} instead]
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+mod parse_error {
+    use {super::*, argh::ParseError};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option)]
+        /// how many
+        count: Option<u32>,
+        #[argh(option)]
+        /// the name
+        name: Vec<String>,
+        #[argh(switch, short = 'v')]
+        /// be loud
+        verbose: bool,
+        #[argh(subcommand)]
+        sub: Option<Sub>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// A subcommand.
+    #[argh(subcommand, name = "sub")]
+    struct Sub {
+        #[argh(positional)]
+        /// the size
+        size: u8,
+        #[argh(option)]
+        /// the mode
+        mode: Option<u8>,
+    }
+
+    fn reason(args: &[&str]) -> ParseError {
+        Cmd::try_parse(&["cmdname"], args).unwrap_err()
+    }
+
+    #[test]
+    fn variants() {
+        assert_eq!(
+            reason(&["--verbose", "--count", "x"]),
+            ParseError::InvalidValue {
+                index: 2,
+                arg: "--count".into(),
+                value: "x".into(),
+                source: "invalid digit found in string".into(),
+            }
+        );
+        assert_eq!(
+            reason(&["--count=1", "--count=2"]),
+            ParseError::DuplicateValue { index: 1, arg: "--count".into(), value: "2".into() }
+        );
+        assert_eq!(
+            reason(&["--name"]),
+            ParseError::MissingValue { index: 0, option: "--name".into() }
+        );
+        assert_eq!(
+            reason(&["-v", "--verbose=1"]),
+            ParseError::UnexpectedValue { index: 1, switch: "--verbose".into() }
+        );
+        assert_eq!(reason(&["help", "-v"]), ParseError::ArgumentAfterHelp { index: 1 });
+        assert!(matches!(reason(&["--help"]), ParseError::HelpRequested { .. }));
+
+        let unknown = reason(&["-v", "--bogus"]);
+        assert!(matches!(
+            unknown,
+            ParseError::UnknownArgument { index: 1, ref arg, .. } if arg == "--bogus"
+        ));
+        assert_eq!(unknown.index(), Some(1));
+    }
+
+    #[test]
+    fn subcommand_indices_are_relative_to_the_top_level() {
+        assert_eq!(
+            reason(&["-v", "sub", "--mode", "1", "big"]),
+            ParseError::InvalidPositional {
+                index: 4,
+                name: "size".into(),
+                value: "big".into(),
+                source: "invalid digit found in string".into(),
+            }
+        );
+        assert_eq!(
+            reason(&["help", "sub", "1", "--mode"]),
+            ParseError::ArgumentAfterHelp { index: 3 }
+        );
+        assert_eq!(
+            reason(&["sub", "1", "--mode"]),
+            ParseError::MissingValue { index: 2, option: "--mode".into() }
+        );
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Takes no options, not even for help.
+    #[argh(help_triggers(""))]
+    struct NoOptions {}
+
+    #[test]
+    fn unknown_option_without_options_is_quoted() {
+        assert_eq!(
+            NoOptions::try_parse(&["cmdname"], &["--bogus"]).unwrap_err(),
+            ParseError::UnknownArgument {
+                index: 0,
+                arg: "--bogus".into(),
+                suggestion: None,
+                no_options: true,
+            }
+        );
+        assert_error::<NoOptions>(&["--bogus"], "Unrecognized argument: \"--bogus\"\n");
+    }
+
+    #[test]
+    fn missing_requirements() {
+        let error = reason(&["sub"]);
+        let missing = match &error {
+            ParseError::MissingRequirements(missing) => missing,
+            reason => panic!("unexpected reason: {:?}", reason),
+        };
+        assert_eq!(missing.positional_args(), ["size"]);
+        assert!(missing.options().is_empty());
+        assert_eq!(error.index(), None);
+    }
+
+    #[test]
+    fn early_exit_matches_from_args() {
+        for args in
+            [&["--count", "x"][..], &["--bogus"], &["help", "-v"], &["sub", "1", "2"], &["--help"]]
+        {
+            let early_exit = Cmd::from_args(&["cmdname"], args).unwrap_err();
+            assert_eq!(early_exit, argh::EarlyExit::from(reason(args)), "{:?}", args);
+        }
+    }
+}
+
+mod collect_errors {
+    use {super::*, argh::ParseError};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    #[argh(collect_errors)]
+    struct Cmd {
+        #[argh(option)]
+        /// how many
+        count: u32,
+        #[argh(option)]
+        /// the size
+        size: Option<u8>,
+        #[argh(positional)]
+        /// the first
+        first: u8,
+        #[argh(positional)]
+        /// the second
+        second: Option<u8>,
+        #[argh(subcommand)]
+        sub: Option<Sub>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// A subcommand.
+    #[argh(subcommand, name = "sub")]
+    struct Sub {
+        #[argh(option)]
+        /// the mode
+        mode: u8,
+        #[argh(switch)]
+        /// be loud
+        verbose: bool,
+    }
+
+    #[test]
+    fn valid_arguments_are_parsed() {
+        assert_eq!(
+            Cmd::from_args(&["cmdname"], &["--count", "1", "2", "sub", "--mode", "3"]).unwrap(),
+            Cmd {
+                count: 1,
+                size: None,
+                first: 2,
+                second: None,
+                sub: Some(Sub { mode: 3, verbose: false })
+            }
+        );
+    }
+
+    #[test]
+    fn errors_are_reported_together() {
+        let args = ["x", "--size", "big", "--bogus", "y"];
+        let error = Cmd::try_parse(&["cmdname"], &args).unwrap_err();
+        let ParseError::Multiple(errors) = &error else { panic!("unexpected reason: {:?}", error) };
+        assert_eq!(
+            errors.iter().map(ParseError::index).collect::<Vec<_>>(),
+            [Some(0), Some(2), Some(3), Some(4), None]
+        );
+        let [first, size, bogus, second, ParseError::MissingRequirements(missing)] =
+            errors.as_slice()
+        else {
+            panic!("unexpected errors: {:?}", errors)
+        };
+        assert!(matches!(first, ParseError::InvalidPositional { name, .. } if name == "first"));
+        assert!(matches!(size, ParseError::InvalidValue { arg, .. } if arg == "--size"));
+        assert!(matches!(bogus, ParseError::UnknownArgument { arg, .. } if arg == "--bogus"));
+        assert!(matches!(second, ParseError::InvalidPositional { name, .. } if name == "second"));
+        // `first` was provided, so only `--count` is missing.
+        assert_eq!(missing.options(), ["--count"]);
+        assert!(missing.positional_args().is_empty());
+        assert_eq!(error.index(), Some(0));
+        let early_exit = Cmd::from_args(&["cmdname"], &args).unwrap_err();
+        assert_eq!(early_exit.status, Err(()));
+        assert_eq!(early_exit.output, errors.iter().map(ParseError::to_string).collect::<String>());
+    }
+
+    #[test]
+    fn subcommands_collect_errors() {
+        let args = ["--count", "1", "2", "sub", "--verbose=1", "--mode", "x", "extra"];
+        let early_exit = Cmd::from_args(&["cmdname"], &args).unwrap_err();
+        assert_eq!(
+            early_exit.output,
+            concat!(
+                "Switch '--verbose' does not take a value.\n",
+                "Error parsing option '--mode' with value 'x': invalid digit found in string\n",
+                "Unrecognized argument: extra\n",
+            ),
+        );
+        let ParseError::Multiple(errors) = Cmd::try_parse(&["cmdname"], &args).unwrap_err() else {
+            panic!("expected errors")
+        };
+        assert_eq!(
+            errors.iter().map(ParseError::index).collect::<Vec<_>>(),
+            [Some(4), Some(6), Some(7)]
+        );
+    }
+
+    #[test]
+    fn single_error_is_not_wrapped() {
+        assert_eq!(
+            Cmd::try_parse(&["cmdname"], &["2", "--count"]).unwrap_err(),
+            ParseError::MissingValue { index: 1, option: "--count".into() },
+        );
+    }
+
+    #[test]
+    fn help_is_reported_on_its_own() {
+        let early_exit = Cmd::from_args(&["cmdname"], &["--bogus", "--help"]).unwrap_err();
+        assert_eq!(early_exit.status, Ok(()));
+        let error = Cmd::try_parse(&["cmdname"], &["--bogus", "--help"]).unwrap_err();
+        assert!(matches!(error, ParseError::HelpRequested { .. }));
+    }
+}
+
+#[cfg(feature = "suggestions")]
+mod suggestions {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, default = "Speed::Fast")]
+        /// how fast to go
+        speed: Speed,
+        #[argh(subcommand)]
+        sub: SubCommandEnum,
+    }
+
+    #[derive(FromArgValue, Debug, PartialEq)]
+    enum Speed {
+        Fast,
+        Slow,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum SubCommandEnum {
+        Deploy(Deploy),
+        #[argh(dynamic)]
+        Plugin(Plugin),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Deploy it.
+    #[argh(subcommand, name = "deploy", visible_alias = "ship", alias = "push")]
+    struct Deploy {}
+
+    #[derive(Debug, PartialEq)]
+    struct Plugin;
+
+    impl argh::DynamicSubCommand for Plugin {
+        fn commands() -> &'static [&'static argh::CommandInfo] {
+            &[&argh::CommandInfo {
+                name: "rollback",
+                short: &'\0',
+                description: "Roll back a deployment",
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
+            }]
+        }
+
+        fn try_redact_arg_values(
+            _command_name: &[&str],
+            _args: &[&str],
+        ) -> Option<Result<Vec<String>, argh::EarlyExit>> {
+            None
+        }
+
+        fn try_from_args(
+            command_name: &[&str],
+            _args: &[&str],
+        ) -> Option<Result<Self, argh::EarlyExit>> {
+            (command_name.last() == Some(&"rollback")).then_some(Ok(Plugin))
+        }
+    }
+
+    fn suggestion(args: &[&str]) -> Option<String> {
+        match Cmd::try_parse(&["cmdname"], args).unwrap_err() {
+            argh::ParseError::UnknownArgument { suggestion, .. } => suggestion,
+            reason => panic!("unexpected reason: {:?}", reason),
+        }
+    }
+
+    #[test]
+    fn subcommands() {
+        assert_eq!(suggestion(&["deplyo"]).as_deref(), Some("deploy"));
+        assert_eq!(suggestion(&["shipp"]).as_deref(), Some("ship"));
+        assert_eq!(suggestion(&["rolback"]).as_deref(), Some("rollback"));
+        assert_eq!(
+            Cmd::from_args(&["cmdname"], &["deplyo"]).unwrap_err().output,
+            "Unrecognized argument: \"deplyo\". Did you mean \"deploy\"?\n",
+        );
+    }
+
+    #[test]
+    fn hidden_aliases_are_not_suggested() {
+        assert_eq!(suggestion(&["pushh"]), None);
+    }
+
+    #[test]
+    fn help_triggers() {
+        assert_eq!(suggestion(&["helpp"]).as_deref(), Some("help"));
+        assert_eq!(suggestion(&["--hlp"]).as_deref(), Some("--help"));
+    }
+
+    #[test]
+    fn poor_matches_are_not_suggested() {
+        assert_eq!(suggestion(&["zzz"]), None);
+        assert_eq!(suggestion(&["--zz"]), None);
+        assert_eq!(
+            Cmd::from_args(&["cmdname"], &["zzz"]).unwrap_err().output,
+            "Unrecognized argument: zzz\n"
+        );
+    }
+
+    #[test]
+    fn choice_values() {
+        assert_eq!(
+            Cmd::from_args(&["cmdname"], &["--speed", "fastt", "deploy"]).unwrap_err().output,
+            "Error parsing option '--speed' with value 'fastt': expected \"fast\" or \"slow\". \
+             Did you mean \"fast\"?\n",
+        );
+        assert_eq!(
+            Cmd::from_args(&["cmdname"], &["--speed", "zzz", "deploy"]).unwrap_err().output,
+            "Error parsing option '--speed' with value 'zzz': expected \"fast\" or \"slow\"\n",
+        );
+    }
+}
+
+mod exit_codes {
+    use {
+        super::*,
+        argh::FromEnvError,
+        std::process::{ExitCode, Termination},
+    };
+
+    #[derive(FromArgs, Debug)]
+    /// Chop down trees.
+    #[argh(error_code(3, "The blade is too dull."), error_code(300, "Out of fuel."))]
+    struct Chop {
+        #[argh(switch)]
+        /// chop faster
+        _fast: bool,
+    }
+
+    #[test]
+    fn declared_codes() {
+        let codes: Vec<i32> = Chop::error_codes().iter().map(|info| info.code).collect();
+        assert_eq!(codes, [3, 300]);
+        assert_eq!(Box::<Chop>::error_codes(), Chop::error_codes());
+
+        #[derive(FromArgs)]
+        /// Plant trees.
+        struct Plant {}
+        assert!(Plant::error_codes().is_empty());
+    }
+
+    #[test]
+    fn exit_code() {
+        assert_eq!(argh::exit_code::<Chop>(0), ExitCode::SUCCESS);
+        assert_eq!(argh::exit_code::<Chop>(3), ExitCode::from(3));
+        assert_eq!(argh::exit_code::<Chop>(300), ExitCode::FAILURE);
+    }
+
+    #[test]
+    #[should_panic(expected = "error code 4 is not declared by")]
+    fn undeclared_exit_code() {
+        let _ = argh::exit_code::<Chop>(4);
+    }
+
+    #[test]
+    fn usage_errors() {
+        let early_exit = Chop::from_args(&["chop"], &["--slow"]).unwrap_err();
+        let error = FromEnvError::EarlyExit(early_exit, "chop".into());
+        assert_eq!(error.report(), ExitCode::from(2));
+
+        let early_exit = Chop::from_args(&["chop"], &["--help"]).unwrap_err();
+        let error = FromEnvError::EarlyExit(early_exit, "chop".into());
+        assert_eq!(error.report(), ExitCode::SUCCESS);
+    }
+}
+
+mod flatten {
+    use {super::*, argh::ParseError};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Options shared by several commands.
+    struct Common {
+        #[argh(switch, short = 'v')]
+        /// be loud
+        verbose: bool,
+        #[argh(option, short = 'j')]
+        /// how many jobs to run
+        jobs: Option<u32>,
+        #[argh(flatten)]
+        /// colors
+        color: Color,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Color options.
+    struct Color {
+        #[argh(option, default = "String::from(\"auto\")")]
+        /// when to use colors
+        color: String,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Build things.
+    struct Build {
+        #[argh(option)]
+        /// the target to build
+        target: String,
+        #[argh(flatten)]
+        /// common options
+        common: Common,
+        #[argh(positional)]
+        /// the packages to build
+        packages: Vec<String>,
+    }
+
+    #[test]
+    fn flattened_options_are_parsed_among_those_of_the_parent() {
+        assert_output(
+            &["-v", "a", "--jobs", "4", "--target=arm", "b"],
+            Build {
+                target: "arm".into(),
+                common: Common {
+                    verbose: true,
+                    jobs: Some(4),
+                    color: Color { color: "auto".into() },
+                },
+                packages: vec!["a".into(), "b".into()],
+            },
+        );
+    }
+
+    #[test]
+    fn flattened_short_names_run_together() {
+        assert_output(
+            &["--target", "x86", "-vj8"],
+            Build {
+                target: "x86".into(),
+                common: Common {
+                    verbose: true,
+                    jobs: Some(8),
+                    color: Color { color: "auto".into() },
+                },
+                packages: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn nested_flattened_options_are_parsed() {
+        let parsed = Build::from_args(&["cmd"], &["--target", "x86", "--color", "never"]).unwrap();
+        assert_eq!(parsed.common.color, Color { color: "never".into() });
+    }
+
+    #[test]
+    fn invalid_flattened_options_are_errors() {
+        assert_error::<Build>(
+            &["--target", "x86", "--verbose=1"],
+            "Switch '--verbose' does not take a value.\n",
+        );
+        assert_error::<Build>(
+            &["--target", "x86", "--jobs"],
+            "No value provided for option '--jobs'.\n",
+        );
+        assert_error::<Build>(&["-v"], "Required options not provided:\n    --target\n");
+    }
+
+    #[test]
+    fn error_indices_are_those_of_the_parent() {
+        let reason =
+            Build::try_parse(&["cmd"], &["a", "--target", "x86", "-j", "many"]).unwrap_err();
+        assert_eq!(
+            reason,
+            ParseError::InvalidValue {
+                index: 4,
+                arg: "-j".into(),
+                value: "many".into(),
+                source: "invalid digit found in string".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_requirements_are_merged() {
+        #[derive(FromArgs, Debug)]
+        /// Connection options.
+        struct Connection {
+            #[argh(option)]
+            /// the host
+            _host: String,
+        }
+
+        #[derive(FromArgs, Debug)]
+        /// Connect.
+        struct Connect {
+            #[argh(option)]
+            /// the user
+            _user: String,
+            #[argh(flatten)]
+            /// connection options
+            _connection: Connection,
+        }
+
+        assert_error::<Connect>(&[], "Required options not provided:\n    --host\n    --user\n");
+    }
+
+    #[test]
+    fn flattened_options_are_redacted() {
+        let redacted = Build::redact_arg_values(
+            &["cmd"],
+            &["a", "-v", "--target", "x86", "--jobs=4", "--color", "always"],
+        )
+        .unwrap();
+        assert_eq!(redacted, ["cmd", "--target", "-v", "--jobs", "--color", "packages"],);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_lists_flattened_options() {
+        assert_help_string::<Build>(
+            r###"Usage: test_arg_0 --target <target> [-v] [-j <jobs>] [--color <color>] [--] [<packages...>]
+
+Build things.
+
+Positional Arguments:
+  packages          the packages to build
+
+Options:
+  --target          the target to build
+  -v, --verbose     be loud
+  -j, --jobs        how many jobs to run
+  --color           when to use colors
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+mod global_options {
+    use {super::*, argh::ParseError};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Manage a repository.
+    struct Repo {
+        #[argh(switch, short = 'v', global)]
+        /// be loud
+        verbose: bool,
+        #[argh(option, global)]
+        /// the directory of the repository
+        dir: Option<String>,
+        #[argh(subcommand)]
+        command: RepoCommand,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum RepoCommand {
+        Remote(Remote),
+        Status(Status),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Manage remotes.
+    #[argh(subcommand, name = "remote")]
+    struct Remote {
+        #[argh(switch, global)]
+        /// show urls
+        urls: bool,
+        #[argh(subcommand)]
+        command: Option<RemoteCommand>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum RemoteCommand {
+        Add(Add),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Add a remote.
+    #[argh(subcommand, name = "add")]
+    struct Add {
+        #[argh(switch, short = 'f')]
+        /// fetch after adding
+        fetch: bool,
+        #[argh(positional)]
+        /// the name of the remote
+        name: String,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Show the status.
+    #[argh(subcommand, name = "status")]
+    struct Status {
+        #[argh(option, short = 'j')]
+        /// how many jobs to run
+        jobs: Option<u32>,
+    }
+
+    #[test]
+    fn parsed_before_the_subcommand() {
+        assert_output(
+            &["-v", "--dir", "x", "status"],
+            Repo {
+                verbose: true,
+                dir: Some("x".into()),
+                command: RepoCommand::Status(Status { jobs: None }),
+            },
+        );
+    }
+
+    #[test]
+    fn parsed_after_the_subcommand() {
+        assert_output(
+            &["status", "-j2", "-v", "--dir", "x"],
+            Repo {
+                verbose: true,
+                dir: Some("x".into()),
+                command: RepoCommand::Status(Status { jobs: Some(2) }),
+            },
+        );
+        assert_output(
+            &["status", "--verbose", "--dir=x"],
+            Repo {
+                verbose: true,
+                dir: Some("x".into()),
+                command: RepoCommand::Status(Status { jobs: None }),
+            },
+        );
+    }
+
+    #[test]
+    fn short_names_run_together_with_those_of_the_subcommand() {
+        assert_output(
+            &["status", "-vj2"],
+            Repo {
+                verbose: true,
+                dir: None,
+                command: RepoCommand::Status(Status { jobs: Some(2) }),
+            },
+        );
+    }
+
+    #[test]
+    fn parsed_at_any_depth() {
+        assert_output(
+            &["remote", "add", "-v", "origin", "--urls", "--dir", "x", "-f"],
+            Repo {
+                verbose: true,
+                dir: Some("x".into()),
+                command: RepoCommand::Remote(Remote {
+                    urls: true,
+                    command: Some(RemoteCommand::Add(Add { fetch: true, name: "origin".into() })),
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn invalid_global_options_are_errors() {
+        assert_error::<Repo>(
+            &["status", "--verbose=1"],
+            "Switch '--verbose' does not take a value.\n",
+        );
+        assert_error::<Repo>(&["status", "--dir"], "No value provided for option '--dir'.\n");
+        assert_error::<Repo>(&["status", "--urls"], "Unrecognized argument: --urls\n");
+    }
+
+    #[test]
+    fn error_indices_are_those_of_the_parent() {
+        let reason =
+            Repo::try_parse(&["cmd"], &["-v", "remote", "add", "origin", "--dir"]).unwrap_err();
+        assert_eq!(reason, ParseError::MissingValue { index: 4, option: "--dir".into() });
+    }
+
+    #[test]
+    fn redacted_before_the_subcommand() {
+        let redacted = Repo::redact_arg_values(
+            &["cmd"],
+            &["remote", "--dir", "secret", "add", "origin", "-v", "--urls"],
+        )
+        .unwrap();
+        assert_eq!(redacted, ["cmd", "-v", "--dir", "remote", "--urls", "add", "name"]);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_lists_global_options_of_parents() {
+        let early_exit = Repo::from_args(&["repo"], &["remote", "add", "--help"]).unwrap_err();
+        assert_eq!(
+            early_exit.output,
+            r###"Usage: repo remote add [-f] [--] <name>
+
+Add a remote.
+
+Positional Arguments:
+  name              the name of the remote
+
+Options:
+  -f, --fetch       fetch after adding
+  --help, help      display usage information
+
+Global options:
+  --urls            show urls
+  -v, --verbose     be loud
+  --dir             the directory of the repository
+"###,
+        );
+
+        assert_help_string::<Repo>(
+            r###"Usage: test_arg_0 [-v] [--dir <dir>] <command> [<args>]
+
+Manage a repository.
+
+Options:
+  -v, --verbose     be loud
+  --dir             the directory of the repository
+  --help, help      display usage information
+
+Commands:
+  remote            Manage remotes.
+  status            Show the status.
+"###,
+        );
+    }
+}
+
+mod default_subcommand {
+    use {super::*, argh::ParseError};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Manage a repository.
+    struct Repo {
+        #[argh(switch, short = 'v')]
+        /// be loud
+        verbose: bool,
+        #[argh(subcommand)]
+        command: RepoCommand,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum RepoCommand {
+        #[argh(default)]
+        Status(Status),
+        Log(Log),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Show the status.
+    #[argh(subcommand, name = "status")]
+    struct Status {
+        #[argh(switch, short = 's')]
+        /// show the short format
+        short: bool,
+        #[argh(positional)]
+        /// the paths to show
+        paths: Vec<String>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Show the log.
+    #[argh(subcommand, name = "log")]
+    struct Log {
+        #[argh(option, short = 'n')]
+        /// how many commits to show
+        count: Option<u32>,
+    }
+
+    #[test]
+    fn parsed_when_no_subcommand_is_given() {
+        assert_output(
+            &[],
+            Repo {
+                verbose: false,
+                command: RepoCommand::Status(Status { short: false, paths: vec![] }),
+            },
+        );
+        assert_output(
+            &["-v"],
+            Repo {
+                verbose: true,
+                command: RepoCommand::Status(Status { short: false, paths: vec![] }),
+            },
+        );
+    }
+
+    #[test]
+    fn unknown_arguments_are_given_to_the_default() {
+        assert_output(
+            &["-v", "-s", "src"],
+            Repo {
+                verbose: true,
+                command: RepoCommand::Status(Status { short: true, paths: vec!["src".into()] }),
+            },
+        );
+        assert_output(
+            &["src", "-s"],
+            Repo {
+                verbose: false,
+                command: RepoCommand::Status(Status { short: true, paths: vec!["src".into()] }),
+            },
+        );
+    }
+
+    #[test]
+    fn named_subcommands_are_parsed() {
+        assert_output(
+            &["status", "-s"],
+            Repo {
+                verbose: false,
+                command: RepoCommand::Status(Status { short: true, paths: vec![] }),
+            },
+        );
+        assert_output(
+            &["-v", "log", "-n", "2"],
+            Repo { verbose: true, command: RepoCommand::Log(Log { count: Some(2) }) },
+        );
+    }
+
+    #[test]
+    fn arguments_after_double_dash_are_given_to_the_default() {
+        assert_output(
+            &["-v", "--", "-n", "src"],
+            Repo {
+                verbose: true,
+                command: RepoCommand::Status(Status {
+                    short: false,
+                    paths: vec!["-n".into(), "src".into()],
+                }),
+            },
+        );
+        assert_output(
+            &["--"],
+            Repo {
+                verbose: false,
+                command: RepoCommand::Status(Status { short: false, paths: vec![] }),
+            },
+        );
+    }
+
+    #[cfg(feature = "suggestions")]
+    #[test]
+    fn mistyped_subcommand_is_not_given_to_the_default() {
+        assert_error::<Repo>(&["lgo"], "Unrecognized argument: \"lgo\". Did you mean \"log\"?\n");
+        assert_output(
+            &["--", "lgo"],
+            Repo {
+                verbose: false,
+                command: RepoCommand::Status(Status { short: false, paths: vec!["lgo".into()] }),
+            },
+        );
+    }
+
+    #[test]
+    fn default_of_the_field_overrides_that_of_the_subcommands() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Manage a repository.
+        struct Cmd {
+            #[argh(subcommand, default = "log")]
+            command: RepoCommand,
+        }
+
+        assert_output(&["-n", "3"], Cmd { command: RepoCommand::Log(Log { count: Some(3) }) });
+    }
+
+    #[test]
+    fn unknown_default_of_a_generic_command() {
+        // The default is only checked at compile time for commands which are
+        // not generic.
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Manage a repository.
+        struct Cmd<T: FromArgs + argh::SubCommands> {
+            #[argh(subcommand, default = "lgo")]
+            command: T,
+        }
+
+        assert_error::<Cmd<RepoCommand>>(
+            &["-n", "3"],
+            "The default subcommand 'lgo' matches no subcommand.\n",
+        );
+    }
+
+    #[test]
+    fn unknown_options_are_errors() {
+        assert_error::<Repo>(&["-x"], "Unrecognized argument: -x\n");
+        assert_error::<Repo>(&["-v", "-n", "2"], "Unrecognized argument: -n\n");
+        assert_error::<Repo>(&["--short", "-v"], "Unrecognized argument: -v\n");
+
+        let reason = Repo::try_parse(&["cmd"], &["-v", "--bogus"]).unwrap_err();
+        assert_eq!(
+            reason,
+            ParseError::UnknownArgument {
+                index: 1,
+                arg: "--bogus".into(),
+                suggestion: None,
+                no_options: false,
+            }
+        );
+    }
+
+    #[test]
+    fn redacted_as_the_default() {
+        let redacted = Repo::redact_arg_values(&["cmd"], &["-v", "secret", "-s"]).unwrap();
+        assert_eq!(redacted, ["cmd", "-v", "status", "-s", "paths"]);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_marks_the_default() {
+        assert_help_string::<Repo>(
+            r###"Usage: test_arg_0 [-v] [<command>] [<args>]
+
+Manage a repository.
+
+Options:
+  -v, --verbose     be loud
+  --help, help      display usage information
+
+Commands:
+  status            Show the status. [default]
+  log               Show the log.
+"###,
+        );
+    }
+}
+
+#[cfg(unix)]
+mod external {
+    use {
+        super::*,
+        argh::external::{self, ExternalCommands, PathSubCommands},
+        std::{fs, os::unix::fs::PermissionsExt, path::PathBuf},
+    };
+
+    /// The directories of the tool of the test named `test`, which
+    /// `create_tools` fills: the first has `tool-deploy` and `tool-lint`, the
+    /// second has another `tool-lint` and `tool-serve`, and the third is
+    /// missing.
+    fn tool_dirs(test: &str) -> Vec<PathBuf> {
+        let root =
+            std::env::temp_dir().join(format!("argh-external-{}-{}", test, std::process::id()));
+        let second = root.join("second");
+        vec![root.join("first"), second.clone(), second.join("missing")]
+    }
+
+    fn create_tools(dirs: &[PathBuf]) {
+        let [first, second, ..] = dirs else { panic!("missing directories") };
+        for (dir, name, mode) in [
+            (first, "tool-lint", 0o755),
+            (first, "tool-deploy", 0o700),
+            (first, "tool-notes", 0o644),
+            (first, "tool-", 0o755),
+            (first, "other-build", 0o755),
+            (second, "tool-lint", 0o755),
+            (second, "tool-serve", 0o755),
+        ] {
+            fs::create_dir_all(dir).unwrap();
+            let path = dir.join(name);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        fs::create_dir(first.join("tool-dir")).unwrap();
+    }
+
+    /// Removes the directories which `create_tools` filled.
+    fn remove_tools(dirs: &[PathBuf]) {
+        let [first, ..] = dirs else { panic!("missing directories") };
+        fs::remove_dir_all(first.parent().unwrap()).unwrap();
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// A tool.
+    struct Cmd<C: ExternalCommands> {
+        #[argh(subcommand)]
+        command: Command<C>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum Command<C: ExternalCommands> {
+        Build(Build),
+        #[argh(dynamic)]
+        External(PathSubCommands<C>),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Build things.
+    #[argh(subcommand, name = "build")]
+    struct Build {}
+
+    #[test]
+    fn executables_are_found_in_order_of_the_dirs() {
+        let dirs = tool_dirs("find-all");
+        create_tools(&dirs);
+        let [first, second, ..] = &dirs[..] else { panic!("missing directories") };
+        let found = external::find_all("tool-", &dirs);
+        assert_eq!(
+            found,
+            [
+                ("deploy".to_owned(), first.join("tool-deploy")),
+                ("lint".to_owned(), first.join("tool-lint")),
+                ("serve".to_owned(), second.join("tool-serve")),
+            ],
+        );
+
+        assert_eq!(external::find("tool-", &dirs, "serve"), Some(second.join("tool-serve")));
+        assert_eq!(external::find("tool-", &dirs, "notes"), None);
+        assert_eq!(external::find("tool-", &dirs, "build"), None);
+        remove_tools(&dirs);
+    }
+
+    #[test]
+    fn parsed_as_the_executable_and_its_arguments() {
+        #[derive(Debug, PartialEq)]
+        struct Tool;
+
+        impl ExternalCommands for Tool {
+            const PREFIX: &'static str = "tool-";
+
+            fn dirs() -> Vec<PathBuf> {
+                tool_dirs("parsed")
+            }
+        }
+
+        let dirs = Tool::dirs();
+        create_tools(&dirs);
+        let [first, ..] = &dirs[..] else { panic!("missing directories") };
+        let parsed = Cmd::<Tool>::from_args(&["tool"], &["lint", "--fix", "src"]).unwrap();
+        let Command::External(external) = parsed.command else { panic!("not external") };
+        assert_eq!(external.name, "lint");
+        assert_eq!(external.path, first.join("tool-lint"));
+        assert_eq!(external.args, ["--fix", "src"]);
+        assert_eq!(external.command().get_args().collect::<Vec<_>>(), ["--fix", "src"]);
+
+        assert_output(&["build"], Cmd::<Tool> { command: Command::Build(Build {}) });
+        assert_error::<Cmd<Tool>>(&["notes"], "Unrecognized argument: notes\n");
+        remove_tools(&dirs);
+    }
+
+    #[test]
+    fn non_utf8_arguments_are_passed_on() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        struct Tool;
+
+        impl ExternalCommands for Tool {
+            const PREFIX: &'static str = "tool-";
+
+            fn dirs() -> Vec<PathBuf> {
+                tool_dirs("non-utf8")
+            }
+        }
+
+        let dirs = Tool::dirs();
+        create_tools(&dirs);
+        let arg = OsStr::from_bytes(b"caf\xe9");
+        let parsed =
+            Cmd::<Tool>::from_args_os(&[OsStr::new("tool")], &[OsStr::new("lint"), arg]).unwrap();
+        let Command::External(external) = parsed.command else { panic!("not external") };
+        assert_eq!(external.args, [arg]);
+        assert_eq!(external.command().get_args().collect::<Vec<_>>(), [arg]);
+        remove_tools(&dirs);
+    }
+
+    #[test]
+    fn arguments_are_redacted() {
+        struct Tool;
+
+        impl ExternalCommands for Tool {
+            const PREFIX: &'static str = "tool-";
+
+            fn dirs() -> Vec<PathBuf> {
+                tool_dirs("redact")
+            }
+        }
+
+        let dirs = Tool::dirs();
+        create_tools(&dirs);
+        let redacted =
+            Cmd::<Tool>::redact_arg_values(&["tool"], &["serve", "--token", "secret"]).unwrap();
+        assert_eq!(redacted, ["tool", "serve"]);
+        remove_tools(&dirs);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_lists_executables_as_commands() {
+        struct Tool;
+
+        impl ExternalCommands for Tool {
+            const PREFIX: &'static str = "tool-";
+
+            fn dirs() -> Vec<PathBuf> {
+                tool_dirs("help")
+            }
+        }
+
+        let dirs = Tool::dirs();
+        create_tools(&dirs);
+        assert_help_string::<Cmd<Tool>>(
+            r###"Usage: test_arg_0 <command> [<args>]
+
+A tool.
+
+Options:
+  --help, help      display usage information
+
+Commands:
+  build             Build things.
+  deploy
+  lint
+  serve
+"###,
+        );
+        remove_tools(&dirs);
+    }
+}
+
+mod unknown {
+    use {super::*, std::ffi::OsString};
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Run ninja.
+    struct Ninja {
+        #[argh(switch, short = 'v')]
+        /// be loud
+        verbose: bool,
+        #[argh(option, short = 'C')]
+        /// the directory to run in
+        dir: Option<String>,
+        #[argh(unknown)]
+        /// options passed on to ninja
+        ninja_args: Vec<String>,
+        #[argh(positional)]
+        /// the targets to build
+        targets: Vec<String>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Run ssh.
+    struct Ssh {
+        #[argh(switch, short = 'q')]
+        /// be quiet
+        quiet: bool,
+        #[argh(unknown, with_values)]
+        /// options passed on to ssh
+        ssh_args: Vec<OsString>,
+        #[argh(positional)]
+        /// the host
+        host: String,
+    }
+
+    #[test]
+    fn nothing_is_collected_without_unknown_options() {
+        assert_output(
+            &["all"],
+            Ninja { verbose: false, dir: None, ninja_args: vec![], targets: vec!["all".into()] },
+        );
+    }
+
+    #[test]
+    fn unknown_options_are_collected_in_order() {
+        assert_output(
+            &["-j8", "-v", "all", "--keep-going=3", "-C", "out", "-d", "stats", "--", "-x"],
+            Ninja {
+                verbose: true,
+                dir: Some("out".into()),
+                ninja_args: vec!["-j8".into(), "--keep-going=3".into(), "-d".into()],
+                targets: vec!["all".into(), "stats".into(), "-x".into()],
+            },
+        );
+    }
+
+    #[test]
+    fn groups_are_collected_if_their_first_option_is_unknown() {
+        assert_output(
+            &["-jv"],
+            Ninja { verbose: false, dir: None, ninja_args: vec!["-jv".into()], targets: vec![] },
+        );
+        assert_error::<Ninja>(&["-vj8"], "Unrecognized argument: -j\n");
+    }
+
+    #[test]
+    fn with_values_collects_the_argument_after_an_unknown_option() {
+        assert_output(
+            &["-p", "22", "-q", "host"],
+            Ssh { quiet: true, ssh_args: vec!["-p".into(), "22".into()], host: "host".into() },
+        );
+        assert_output(
+            &["host", "-o", "Compression=yes"],
+            Ssh {
+                quiet: false,
+                ssh_args: vec!["-o".into(), "Compression=yes".into()],
+                host: "host".into(),
+            },
+        );
+    }
+
+    #[test]
+    fn with_values_leaves_attached_values_and_options_alone() {
+        assert_output(
+            &["-p22", "host"],
+            Ssh { quiet: false, ssh_args: vec!["-p22".into()], host: "host".into() },
+        );
+        assert_output(
+            &["--port=22", "host"],
+            Ssh { quiet: false, ssh_args: vec!["--port=22".into()], host: "host".into() },
+        );
+        assert_output(
+            &["-A", "-q", "host"],
+            Ssh { quiet: true, ssh_args: vec!["-A".into()], host: "host".into() },
+        );
+    }
+
+    #[test]
+    fn unknown_options_are_redacted() {
+        let redacted =
+            Ninja::redact_arg_values(&["cmd"], &["-j8", "all", "--secret=hunter2", "-v"]).unwrap();
+        assert_eq!(redacted, ["cmd", "-v", "ninja_args", "ninja_args", "targets"]);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_leaves_out_unknown_options() {
+        assert_help_string::<Ninja>(
+            r###"Usage: test_arg_0 [-v] [-C <dir>] [--] [<targets...>]
+
+Run ninja.
+
+Positional Arguments:
+  targets           the targets to build
+
+Options:
+  -v, --verbose     be loud
+  -C, --dir         the directory to run in
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+mod trailing {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Run a binary.
+    struct Run {
+        #[argh(switch, short = 'r')]
+        /// build in release mode
+        release: bool,
+        #[argh(positional)]
+        /// the binaries to run
+        bins: Vec<String>,
+        #[argh(trailing)]
+        /// the arguments of the binaries
+        passthrough: Vec<String>,
+    }
+
+    #[test]
+    fn arguments_before_double_dash_are_parsed_as_usual() {
+        assert_output(
+            &["a", "-r", "b"],
+            Run { release: true, bins: vec!["a".into(), "b".into()], passthrough: vec![] },
+        );
+        assert_output(
+            &["a", "--"],
+            Run { release: false, bins: vec!["a".into()], passthrough: vec![] },
+        );
+    }
+
+    #[test]
+    fn arguments_after_double_dash_are_collected_as_given() {
+        assert_output(
+            &["a", "--", "b", "-r", "--", "--help"],
+            Run {
+                release: false,
+                bins: vec!["a".into()],
+                passthrough: vec!["b".into(), "-r".into(), "--".into(), "--help".into()],
+            },
+        );
+        assert_output(
+            &["--", "-r"],
+            Run { release: false, bins: vec![], passthrough: vec!["-r".into()] },
+        );
+    }
+
+    #[test]
+    fn trailing_values_are_parsed() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Add numbers.
+        struct Add {
+            #[argh(trailing)]
+            /// the numbers
+            numbers: Vec<u32>,
+        }
+
+        assert_output(&["--", "1", "2"], Add { numbers: vec![1, 2] });
+        assert_error::<Add>(
+            &["--", "1", "x"],
+            "Error parsing positional argument 'numbers' with value 'x': invalid digit found in string\n",
+        );
+    }
+
+    #[test]
+    fn trailing_arguments_are_redacted() {
+        let redacted =
+            Run::redact_arg_values(&["cmd"], &["a", "-r", "--", "--secret", "hunter2"]).unwrap();
+        assert_eq!(redacted, ["cmd", "-r", "bins", "passthrough", "passthrough"]);
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn help_shows_trailing_arguments_after_double_dash() {
+        assert_help_string::<Run>(
+            r###"Usage: test_arg_0 [-r] [<bins...>] [-- <passthrough>...]
+
+Run a binary.
+
+Positional Arguments:
+  bins              the binaries to run
+  passthrough       the arguments of the binaries
+
+Options:
+  -r, --release     build in release mode
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+mod negatable {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Negatable {
+        #[argh(switch, negatable, default = "true")]
+        /// colorize output
+        color: bool,
+        #[argh(switch, short = 'p', negatable)]
+        /// use a pager
+        pager: Option<bool>,
+    }
+
+    #[test]
+    fn last_of_a_switch_and_its_negation_wins() {
+        assert_output(&[], Negatable { color: true, pager: None });
+        assert_output(&["--no-color"], Negatable { color: false, pager: None });
+        assert_output(&["--no-color", "--color"], Negatable { color: true, pager: None });
+        assert_output(&["--pager"], Negatable { color: true, pager: Some(true) });
+        assert_output(&["-p"], Negatable { color: true, pager: Some(true) });
+        assert_output(&["--no-pager"], Negatable { color: true, pager: Some(false) });
+        assert_output(&["-p", "--no-pager"], Negatable { color: true, pager: Some(false) });
+    }
+
+    #[test]
+    fn negation_takes_no_value() {
+        assert_error::<Negatable>(
+            &["--no-color=1"],
+            "Switch '--no-color' does not take a value.\n",
+        );
+    }
+
+    #[test]
+    fn counting_switch_is_reset() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Cmd {
+            #[argh(switch, short = 'v', negatable)]
+            /// verbosity
+            verbose: u8,
+        }
+
+        assert_output(&["-vv", "--no-verbose", "-v"], Cmd { verbose: 1 });
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_shows_negated_forms() {
+        assert_help_string::<Negatable>(
+            r###"Usage: test_arg_0 [--[no-]color] [-p]
+
+Woot
+
+Options:
+  --[no-]color      colorize output
+  -p, --[no-]pager  use a pager
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[test]
+    fn redact_keeps_negated_forms() {
+        let actual =
+            Negatable::redact_arg_values(&["program-name"], &["--no-color", "-p"]).unwrap();
+        assert_eq!(actual, &["program-name", "--no-color", "-p"]);
+    }
+}
+
+mod constraints {
+    use super::*;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    #[argh(group(name = "output", required, multiple = false))]
+    struct Output {
+        #[argh(switch, group = "output", conflicts_with = "pretty")]
+        /// print json
+        json: bool,
+        #[argh(option, group = "output")]
+        /// format string
+        format: Option<String>,
+        #[argh(switch, negatable)]
+        /// pretty print
+        pretty: bool,
+        #[argh(option, requires = "cert")]
+        /// private key
+        key: Option<String>,
+        #[argh(option)]
+        /// certificate
+        cert: Option<String>,
+    }
+
+    #[test]
+    fn satisfied_constraints_are_parsed() {
+        assert_output(
+            &["--json", "--key", "k", "--cert", "c"],
+            Output {
+                json: true,
+                format: None,
+                pretty: false,
+                key: Some("k".to_owned()),
+                cert: Some("c".to_owned()),
+            },
+        );
+        assert_output(
+            &["--format", "{}", "--pretty"],
+            Output {
+                json: false,
+                format: Some("{}".to_owned()),
+                pretty: true,
+                key: None,
+                cert: None,
+            },
+        );
+        assert_output(
+            &["--json", "--pretty", "--no-pretty"],
+            Output { json: true, format: None, pretty: false, key: None, cert: None },
+        );
+    }
+
+    #[test]
+    fn conflicting_arguments_are_errors() {
+        assert_error::<Output>(
+            &["--json", "--pretty"],
+            "Conflicting arguments provided:\n    --json cannot be used with --pretty\n",
+        );
+    }
+
+    #[test]
+    fn missing_required_arguments_are_errors() {
+        assert_error::<Output>(
+            &["--json", "--key", "k"],
+            "Required arguments not provided:\n    --cert (required by --key)\n",
+        );
+    }
+
+    #[test]
+    fn groups_limit_how_many_arguments_are_given() {
+        assert_error::<Output>(
+            &[],
+            "One of the following arguments must be provided:\n    --json\n    --format\n",
+        );
+        assert_error::<Output>(
+            &["--json", "--format", "{}"],
+            "Only one of the following arguments may be provided:\n    --json\n    --format\n",
+        );
+    }
+
+    #[test]
+    fn constraint_errors_are_aggregated() {
+        assert_error::<Output>(
+            &["--json", "--format", "{}", "--pretty", "--key", "k"],
+            r###"Conflicting arguments provided:
+    --json cannot be used with --pretty
+Required arguments not provided:
+    --cert (required by --key)
+Only one of the following arguments may be provided:
+    --json
+    --format
+"###,
+        );
+    }
+
+    #[test]
+    fn optional_group_may_be_left_out() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        #[argh(group(name = "verbosity", multiple = false))]
+        struct Cmd {
+            #[argh(switch, short = 'q', group = "verbosity")]
+            /// print less
+            quiet: bool,
+            #[argh(switch, short = 'v', group = "verbosity")]
+            /// print more
+            verbose: bool,
+        }
+
+        assert_output(&[], Cmd { quiet: false, verbose: false });
+        assert_output(&["-v"], Cmd { quiet: false, verbose: true });
+        assert_error::<Cmd>(
+            &["-v", "-q"],
+            "Only one of the following arguments may be provided:\n    --quiet\n    --verbose\n",
+        );
+        #[cfg(feature = "help")]
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 [-q | -v]
+
+Woot
+
+Options:
+  -q, --quiet       print less
+  -v, --verbose     print more
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_shows_groups_in_usage() {
+        assert_help_string::<Output>(
+            r###"Usage: test_arg_0 (--json | --format <format>) [--[no-]pretty] [--key <key>] [--cert <cert>]
+
+Woot
+
+Options:
+  --json            print json
+  --format          format string
+  --[no-]pretty     pretty print
+  --key             private key
+  --cert            certificate
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[test]
+    fn redact_keeps_grouped_arguments() {
+        let actual = Output::redact_arg_values(&["program-name"], &["--json", "--pretty"]).unwrap();
+        assert_eq!(actual, &["program-name", "--json", "--pretty"]);
+    }
+}
+
+mod env {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, env = "APP_TOKEN")]
+        /// the api token
+        token: String,
+        #[argh(option, env = "APP_RETRIES", default = "3")]
+        /// how many times to retry
+        retries: u8,
+        #[argh(option, env = "APP_REGION")]
+        /// the region to deploy to
+        region: Option<String>,
+    }
+
+    fn parse(args: &[&str], env: &dyn argh::Environment) -> Result<Cmd, argh::EarlyExit> {
+        let args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+        Cmd::from_args_with_env(&[OsStr::new("cmd")], &args, env)
+    }
+
+    #[test]
+    fn command_line_takes_precedence_over_env() {
+        let env = [("APP_TOKEN", "from-env"), ("APP_RETRIES", "5")];
+        assert_eq!(
+            parse(&[], &env).unwrap(),
+            Cmd { token: "from-env".to_owned(), retries: 5, region: None }
+        );
+        assert_eq!(
+            parse(&["--token", "from-cli", "--retries=1"], &env).unwrap(),
+            Cmd { token: "from-cli".to_owned(), retries: 1, region: None }
+        );
+        assert_eq!(
+            parse(&["--region", "eu"], &[("APP_TOKEN", "from-env")]).unwrap(),
+            Cmd { token: "from-env".to_owned(), retries: 3, region: Some("eu".to_owned()) }
+        );
+    }
+
+    #[test]
+    fn required_option_missing_from_env() {
+        let e = parse(&[], &[("APP_RETRIES", "5")]).unwrap_err();
+        assert_eq!(e.output, "Required options not provided:\n    --token\n");
+    }
+
+    #[test]
+    fn invalid_env_value_is_an_error() {
+        let e = parse(&["--token", "t"], &[("APP_RETRIES", "many")]).unwrap_err();
+        assert_eq!(
+            e.output,
+            "Error parsing environment variable 'APP_RETRIES' with value 'many': \
+             invalid digit found in string\n"
+        );
+    }
+
+    #[test]
+    fn subcommand_options_fall_back_to_env() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Top-level command.
+        struct TopLevel {
+            #[argh(subcommand)]
+            nested: Nested,
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argh(subcommand)]
+        enum Nested {
+            Deploy(DeployCmd),
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Deploy.
+        #[argh(subcommand, name = "deploy")]
+        struct DeployCmd {
+            #[argh(option, env = "APP_REGION")]
+            /// region
+            region: String,
+        }
+
+        let top = TopLevel::from_args_with_env(
+            &[OsStr::new("cmd")],
+            &[OsStr::new("deploy")],
+            &[("APP_REGION", "us")],
+        )
+        .unwrap();
+        assert_eq!(top, TopLevel { nested: Nested::Deploy(DeployCmd { region: "us".to_owned() }) });
+    }
+
+    #[test]
+    fn env_prefix_names_variables_of_options() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Top-level command.
+        #[argh(env_prefix = "MYTOOL_")]
+        struct TopLevel {
+            #[argh(option)]
+            /// the api token
+            api_token: Option<String>,
+            #[argh(option, env = "LOG_LEVEL")]
+            /// the log level
+            log_level: Option<String>,
+            #[argh(subcommand)]
+            nested: Nested,
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argh(subcommand)]
+        enum Nested {
+            Deploy(DeployCmd),
+            Build(BuildCmd),
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Deploy.
+        #[argh(subcommand, name = "deploy")]
+        struct DeployCmd {
+            #[argh(option)]
+            /// region
+            region: String,
+        }
+
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Build.
+        #[argh(subcommand, name = "build", env_prefix = "BUILD_")]
+        struct BuildCmd {
+            #[argh(option)]
+            /// build profile
+            profile: String,
+        }
+
+        let env = [
+            ("MYTOOL_API_TOKEN", "token"),
+            ("MYTOOL_LOG_LEVEL", "ignored"),
+            ("LOG_LEVEL", "debug"),
+            ("MYTOOL_DEPLOY_REGION", "us"),
+            ("BUILD_PROFILE", "release"),
+        ];
+        let parse = |args: &[&str]| {
+            let args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+            TopLevel::from_args_with_env(&[OsStr::new("cmd")], &args, &env)
+        };
+
+        assert_eq!(
+            parse(&["deploy"]).unwrap(),
+            TopLevel {
+                api_token: Some("token".to_owned()),
+                log_level: Some("debug".to_owned()),
+                nested: Nested::Deploy(DeployCmd { region: "us".to_owned() }),
+            }
+        );
+        assert_eq!(
+            parse(&["--api-token", "cli", "build"]).unwrap(),
+            TopLevel {
+                api_token: Some("cli".to_owned()),
+                log_level: Some("debug".to_owned()),
+                nested: Nested::Build(BuildCmd { profile: "release".to_owned() }),
+            }
+        );
+
+        // Subcommands parsed on their own don't inherit a prefix.
+        assert_error::<DeployCmd>(&[], "Required options not provided:\n    --region\n");
+    }
+
+    #[test]
+    fn delimited_env_values_are_split() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        #[argh(env_prefix = "PROBE_")]
+        struct Delimited {
+            #[argh(option, delimiter = ',')]
+            /// tags
+            tag: Vec<String>,
+            #[argh(option, env = "APP_PORTS", delimiter = ':')]
+            /// ports
+            port: Vec<u16>,
+        }
+
+        let parse = |env: &dyn argh::Environment| {
+            Delimited::from_args_with_env(&[OsStr::new("cmd")], &[], env)
+        };
+        assert_eq!(
+            parse(&[("PROBE_TAG", r"a,b\,c"), ("APP_PORTS", "80:443")]).unwrap(),
+            Delimited { tag: vec!["a".into(), "b,c".into()], port: vec![80, 443] }
+        );
+        assert_eq!(
+            parse(&[("APP_PORTS", "80:x")]).unwrap_err().output,
+            "Error parsing environment variable 'APP_PORTS' with value 'x': \
+             invalid digit found in string\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn help_names_the_variables() {
+        assert_help_string::<Cmd>(
+            r###"Usage: test_arg_0 --token <token> [--retries <retries>] [--region <region>]
+
+Woot
+
+Options:
+  --token           the api token [env: APP_TOKEN]
+  --retries         how many times to retry [env: APP_RETRIES]
+  --region          the region to deploy to [env: APP_REGION]
+  --help, help      display usage information
+"###,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "help")]
+    fn braces_in_variable_names_in_help() {
+        #[derive(FromArgs)]
+        #[allow(dead_code)]
+        /// Woot
+        struct Braces {
+            #[argh(option, env = "APP_{TOKEN}")]
+            /// the api token
+            token: Option<String>,
+        }
+
+        assert_help_string::<Braces>(
+            r###"Usage: test_arg_0 [--token <token>]
+
+Woot
+
+Options:
+  --token           the api token [env: APP_{TOKEN}]
+  --help, help      display usage information
+"###,
+        );
+    }
+}
+
+#[cfg(feature = "config")]
+mod config {
+    use super::*;
+    use argh::Config;
+    use std::ffi::OsStr;
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Top-level command.
+    struct TopLevel {
+        #[argh(option, env = "APP_TOKEN")]
+        /// the api token
+        api_token: String,
+        #[argh(option, default = "1")]
+        /// how many times to retry
+        retries: u8,
+        #[argh(subcommand)]
+        nested: Option<Nested>,
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    #[argh(subcommand)]
+    enum Nested {
+        Deploy(DeployCmd),
+    }
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Deploy.
+    #[argh(subcommand, name = "deploy")]
+    struct DeployCmd {
+        #[argh(option)]
+        /// region
+        region: String,
+        #[argh(option)]
+        /// tags
+        tag: Vec<String>,
+    }
+
+    const CONFIG: &str = r#"
+api-token = "from-config"
+retries = 3
+
+[deploy]
+region = "us"
+tag = ["a", "b"]
+"#;
+
+    fn parse(
+        args: &[&str],
+        env: &dyn argh::Environment,
+        config: &str,
+    ) -> Result<TopLevel, argh::EarlyExit> {
+        let config = Config::parse(".mytool.toml", config)?;
+        let args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+        config.from_args(&[OsStr::new("cmd")], &args, env)
+    }
+
+    #[test]
+    fn command_line_and_env_take_precedence_over_config() {
+        assert_eq!(
+            parse(&[], &[], CONFIG).unwrap(),
+            TopLevel { api_token: "from-config".to_owned(), retries: 3, nested: None }
+        );
+        assert_eq!(
+            parse(&[], &[("APP_TOKEN", "from-env")], CONFIG).unwrap(),
+            TopLevel { api_token: "from-env".to_owned(), retries: 3, nested: None }
+        );
+        assert_eq!(
+            parse(&["--api-token", "from-cli"], &[("APP_TOKEN", "from-env")], CONFIG).unwrap(),
+            TopLevel { api_token: "from-cli".to_owned(), retries: 3, nested: None }
+        );
+        assert_eq!(
+            parse(&[], &[], "api-token = \"t\"").unwrap(),
+            TopLevel { api_token: "t".to_owned(), retries: 1, nested: None }
+        );
+    }
+
+    #[test]
+    fn subcommand_options_are_read_from_its_table() {
+        assert_eq!(
+            parse(&["deploy", "--tag", "c"], &[], CONFIG).unwrap(),
+            TopLevel {
+                api_token: "from-config".to_owned(),
+                retries: 3,
+                nested: Some(Nested::Deploy(DeployCmd {
+                    region: "us".to_owned(),
+                    tag: vec!["c".to_owned()],
+                })),
+            }
+        );
+        assert_eq!(
+            parse(&["deploy"], &[], CONFIG).unwrap().nested,
+            Some(Nested::Deploy(DeployCmd {
+                region: "us".to_owned(),
+                tag: vec!["a".to_owned(), "b".to_owned()],
+            }))
+        );
+    }
+
+    #[test]
+    fn delimited_config_values_are_split() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Woot
+        struct Delimited {
+            #[argh(option, delimiter = ',')]
+            /// tags
+            tag: Vec<String>,
+            #[argh(option, delimiter = ':')]
+            /// ports
+            port: Vec<u16>,
+        }
 
-#[test]
-fn default_function() {
-    const MSG: &str = "hey I just met you";
-    fn call_me_maybe() -> String {
-        MSG.to_owned()
+        let parse = |config| {
+            let config = Config::parse(".mytool.toml", config).unwrap();
+            config.from_args::<Delimited>(&[OsStr::new("cmd")], &[], &[])
+        };
+        assert_eq!(
+            parse("tag = [\"a,b\", \"c\"]\nport = \"80:443\"").unwrap(),
+            Delimited { tag: vec!["a".into(), "b".into(), "c".into()], port: vec![80, 443] }
+        );
+        assert_eq!(
+            parse("port = \"80:x\"").unwrap_err().output,
+            "Error parsing config file '.mytool.toml' key 'port' with value 'x': \
+             invalid digit found in string\n"
+        );
     }
 
-    #[derive(FromArgs)]
-    /// Short description
-    struct Cmd {
-        #[argh(option, default = "call_me_maybe()")]
-        /// fooey
-        msg: String,
-    }
+    #[test]
+    fn invalid_config_values_are_errors() {
+        let e = parse(&[], &[], "api-token = \"t\"\nretries = 300").unwrap_err();
+        assert_eq!(
+            e.output,
+            "Error parsing config file '.mytool.toml' key 'retries' with value '300': \
+             number too large to fit in target type\n"
+        );
 
-    let cmd = Cmd::from_args(&["cmdname"], &[]).unwrap();
-    assert_eq!(cmd.msg, MSG);
-}
+        let e = parse(&["deploy"], &[], "api-token = \"t\"\n[deploy]\nregion = [[1]]").unwrap_err();
+        assert_eq!(
+            e.output,
+            "Error parsing config file '.mytool.toml' key 'deploy.region': \
+             expected a single value or an array of values\n"
+        );
 
-#[test]
-fn missing_option_value() {
-    #[derive(FromArgs, Debug)]
-    /// Short description
-    struct Cmd {
-        #[argh(option)]
-        /// fooey
-        _msg: String,
+        let e = Config::parse(".mytool.toml", "retries = ").unwrap_err();
+        assert!(e.starts_with("Error parsing config file '.mytool.toml': "), "{}", e);
     }
 
-    let e = Cmd::from_args(&["cmdname"], &["--msg"])
-        .expect_err("Parsing missing option value should fail");
-    assert_eq!(e.output, "No value provided for option \'--msg\'.\n");
-    assert!(e.status.is_err());
-}
+    #[test]
+    fn missing_file_is_read_as_empty() {
+        let config = Config::read("this/file/does/not/exist.toml").unwrap();
+        let e = config.from_args::<TopLevel>(&[OsStr::new("cmd")], &[], &[]).unwrap_err();
+        assert_eq!(e.output, "Required options not provided:\n    --api-token\n");
+    }
 
-#[cfg(feature = "help")]
-fn assert_help_string<T: FromArgs>(help_str: &str) {
-    match T::from_args(&["test_arg_0"], &["--help"]) {
-        Ok(_) => panic!("help was parsed as args"),
-        Err(e) => {
-            assert_eq!(help_str, e.output);
-            e.status.expect("help returned an error");
+    #[test]
+    fn global_options_are_parsed_with_a_config() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Top-level command.
+        struct Verbose {
+            #[argh(switch, short = 'v', global)]
+            /// be loud
+            verbose: bool,
+            #[argh(subcommand)]
+            nested: Nested,
         }
+
+        let config = Config::parse(".mytool.toml", CONFIG).unwrap();
+        let args = [OsStr::new("deploy"), OsStr::new("-v")];
+        assert_eq!(
+            config.from_args::<Verbose>(&[OsStr::new("cmd")], &args, &[]).unwrap(),
+            Verbose {
+                verbose: true,
+                nested: Nested::Deploy(DeployCmd {
+                    region: "us".to_owned(),
+                    tag: vec!["a".to_owned(), "b".to_owned()],
+                }),
+            }
+        );
     }
 }
 
-fn assert_output<T: FromArgs + Debug + PartialEq>(args: &[&str], expected: T) {
-    let t = T::from_args(&["cmd"], args).expect("failed to parse");
-    assert_eq!(t, expected);
-}
+mod response_files {
+    use super::*;
+    use std::path::PathBuf;
 
-fn assert_error<T: FromArgs + Debug>(args: &[&str], err_msg: &str) {
-    let e = T::from_args(&["cmd"], args).expect_err("unexpectedly succeeded parsing");
-    assert_eq!(err_msg, e.output);
-    e.status.expect_err("error had a positive status");
-}
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Expands response files.
+    #[argh(response_files)]
+    struct Expanding {
+        #[argh(option)]
+        /// a name
+        name: Option<String>,
+        #[argh(positional)]
+        /// inputs
+        inputs: Vec<String>,
+    }
 
-mod options {
-    use super::*;
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Does not expand response files.
+    struct Literal {
+        #[argh(positional)]
+        /// inputs
+        inputs: Vec<String>,
+    }
 
-    #[derive(argh::FromArgs, Debug, PartialEq)]
-    /// Woot
-    struct Parsed {
-        #[argh(option, short = 'n')]
-        /// fooey
-        n: usize,
+    /// Writes `contents` to a file unique to `name` and returns its path. Tests
+    /// remove the file when they are done with it.
+    fn response_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "argh-response-file-{}-{}.txt",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn at(path: &std::path::Path) -> String {
+        format!("@{}", path.display())
     }
 
     #[test]
-    fn parsed() {
-        assert_output(&["-n", "5"], Parsed { n: 5 });
-        assert_error::<Parsed>(
-            &["-n", "x"],
-            r###"Error parsing option '-n' with value 'x': invalid digit found in string
-"###,
+    fn expands_quoted_arguments() {
+        let path = response_file("expands", "# the name\n--name 'a b'\n\"c \\\"d\\\"\" e\\ f\n");
+        assert_eq!(
+            Expanding::from_args(&["cmd"], &["first", &at(&path), "last"]).unwrap(),
+            Expanding {
+                name: Some("a b".to_owned()),
+                inputs: vec![
+                    "first".to_owned(),
+                    "c \"d\"".to_owned(),
+                    "e f".to_owned(),
+                    "last".to_owned()
+                ],
+            }
         );
+        std::fs::remove_file(&path).unwrap();
     }
 
-    #[derive(argh::FromArgs, Debug, PartialEq)]
-    /// Woot
-    struct Repeating {
-        #[argh(option, short = 'n')]
-        /// fooey
-        n: Vec<String>,
+    #[test]
+    fn expands_nested_files() {
+        let inner = response_file("nested-inner", "inner");
+        let outer = response_file("nested-outer", &format!("outer '{}'", at(&inner)));
+        assert_eq!(
+            Expanding::from_args(&["cmd"], &[&at(&outer)]).unwrap().inputs,
+            ["outer", "inner"]
+        );
+        std::fs::remove_file(&inner).unwrap();
+        std::fs::remove_file(&outer).unwrap();
     }
 
     #[test]
-    #[cfg(feature = "help")]
-    fn repeating() {
-        assert_help_string::<Repeating>(
-            r###"Usage: test_arg_0 [-n <n...>]
-
-Woot
+    fn nesting_is_limited() {
+        let path = response_file("recursion", "");
+        std::fs::write(&path, at(&path)).unwrap();
+        let e = Expanding::from_args(&["cmd"], &[&at(&path)]).unwrap_err();
+        assert_eq!(e.output, format!("Response file '{}' is nested too deeply.\n", path.display()));
+        std::fs::remove_file(&path).unwrap();
+    }
 
-Options:
-  -n, --n           fooey
-  --help, help      display usage information
-"###,
+    #[test]
+    fn after_dashes_and_lone_at_are_literal() {
+        let path = response_file("dashes", "expanded");
+        assert_eq!(
+            Expanding::from_args(&["cmd"], &["@", &at(&path), "--", &at(&path)]).unwrap().inputs,
+            vec!["@".to_owned(), "expanded".to_owned(), at(&path)]
         );
+        std::fs::remove_file(&path).unwrap();
     }
 
-    #[derive(argh::FromArgs, Debug, PartialEq)]
-    /// Woot
-    struct WithArgName {
-        #[argh(option, arg_name = "name")]
-        /// fooey
-        option_name: Option<String>,
+    #[test]
+    fn dashes_in_a_response_file_end_expansion() {
+        let inner = response_file("dashes-in-file-inner", "inner");
+        let outer = response_file("dashes-in-file-outer", &format!("-- '{}'", at(&inner)));
+        assert_eq!(
+            Expanding::from_args(&["cmd"], &[&at(&outer), &at(&inner)]).unwrap().inputs,
+            [at(&inner), at(&inner)]
+        );
+        std::fs::remove_file(&inner).unwrap();
+        std::fs::remove_file(&outer).unwrap();
     }
 
     #[test]
-    #[cfg(feature = "help")]
-    fn with_arg_name() {
-        assert_help_string::<WithArgName>(
-            r###"Usage: test_arg_0 [--option-name <name>]
+    fn unreadable_or_malformed_files_are_errors() {
+        let path = response_file("errors", "--name 'unterminated");
+        let e = Expanding::from_args(&["cmd"], &[&at(&path)]).unwrap_err();
+        assert_eq!(
+            e.output,
+            format!("Error parsing response file '{}': unterminated quote\n", path.display())
+        );
+        std::fs::remove_file(&path).unwrap();
 
-Woot
+        let e = Expanding::from_args(&["cmd"], &["@this/file/does/not/exist.txt"]).unwrap_err();
+        assert!(
+            e.output.starts_with("Error reading response file 'this/file/does/not/exist.txt': "),
+            "{}",
+            e.output
+        );
+    }
 
-Options:
-  --option-name     fooey
-  --help, help      display usage information
-"###,
+    #[test]
+    fn not_expanded_without_attribute() {
+        assert_output(&["@file"], Literal { inputs: vec!["@file".to_owned()] });
+    }
+
+    #[test]
+    fn redacted_after_expansion() {
+        let path = response_file("redact", "--name secret input");
+        assert_eq!(
+            Expanding::redact_arg_values(&["cmd"], &[&at(&path)]).unwrap(),
+            ["cmd", "--name", "inputs"]
         );
+        std::fs::remove_file(&path).unwrap();
     }
+}
 
-    /// Test choices
-    #[derive(FromArgs, PartialEq, Debug)]
-    struct WithChoices {
-        /// first choice with a default
-        #[argh(option, default = "TwoChoices::Chao")]
-        choice1: TwoChoices,
-        /// second choice.
+#[cfg(unix)]
+mod os_args {
+    use super::*;
+    use std::{
+        ffi::{OsStr, OsString},
+        os::unix::ffi::OsStrExt,
+        path::PathBuf,
+    };
+
+    #[derive(FromArgs, Debug, PartialEq)]
+    /// Woot
+    struct Cmd {
+        #[argh(option, short = 'o')]
+        /// output file
+        output: Option<PathBuf>,
         #[argh(option)]
-        choice2: ThreeChoices,
+        /// a name
+        name: Option<String>,
+        #[argh(switch, short = 'v')]
+        /// verbose
+        verbose: bool,
+        #[argh(positional)]
+        /// inputs
+        inputs: Vec<OsString>,
     }
 
-    #[derive(FromArgValue, PartialEq, Debug)]
-    enum TwoChoices {
-        Hola,
-        Chao,
-    }
+    // Not valid UTF-8.
+    const RAW: &[u8] = b"f\xF0\x80o";
 
-    #[derive(FromArgValue, PartialEq, Debug)]
-    enum ThreeChoices {
-        FirstChoice,
-        #[argh(name = "に")]
-        Two,
-        Three,
+    fn os_args<'a>(args: &[&'a [u8]]) -> Vec<&'a OsStr> {
+        args.iter().map(|arg| OsStr::from_bytes(arg)).collect()
     }
 
-    #[test]
-    fn with_choices() {
-        assert_output(
-            &["--choice2", "three"],
-            WithChoices { choice1: TwoChoices::Chao, choice2: ThreeChoices::Three },
-        );
+    fn parse(args: &[&[u8]]) -> Result<Cmd, argh::EarlyExit> {
+        Cmd::from_args_os(&[OsStr::new("cmd")], &os_args(args))
     }
 
     #[test]
-    fn with_choices_snake_case() {
-        assert_output(
-            &["--choice2", "first_choice"],
-            WithChoices { choice1: TwoChoices::Chao, choice2: ThreeChoices::FirstChoice },
-        )
+    fn raw_values() {
+        let raw = OsStr::from_bytes(RAW);
+        let expected = Cmd {
+            output: Some(PathBuf::from(raw)),
+            name: None,
+            verbose: false,
+            inputs: vec![raw.to_owned()],
+        };
+        assert_eq!(parse(&[b"--output", RAW, RAW]).unwrap(), expected);
+        assert_eq!(parse(&[b"--output=f\xF0\x80o", RAW]).unwrap(), expected);
+        assert_eq!(parse(&[b"-of\xF0\x80o", RAW]).unwrap(), expected);
+        assert_eq!(parse(&[b"-vof\xF0\x80o", RAW]).unwrap(), Cmd { verbose: true, ..expected });
     }
 
     #[test]
-    fn override_default() {
-        assert_output(
-            &["--choice2", "first_choice", "--choice1", "hola"],
-            WithChoices { choice1: TwoChoices::Hola, choice2: ThreeChoices::FirstChoice },
-        )
+    fn utf8_values() {
+        let cmd = parse(&[b"--name", "\u{e9}".as_bytes(), b"--", b"--output"]).unwrap();
+        assert_eq!(
+            cmd,
+            Cmd {
+                output: None,
+                name: Some("\u{e9}".to_owned()),
+                verbose: false,
+                inputs: vec![OsString::from("--output")],
+            }
+        );
     }
 
     #[test]
-    fn with_name_override() {
-        assert_output(
-            &["--choice2", "に", "--choice1", "hola"],
-            WithChoices { choice1: TwoChoices::Hola, choice2: ThreeChoices::Two },
-        )
+    fn non_utf8_value_for_string() {
+        let e = parse(&[b"--name", RAW]).unwrap_err();
+        assert_eq!(
+            e.output,
+            "Error parsing option '--name' with value 'f\u{FFFD}\u{FFFD}o': invalid utf8\n"
+        );
     }
 
     #[test]
-    fn invalid_choice() {
-        assert_error::<WithChoices>(
-            &["--choice2", "something"],
-            r###"Error parsing option '--choice2' with value 'something': expected "first_choice", "に" or "three"
-"###,
-        )
+    fn non_utf8_option_name() {
+        let e = parse(&[b"--f\xF0\x80o"]).unwrap_err();
+        assert!(e.output.contains("--f\u{FFFD}\u{FFFD}o"), "{}", e.output);
+
+        let e = parse(&[b"-v\xF0\x80"]).unwrap_err();
+        assert!(e.output.contains("-v\u{FFFD}\u{FFFD}"), "{}", e.output);
     }
 
     #[test]
-    #[cfg(feature = "help")]
-    fn choice_help() {
-        assert_help_string::<WithChoices>(
-            r###"Usage: test_arg_0 [--choice1 <choice1>] --choice2 <choice2>
+    fn subcommand() {
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Top-level command.
+        struct TopLevel {
+            #[argh(subcommand)]
+            nested: Nested,
+        }
 
-Test choices
+        #[derive(FromArgs, Debug, PartialEq)]
+        #[argh(subcommand)]
+        enum Nested {
+            Copy(CopyCmd),
+        }
 
-Options:
-  --choice1         first choice with a default
-  --choice2         second choice.
-  --help, help      display usage information
-"###,
+        #[derive(FromArgs, Debug, PartialEq)]
+        /// Copy a file.
+        #[argh(subcommand, name = "copy")]
+        struct CopyCmd {
+            #[argh(positional)]
+            /// source
+            source: PathBuf,
+        }
+
+        let top = TopLevel::from_args_os(&[OsStr::new("cmd")], &os_args(&[b"copy", RAW])).unwrap();
+        assert_eq!(
+            top,
+            TopLevel { nested: Nested::Copy(CopyCmd { source: OsStr::from_bytes(RAW).into() }) }
         );
     }
 }
@@ -997,12 +3773,29 @@ mod fuchsia_commandline_tools_rubric {
         _b: bool,
     }
 
-    /// Running switches together is not allowed
+    /// Short switches may be run together.
     #[test]
-    fn switches_cannot_run_together() {
-        TwoSwitches::from_args(&["cmdname"], &["-a", "-b"])
+    fn switches_can_run_together() {
+        let separate = TwoSwitches::from_args(&["cmdname"], &["-a", "-b"])
             .expect("parsing separate should succeed");
-        TwoSwitches::from_args(&["cmdname"], &["-ab"]).expect_err("parsing together should fail");
+        assert!(separate._a && separate._b);
+        let together = TwoSwitches::from_args(&["cmdname"], &["-ab"])
+            .expect("parsing together should succeed");
+        assert!(together._a && together._b);
+        let one =
+            TwoSwitches::from_args(&["cmdname"], &["-b"]).expect("parsing one should succeed");
+        assert!(!one._a && one._b);
+
+        let e = TwoSwitches::from_args(&["cmdname"], &["-abc"])
+            .expect_err("parsing an unknown switch in a group should fail");
+        // Single character short options are not suggested for each other.
+        assert_eq!(e.output, "Unrecognized argument: -c\n");
+        assert!(e.status.is_err());
+
+        let e = TwoSwitches::from_args(&["cmdname"], &["-cab"])
+            .expect_err("parsing a group starting with an unknown switch should fail");
+        assert_eq!(e.output, "Unrecognized argument: -cab\n");
+        assert!(e.status.is_err());
     }
 
     #[derive(FromArgs, Debug)]
@@ -1013,18 +3806,24 @@ mod fuchsia_commandline_tools_rubric {
         _foo: String,
     }
 
-    /// Do not use an equals punctuation or similar to separate the key and value.
+    /// The key and value are usually separate arguments, but an equals sign
+    /// may also be used to attach the value to the key.
     #[test]
-    fn keyed_no_equals() {
-        OneOption::from_args(&["cmdname"], &["--foo", "bar"])
+    fn keyed_equals() {
+        let o = OneOption::from_args(&["cmdname"], &["--foo", "bar"])
             .expect("Parsing option value as separate arg should succeed");
-
-        let e = OneOption::from_args(&["cmdname"], &["--foo=bar"])
-            .expect_err("Parsing option value using `=` should fail");
-        #[cfg(feature = "fuzzy_search")]
-        assert_eq!(e.output, "Unrecognized argument: \"--foo=bar\". Did you mean \"--foo\"?\n");
-        #[cfg(not(feature = "fuzzy_search"))]
-        assert_eq!(e.output, "Unrecognized argument: --foo=bar\n");
+        assert_eq!(o._foo, "bar");
+
+        let o = OneOption::from_args(&["cmdname"], &["--foo=bar"])
+            .expect("Parsing option value using `=` should succeed");
+        assert_eq!(o._foo, "bar");
+
+        let e = OneOption::from_args(&["cmdname"], &["--fob=bar"])
+            .expect_err("Parsing an unknown option using `=` should fail");
+        #[cfg(feature = "suggestions")]
+        assert_eq!(e.output, "Unrecognized argument: \"--fob\". Did you mean \"--foo\"?\n");
+        #[cfg(not(feature = "suggestions"))]
+        assert_eq!(e.output, "Unrecognized argument: --fob\n");
         assert!(e.status.is_err());
     }
 
@@ -1098,6 +3897,10 @@ mod fuchsia_commandline_tools_rubric {
         let cv = CountVerbose::from_args(&["cmdname"], &["-v", "-v", "-v"])
             .expect("Parsing verbose flags should succeed");
         assert_eq!(cv.verbose, 3);
+
+        let cv = CountVerbose::from_args(&["cmdname"], &["-vvv", "-v"])
+            .expect("Parsing grouped verbose flags should succeed");
+        assert_eq!(cv.verbose, 4);
     }
 
     // When a tool has many subcommands, it should also have a help subcommand
@@ -1287,6 +4090,9 @@ Options:
                 name: "plugin",
                 short: &'\0',
                 description: "Example dynamic command",
+                aliases: &[],
+                visible_aliases: &[],
+                deprecated: None,
             }]
         }
 
@@ -1486,6 +4292,29 @@ fn redact_arg_values_optional_arg_short() {
     assert_eq!(actual, &["program-name", "-m"]);
 }
 
+#[test]
+fn redact_arg_values_short_group() {
+    #[derive(FromArgs, Debug)]
+    /// Short description
+    struct Cmd {
+        #[argh(switch, short = 'v')]
+        /// verbosity
+        _verbose: u8,
+        #[argh(switch, short = 'q')]
+        /// quiet
+        _quiet: bool,
+        #[argh(option, short = 'm')]
+        /// a msg param
+        _msg: Option<String>,
+    }
+
+    let actual = Cmd::redact_arg_values(&["program-name"], &["-vqmhello"]).unwrap();
+    assert_eq!(actual, &["program-name", "-v", "-q", "-m"]);
+
+    let actual = Cmd::redact_arg_values(&["program-name"], &["-qm", "hello"]).unwrap();
+    assert_eq!(actual, &["program-name", "-q", "-m"]);
+}
+
 #[test]
 fn redact_arg_values_optional_arg_long() {
     #[derive(FromArgs, Debug)]
@@ -1500,6 +4329,20 @@ fn redact_arg_values_optional_arg_long() {
     assert_eq!(actual, &["program-name", "--my-msg"]);
 }
 
+#[test]
+fn redact_arg_values_optional_arg_equals() {
+    #[derive(FromArgs, Debug)]
+    /// Short description
+    struct Cmd {
+        #[argh(option)]
+        /// a msg param
+        _msg: Option<String>,
+    }
+
+    let actual = Cmd::redact_arg_values(&["program-name"], &["--msg=hello"]).unwrap();
+    assert_eq!(actual, &["program-name", "--msg"]);
+}
+
 #[test]
 fn redact_arg_values_two_option_args() {
     #[derive(FromArgs, Debug)]
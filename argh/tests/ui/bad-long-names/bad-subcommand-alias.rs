@@ -0,0 +1,6 @@
+/// Remove a file.
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "remove", alias = "", visible_alias = "help")]
+struct Remove {}
+
+fn main() {}
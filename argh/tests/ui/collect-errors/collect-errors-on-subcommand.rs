@@ -0,0 +1,14 @@
+/// Command
+#[derive(argh::FromArgs)]
+#[argh(collect_errors)]
+struct Cmd {
+    #[argh(subcommand)]
+    sub: Sub,
+}
+
+/// Sub
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "sub", collect_errors)]
+struct Sub {}
+
+fn main() {}
@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    #[argh(positional, greedy)]
+    /// remainder
+    remainder: Vec<String>,
+
+    #[argh(trailing)]
+    /// passed on
+    passthrough: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+#[argh(group(name = "output", required))]
+struct Cmd {
+    /// json
+    #[argh(switch, group = "outptu")]
+    json: bool,
+    /// verbose
+    #[argh(positional, group = "output")]
+    input: String,
+}
+
+fn main() {}
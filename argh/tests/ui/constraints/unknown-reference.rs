@@ -0,0 +1,12 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// json
+    #[argh(switch, conflicts_with = "fromat")]
+    json: bool,
+    /// format
+    #[argh(option, requires = "format")]
+    format: Option<String>,
+}
+
+fn main() {}
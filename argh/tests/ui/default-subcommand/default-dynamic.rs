@@ -0,0 +1,33 @@
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum SubCommand {
+    One(One),
+    #[argh(dynamic, default)]
+    Dynamic(Dynamic),
+}
+
+/// One
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "one")]
+struct One {}
+
+struct Dynamic;
+
+impl argh::DynamicSubCommand for Dynamic {
+    fn commands() -> &'static [&'static argh::CommandInfo] {
+        &[]
+    }
+
+    fn try_redact_arg_values(
+        _command_name: &[&str],
+        _args: &[&str],
+    ) -> Option<Result<Vec<String>, argh::EarlyExit>> {
+        None
+    }
+
+    fn try_from_args(_command_name: &[&str], _args: &[&str]) -> Option<Result<Self, argh::EarlyExit>> {
+        None
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum SubCommand {
+    #[argh(default)]
+    One(One),
+    #[argh(default)]
+    Two(Two),
+}
+
+/// One
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "one")]
+struct One {}
+
+/// Two
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "two")]
+struct Two {}
+
+fn main() {}
@@ -0,0 +1,19 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    #[argh(subcommand, default = "stauts")]
+    command: SubCommand,
+}
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum SubCommand {
+    Status(Status),
+}
+
+/// Show the status.
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "status")]
+struct Status {}
+
+fn main() {}
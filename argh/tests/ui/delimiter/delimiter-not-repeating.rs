@@ -0,0 +1,12 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// tag
+    #[argh(option, delimiter = ',')]
+    tag: Option<String>,
+    /// verbose
+    #[argh(switch, delimiter = ',')]
+    verbose: bool,
+}
+
+fn main() {}
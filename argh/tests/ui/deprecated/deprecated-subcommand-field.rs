@@ -0,0 +1,19 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    #[argh(subcommand, deprecated = "use something else")]
+    sub: SubCommandEnum,
+}
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum SubCommandEnum {
+    Sub(Sub),
+}
+
+/// Sub
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "sub")]
+struct Sub {}
+
+fn main() {}
@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// foo
+    #[argh(option, short = 'f')]
+    foo: u32,
+
+    /// bar
+    #[argh(option, alias = "f", visible_alias = "foo")]
+    bar: u32,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    #[argh(subcommand)]
+    sub: Sub,
+}
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum Sub {
+    Status(Status),
+    Stash(Stash),
+}
+
+/// Show the status.
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "status", alias = "st")]
+struct Status {}
+
+/// Stash changes.
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "stash", visible_alias = "st")]
+struct Stash {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// verbose
+    #[argh(switch, env = "APP_VERBOSE")]
+    verbose: bool,
+}
+
+fn main() {}
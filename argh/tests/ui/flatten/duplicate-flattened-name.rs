@@ -0,0 +1,21 @@
+/// Shared options
+#[derive(argh::FromArgs)]
+struct Common {
+    /// verbose
+    #[argh(switch, short = 'v')]
+    verbose: bool,
+}
+
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// version
+    #[argh(switch, short = 'v')]
+    version: bool,
+
+    /// common
+    #[argh(flatten)]
+    common: Common,
+}
+
+fn main() {}
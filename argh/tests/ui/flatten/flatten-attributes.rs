@@ -0,0 +1,17 @@
+/// Shared options
+#[derive(argh::FromArgs)]
+struct Common {
+    /// verbose
+    #[argh(switch)]
+    verbose: bool,
+}
+
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// common
+    #[argh(flatten, short = 'c')]
+    common: Common,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+/// Inputs
+#[derive(argh::FromArgs)]
+struct Inputs {
+    /// input
+    #[argh(positional)]
+    input: String,
+}
+
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// inputs
+    #[argh(flatten)]
+    inputs: Inputs,
+}
+
+fn main() {}
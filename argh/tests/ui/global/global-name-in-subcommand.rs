@@ -0,0 +1,27 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// verbose
+    #[argh(switch, short = 'v', global)]
+    verbose: bool,
+
+    #[argh(subcommand)]
+    command: SubCommand,
+}
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+enum SubCommand {
+    Show(Show),
+}
+
+/// Show
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "show")]
+struct Show {
+    /// version
+    #[argh(switch, short = 'v')]
+    version: bool,
+}
+
+fn main() {}
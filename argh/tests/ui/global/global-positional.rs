@@ -0,0 +1,9 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// name
+    #[argh(positional, global)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// color
+    #[argh(switch, negatable)]
+    color: bool,
+
+    /// no color
+    #[argh(switch)]
+    no_color: bool,
+}
+
+fn main() {}
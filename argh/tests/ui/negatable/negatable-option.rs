@@ -0,0 +1,9 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// color
+    #[argh(option, negatable)]
+    color: String,
+}
+
+fn main() {}
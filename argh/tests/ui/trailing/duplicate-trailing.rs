@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// passed on
+    #[argh(trailing)]
+    passthrough: Vec<String>,
+
+    /// also passed on
+    #[argh(trailing)]
+    more: Vec<String>,
+}
+
+fn main() {}
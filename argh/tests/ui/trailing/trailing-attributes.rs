@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// passed on
+    #[argh(trailing)]
+    passthrough: String,
+
+    /// also passed on
+    #[argh(trailing, default = "vec![]")]
+    more: Vec<String>,
+}
+
+fn main() {}
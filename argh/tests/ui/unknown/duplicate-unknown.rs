@@ -0,0 +1,13 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// passed on
+    #[argh(unknown)]
+    rest: Vec<String>,
+
+    /// also passed on
+    #[argh(unknown)]
+    more: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+/// Command
+#[derive(argh::FromArgs)]
+struct Cmd {
+    /// passed on
+    #[argh(unknown)]
+    rest: String,
+
+    /// passed on
+    #[argh(unknown, short = 'u', deprecated)]
+    more: Vec<String>,
+
+    /// name
+    #[argh(option, with_values)]
+    name: String,
+}
+
+fn main() {}
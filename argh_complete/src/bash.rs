@@ -62,7 +62,10 @@ impl Generator for Bash {
 
 fn generate_bash_case(out: &mut String, prefix: &str, cmd: &CommandInfoWithArgs<'_>) {
     let full_name = format!("{}_{}", prefix, cmd.name);
-    writeln!(out, "            {})", cmd.name).unwrap();
+    let names: Vec<&str> = std::iter::once(cmd.name)
+        .chain(cmd.aliases.iter().chain(cmd.visible_aliases).copied())
+        .collect();
+    writeln!(out, "            {})", names.join(" | ")).unwrap();
     writeln!(out, "                cmd=\"{}\"", full_name).unwrap();
     writeln!(out, "                ;;").unwrap();
     for subcmd in &cmd.commands {
@@ -81,11 +84,20 @@ fn generate_bash_dispatch(out: &mut String, full_name: &str, cmd: &CommandInfoWi
         if let Some(short) = flag.short {
             opts.push(format!("-{}", short));
         }
+        if let Some(negated_long) = flag.negated_long {
+            opts.push(negated_long.to_string());
+        }
+        for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+            opts.push(alias.to_string());
+        }
     }
 
     let mut cmds = Vec::new();
     for subcmd in &cmd.commands {
         cmds.push(subcmd.name.to_string());
+        for alias in subcmd.command.aliases.iter().chain(subcmd.command.visible_aliases) {
+            cmds.push(alias.to_string());
+        }
     }
 
     if !opts.is_empty() {
@@ -106,6 +118,9 @@ fn generate_bash_dispatch(out: &mut String, full_name: &str, cmd: &CommandInfoWi
                 if let Some(short) = flag.short {
                     prev_matches.push(format!("-{}", short));
                 }
+                for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+                    prev_matches.push(alias.to_string());
+                }
                 if !prev_matches.is_empty() {
                     writeln!(out, "                {})", prev_matches.join(" | ")).unwrap();
                     writeln!(out, "                    COMPREPLY=()").unwrap();
@@ -21,6 +21,9 @@ fn collect_value_flags(cmd: &CommandInfoWithArgs<'_>, out: &mut Vec<String>) {
             if !long.is_empty() {
                 out.push(long.to_string());
             }
+            for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+                out.push(alias.trim_start_matches('-').to_string());
+            }
         }
     }
     for sub in &cmd.commands {
@@ -144,28 +147,66 @@ fn generate_fish_cmd(
         }
 
         writeln!(out, "{}", line).unwrap();
+
+        if let Some(negated_long) = flag.negated_long {
+            let mut line = format!("complete -c {} {}{}", base_cmd, joined_condition, no_files);
+            line.push_str(&format!(" -l {}", negated_long.trim_start_matches('-')));
+            if !flag.description.is_empty() {
+                let description = flag.description.replace("'", "\\'");
+                line.push_str(&format!(" -d '{}'", description));
+            }
+            writeln!(out, "{}", line).unwrap();
+        }
+
+        for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+            let mut line = format!("complete -c {} {}{}", base_cmd, joined_condition, no_files);
+            match alias.strip_prefix("--") {
+                Some(long) => line.push_str(&format!(" -l {}", long)),
+                None => line.push_str(&format!(" -s {}", alias.trim_start_matches('-'))),
+            }
+            if let FlagInfoKind::Option { .. } = flag.kind {
+                line.push_str(" -r");
+            }
+            if !flag.description.is_empty() {
+                let description = flag.description.replace("'", "\\'");
+                line.push_str(&format!(" -d '{}'", description));
+            }
+            writeln!(out, "{}", line).unwrap();
+        }
     }
 
     // Generate immediate subcommands (as arguments to this command)
     for subcmd in &cmd.commands {
-        let mut line = format!("complete -c {}", base_cmd);
-        if !joined_condition.is_empty() {
-            line.push(' ');
-            line.push_str(&joined_condition);
+        for name in subcommand_names(subcmd.name, &subcmd.command) {
+            let mut line = format!("complete -c {}", base_cmd);
+            if !joined_condition.is_empty() {
+                line.push(' ');
+                line.push_str(&joined_condition);
+            }
+            // Subcommands are just arguments that don't take files
+            line.push_str(&format!(
+                " -f -a '{}' -d '{}'",
+                name,
+                subcmd.command.description.replace("'", "\\'")
+            ));
+            writeln!(out, "{}", line).unwrap();
         }
-        // Subcommands are just arguments that don't take files
-        line.push_str(&format!(
-            " -f -a '{}' -d '{}'",
-            subcmd.name,
-            subcmd.command.description.replace("'", "\\'")
-        ));
-        writeln!(out, "{}", line).unwrap();
     }
 
-    // Recurse
+    // Recurse, once for the name and each alias of the subcommand, since the
+    // completions are keyed on the words typed so far.
     for subcmd in &cmd.commands {
-        let mut new_parents = parent_subcommands.to_vec();
-        new_parents.push(subcmd.name);
-        generate_fish_cmd(out, bin_name, base_cmd, &subcmd.command, &new_parents);
+        for name in subcommand_names(subcmd.name, &subcmd.command) {
+            let mut new_parents = parent_subcommands.to_vec();
+            new_parents.push(name);
+            generate_fish_cmd(out, bin_name, base_cmd, &subcmd.command, &new_parents);
+        }
     }
 }
+
+fn subcommand_names<'a>(
+    name: &'a str,
+    cmd: &CommandInfoWithArgs<'a>,
+) -> impl Iterator<Item = &'a str> {
+    std::iter::once(name).chain(cmd.aliases.iter().chain(cmd.visible_aliases).copied())
+}
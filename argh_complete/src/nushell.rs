@@ -51,6 +51,25 @@ fn generate_nushell_cmd(out: &mut String, cmd_name: &str, cmd: &CommandInfoWithA
         }
 
         writeln!(out, "    {}", flag_def).unwrap();
+
+        if let Some(negated_long) = flag.negated_long {
+            let mut negated_def = negated_long.to_string();
+            if !flag.description.is_empty() {
+                negated_def.push_str(&format!(" # {}", flag.description));
+            }
+            writeln!(out, "    {}", negated_def).unwrap();
+        }
+
+        for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+            let mut alias_def = alias.to_string();
+            if let FlagInfoKind::Option { .. } = flag.kind {
+                alias_def.push_str(": string");
+            }
+            if !flag.description.is_empty() {
+                alias_def.push_str(&format!(" # {}", flag.description));
+            }
+            writeln!(out, "    {}", alias_def).unwrap();
+        }
     }
 
     // Generate positional arguments
@@ -61,7 +80,7 @@ fn generate_nushell_cmd(out: &mut String, cmd_name: &str, cmd: &CommandInfoWithA
         match pos.optionality {
             Optionality::Required => pos_def.push_str(&format!("{}: string", name)),
             Optionality::Optional => pos_def.push_str(&format!("{}?: string", name)),
-            Optionality::Repeating | Optionality::Greedy => {
+            Optionality::Repeating | Optionality::Greedy | Optionality::Trailing => {
                 pos_def.push_str(&format!("...{}: string", name))
             }
         }
@@ -78,7 +97,10 @@ fn generate_nushell_cmd(out: &mut String, cmd_name: &str, cmd: &CommandInfoWithA
 
     // Recurse for subcommands
     for subcmd in &cmd.commands {
-        let next_cmd_name = format!("{} {}", cmd_name, subcmd.name);
-        generate_nushell_cmd(out, &next_cmd_name, &subcmd.command);
+        let aliases = subcmd.command.aliases.iter().chain(subcmd.command.visible_aliases);
+        for name in std::iter::once(&subcmd.name).chain(aliases) {
+            let next_cmd_name = format!("{} {}", cmd_name, name);
+            generate_nushell_cmd(out, &next_cmd_name, &subcmd.command);
+        }
     }
 }
@@ -54,6 +54,11 @@ fn make_mock_command() -> CommandInfoWithArgs<'static> {
             optionality: Optionality::Optional,
             long: "--verbose",
             short: Some('v'),
+            negated_long: None,
+            env: None,
+            aliases: &[],
+            visible_aliases: &[],
+            deprecated: None,
             description: "verbose output",
             hidden: false,
         }],
@@ -108,3 +113,91 @@ fn test_nushell_generator() {
     assert!(nushell_out.contains("--verbose(-v) # verbose output"));
     assert!(nushell_out.contains("export extern \"mycmd subcmd\" ["));
 }
+
+#[test]
+fn test_negatable_switch() {
+    let cmd = CommandInfoWithArgs {
+        name: "mycmd",
+        description: "A command with a negatable switch",
+        flags: &[FlagInfo {
+            kind: FlagInfoKind::Switch,
+            optionality: Optionality::Optional,
+            long: "--color",
+            short: None,
+            negated_long: Some("--no-color"),
+            env: None,
+            aliases: &[],
+            visible_aliases: &[],
+            deprecated: None,
+            description: "colorize output",
+            hidden: false,
+        }],
+        ..Default::default()
+    };
+
+    let bash_out = crate::bash::Bash::generate("mycmd", &cmd);
+    assert!(bash_out.contains("opts=\"--color --no-color\""));
+
+    let zsh_out = crate::zsh::Zsh::generate("mycmd", &cmd);
+    assert!(zsh_out.contains("'--no-color[colorize output]'"));
+
+    let fish_out = crate::fish::Fish::generate("mycmd", &cmd);
+    assert!(fish_out.contains(
+        "complete -c mycmd -n '__fish_mycmd_using_command' -f -l no-color -d 'colorize output'"
+    ));
+
+    let nushell_out = crate::nushell::Nushell::generate("mycmd", &cmd);
+    assert!(nushell_out.contains("--no-color # colorize output"));
+}
+
+#[test]
+fn test_aliases() {
+    let remove = CommandInfoWithArgs {
+        name: "remove",
+        description: "remove a file",
+        aliases: &["rm"],
+        visible_aliases: &["delete"],
+        deprecated: None,
+        ..Default::default()
+    };
+    let cmd = CommandInfoWithArgs {
+        name: "mycmd",
+        description: "A command with aliases",
+        flags: &[FlagInfo {
+            kind: FlagInfoKind::Option { arg_name: "name" },
+            optionality: Optionality::Optional,
+            long: "--name",
+            short: None,
+            negated_long: None,
+            env: None,
+            aliases: &["--old-name"],
+            visible_aliases: &["-x"],
+            deprecated: None,
+            description: "the name",
+            hidden: false,
+        }],
+        commands: vec![SubCommandInfo { name: "remove", command: remove }],
+        ..Default::default()
+    };
+
+    let bash_out = crate::bash::Bash::generate("mycmd", &cmd);
+    assert!(bash_out.contains("opts=\"--name --old-name -x\""));
+    assert!(bash_out.contains("cmds=\"remove rm delete\""));
+    assert!(bash_out.contains("remove | rm | delete)"));
+
+    let zsh_out = crate::zsh::Zsh::generate("mycmd", &cmd);
+    assert!(zsh_out.contains("'--old-name[the name]'': :'"));
+    assert!(zsh_out.contains("'rm:remove a file'"));
+    assert!(zsh_out.contains("(remove|rm|delete)"));
+
+    let fish_out = crate::fish::Fish::generate("mycmd", &cmd);
+    assert!(fish_out
+        .contains("complete -c mycmd -n '__fish_mycmd_using_command' -f -s x -r -d 'the name'"));
+    assert!(fish_out.contains(
+        "complete -c mycmd -n '__fish_mycmd_using_command' -f -a 'rm' -d 'remove a file'"
+    ));
+
+    let nushell_out = crate::nushell::Nushell::generate("mycmd", &cmd);
+    assert!(nushell_out.contains("--old-name: string # the name"));
+    assert!(nushell_out.contains("export extern \"mycmd delete\" ["));
+}
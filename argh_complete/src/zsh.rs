@@ -88,11 +88,21 @@ fn generate_zsh_args(out: &mut String, prefix: &str, cmd: &CommandInfoWithArgs<'
             def.push_str(&format!("'{}[{}]'", short, desc));
         }
 
-        if let FlagInfoKind::Option { .. } = flag.kind {
+        let takes_value = matches!(flag.kind, FlagInfoKind::Option { .. });
+        if takes_value {
             def.push_str("': :'"); // generic argument
         }
 
         writeln!(out, "{}    {} \\", ind, def).unwrap();
+
+        for alias in flag.aliases.iter().chain(flag.visible_aliases) {
+            let value = if takes_value { "': :'" } else { "" };
+            writeln!(out, "{}    '{}[{}]'{} \\", ind, alias, desc, value).unwrap();
+        }
+
+        if let Some(negated_long) = flag.negated_long {
+            writeln!(out, "{}    '{}[{}]' \\", ind, negated_long, desc).unwrap();
+        }
     }
 
     if !cmd.commands.is_empty() {
@@ -105,6 +115,9 @@ fn generate_zsh_args(out: &mut String, prefix: &str, cmd: &CommandInfoWithArgs<'
         for subcmd in &cmd.commands {
             let desc = subcmd.command.description.replace("'", "'\\''").replace(":", "\\:");
             writeln!(out, "{}            '{}:{}'", ind, subcmd.name, desc).unwrap();
+            for alias in subcmd.command.aliases.iter().chain(subcmd.command.visible_aliases) {
+                writeln!(out, "{}            '{}:{}'", ind, alias, desc).unwrap();
+            }
         }
         writeln!(out, "{}        )", ind).unwrap();
         writeln!(out, "{}        if (( CURRENT == 1 )); then", ind).unwrap();
@@ -117,7 +130,10 @@ fn generate_zsh_args(out: &mut String, prefix: &str, cmd: &CommandInfoWithArgs<'
             .unwrap();
         writeln!(out, "{}        case $cmd in", ind).unwrap();
         for subcmd in &cmd.commands {
-            writeln!(out, "{}            ({})", ind, subcmd.name).unwrap();
+            let names: Vec<&str> = std::iter::once(subcmd.name)
+                .chain(subcmd.command.aliases.iter().chain(subcmd.command.visible_aliases).copied())
+                .collect();
+            writeln!(out, "{}            ({})", ind, names.join("|")).unwrap();
             writeln!(out, "{}                _{}_{}", ind, prefix, subcmd.name).unwrap();
             writeln!(out, "{}                ;;", ind).unwrap();
         }
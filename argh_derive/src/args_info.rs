@@ -6,6 +6,7 @@ use crate::{
     enum_only_single_field_unnamed_variants,
     errors::Errors,
     help::require_description,
+    optional_lit_str,
     parse_attrs::{check_enum_type_attrs, FieldAttrs, FieldKind, TypeAttrs, VariantAttrs},
     Optionality, StructField,
 };
@@ -74,7 +75,7 @@ fn impl_arg_info_struct(
     let impl_span = Span::call_site();
 
     // Generate the implementation of `get_args_info()` for this struct.
-    let args_info = impl_args_info_data(name, errors, type_attrs, &fields);
+    let args_info = impl_args_info_data(name, errors, type_attrs, generic_args, &fields);
 
     // Split out the generics info for the impl declaration.
     let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
@@ -150,6 +151,9 @@ fn impl_arg_info_enum(
                     name: s.name,
                     short: s.short,
                     description: s.description,
+                    aliases: s.aliases,
+                    visible_aliases: s.visible_aliases,
+                    deprecated: s.deprecated,
                     ..Default::default()
                 }
             }).collect()
@@ -210,6 +214,7 @@ fn impl_args_info_data<'a>(
     name: &proc_macro2::Ident,
     errors: &Errors,
     type_attrs: &TypeAttrs,
+    generic_args: &syn::Generics,
     fields: &'a [StructField<'a>],
 ) -> TokenStream {
     let mut subcommands_iter =
@@ -230,6 +235,11 @@ fn impl_args_info_data<'a>(
         argh::FlagInfo {
             short: None,
             long: "--help",
+            negated_long: None,
+            env: None,
+            aliases: &[],
+            visible_aliases: &[],
+            deprecated: None,
             description: "display usage information",
             optionality: argh::Optionality::Optional,
             kind: argh::FlagInfoKind::Switch,
@@ -242,6 +252,7 @@ fn impl_args_info_data<'a>(
             Optionality::None => quote! { argh::Optionality::Required },
             Optionality::Defaulted(_) => quote! { argh::Optionality::Optional },
             Optionality::Optional => quote! { argh::Optionality::Optional },
+            _ if field.kind == FieldKind::Trailing => quote! { argh::Optionality::Trailing },
             Optionality::Repeating | Optionality::DefaultedRepeating(_)
                 if field.attrs.greedy.is_some() =>
             {
@@ -253,7 +264,7 @@ fn impl_args_info_data<'a>(
         };
 
         match field.kind {
-            FieldKind::Positional => {
+            FieldKind::Positional | FieldKind::Trailing => {
                 let name = field.positional_arg_name();
 
                 let description = if let Some(desc) = &field.attrs.description {
@@ -262,12 +273,14 @@ fn impl_args_info_data<'a>(
                     String::new()
                 };
                 let hidden = field.attrs.hidden_help;
+                let deprecated = optional_lit_str(&field.attrs.deprecated);
 
                 positionals.push(quote! {
                     argh::PositionalInfo {
                         name: #name,
                         description: #description,
                         optionality: #optionality,
+                        deprecated: #deprecated,
                         hidden: #hidden,
                     }
                 });
@@ -281,6 +294,18 @@ fn impl_args_info_data<'a>(
 
                 let long = field.long_name.as_ref().expect("missing long name for option");
 
+                let negated_long = if let Some(negated_long) = &field.negated_long_name {
+                    quote! { Some(#negated_long) }
+                } else {
+                    quote! { None }
+                };
+
+                let env = if let Some(env) = &field.attrs.env {
+                    quote! { Some(#env) }
+                } else {
+                    quote! { None }
+                };
+
                 let description = require_description(
                     errors,
                     field.name.span(),
@@ -300,19 +325,36 @@ fn impl_args_info_data<'a>(
                         quote! { #arg_name }
                     };
 
-                    quote! {
-                        argh::FlagInfoKind::Option {
-                            arg_name: #arg_name,
+                    if let Some(default_missing_value) = &field.attrs.default_missing_value {
+                        quote! {
+                            argh::FlagInfoKind::OptionalValue {
+                                arg_name: #arg_name,
+                                default_missing_value: #default_missing_value,
+                            }
+                        }
+                    } else {
+                        quote! {
+                            argh::FlagInfoKind::Option {
+                                arg_name: #arg_name,
+                            }
                         }
                     }
                 };
 
                 let hidden = field.attrs.hidden_help;
+                let aliases = &field.aliases;
+                let visible_aliases = &field.visible_aliases;
+                let deprecated = optional_lit_str(&field.attrs.deprecated);
 
                 flags.push(quote! {
                     argh::FlagInfo {
                         short: #short,
                         long: #long,
+                        negated_long: #negated_long,
+                        env: #env,
+                        aliases: &[#( #aliases ),*],
+                        visible_aliases: &[#( #visible_aliases ),*],
+                        deprecated: #deprecated,
                         description: #description,
                         optionality: #optionality,
                         kind: #kind,
@@ -320,10 +362,38 @@ fn impl_args_info_data<'a>(
                     }
                 });
             }
-            FieldKind::SubCommand => {}
+            // The flags of flattened fields are added below.
+            // Unknown options are not described.
+            FieldKind::SubCommand | FieldKind::Flatten | FieldKind::Unknown => {}
         }
     }
 
+    let flattened_tys: Vec<_> = fields
+        .iter()
+        .filter(|field| field.kind == FieldKind::Flatten)
+        .map(|field| field.ty_without_wrapper)
+        .collect();
+    let flattened_flags = quote! {
+        argh::flattened_flag_info(
+            &[#( #flags, )*],
+            &[#( <#flattened_tys as argh::ArgsInfo>::get_args_info().flags ),*],
+        )
+    };
+    let flags = if flattened_tys.is_empty() {
+        quote! { &[#( #flags, )*] }
+    } else if generic_args.params.is_empty() {
+        // The joined flags are leaked, so they are only joined once.
+        quote! { {
+            static FLAGS: ::std::sync::OnceLock<&'static [argh::FlagInfo<'static>]> =
+                ::std::sync::OnceLock::new();
+            *FLAGS.get_or_init(|| #flattened_flags)
+        } }
+    } else {
+        // A static in a generic impl would be shared by all of its
+        // instantiations, so generic structs join their flags on each call.
+        flattened_flags
+    };
+
     let empty_str = syn::LitStr::new("", Span::call_site());
     let type_name = LitStr::new(&name.to_string(), Span::call_site());
     let subcommand_name = if type_attrs.is_subcommand.is_some() {
@@ -335,13 +405,28 @@ fn impl_args_info_data<'a>(
         &type_name
     };
 
-    let subcommand = if let Some(subcommand) = subcommand {
-        let subcommand_ty = subcommand.ty_without_wrapper;
-        quote! {
-            #subcommand_ty::get_subcommands()
+    let subcommand = match (subcommand, &type_attrs.env_prefix) {
+        (Some(subcommand), Some(env_prefix)) => {
+            let subcommand_ty = subcommand.ty_without_wrapper;
+            quote! { {
+                let mut commands = #subcommand_ty::get_subcommands();
+                argh::inherit_env_prefix(&mut commands, Some(#env_prefix));
+                commands
+            } }
+        }
+        (Some(subcommand), None) => {
+            let subcommand_ty = subcommand.ty_without_wrapper;
+            quote! {
+                #subcommand_ty::get_subcommands()
+            }
         }
+        (None, _) => quote! {vec![]},
+    };
+
+    let env_prefix = if let Some(env_prefix) = &type_attrs.env_prefix {
+        quote! { Some(#env_prefix.into()) }
     } else {
-        quote! {vec![]}
+        quote! { None }
     };
 
     let description =
@@ -359,17 +444,25 @@ fn impl_args_info_data<'a>(
         quote! { &'\0' }
     };
 
+    let aliases = &type_attrs.aliases;
+    let visible_aliases = &type_attrs.visible_aliases;
+    let deprecated = optional_lit_str(&type_attrs.deprecated);
+
     quote_spanned! { impl_span =>
         argh::CommandInfoWithArgs {
             name: #subcommand_name,
             short: #short_name,
+            aliases: &[#( #aliases ),*],
+            visible_aliases: &[#( #visible_aliases ),*],
+            deprecated: #deprecated,
             description: #description,
             examples: &[#( #examples, )*],
             notes: &[#( #notes, )*],
             positionals: &[#( #positionals, )*],
-            flags: &[#( #flags, )*],
+            flags: #flags,
             commands: #subcommand,
             error_codes: &[#( #error_codes, )*],
+            env_prefix: #env_prefix,
         }
     }
 }
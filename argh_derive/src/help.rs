@@ -5,6 +5,7 @@
 use std::fmt::Write;
 use {
     crate::{
+        default_subcommand,
         errors::Errors,
         parse_attrs::{Description, FieldKind, TypeAttrs},
         Optionality, StructField,
@@ -33,8 +34,9 @@ pub(crate) fn help(
     let positional = fields.iter().filter(|f| {
         f.kind == FieldKind::Positional && f.attrs.greedy.is_none() && !f.attrs.hidden_help
     });
-    let has_positional = positional.clone().next().is_some();
-    let options = fields.iter().filter(|f| f.long_name.is_some() && !f.attrs.hidden_help);
+    let trailing = fields.iter().find(|f| f.kind == FieldKind::Trailing && !f.attrs.hidden_help);
+    let has_positional = positional.clone().next().is_some() || trailing.is_some();
+    let options = fields.iter().filter(|f| has_help_option(f));
 
     if let Some(usage) = &ty_attrs.usage {
         format_lit.push(' ');
@@ -47,12 +49,10 @@ pub(crate) fn help(
         let positional = positional.clone().filter(|p| !has_explicit_usage || p.attrs.usage);
         let options = options.clone().filter(|p| !has_explicit_usage || p.attrs.usage);
 
-        for option in options.clone() {
-            format_lit.push(' ');
-            option_usage(&mut format_lit, option);
-        }
+        options_usage(&mut format_lit, ty_attrs, fields, options);
 
-        if has_positional && subcommand.is_none() {
+        // Trailing arguments show where `--` goes themselves.
+        if has_positional && trailing.is_none() && subcommand.is_none() {
             format_lit.push_str(" [--]");
         }
 
@@ -69,14 +69,20 @@ pub(crate) fn help(
             positional_usage(&mut format_lit, arg);
         }
 
+        if let Some(trailing) = trailing {
+            format_lit.push_str(" [-- <");
+            format_lit.push_str(&trailing.positional_arg_name());
+            format_lit.push_str(">...]");
+        }
+
         if let Some(subcommand) = subcommand {
             format_lit.push(' ');
-            if !subcommand.optionality.is_required() {
-                format_lit.push('[');
-            }
-            format_lit.push_str("<command>");
-            if !subcommand.optionality.is_required() {
-                format_lit.push(']');
+            if subcommand.optionality.is_required() {
+                // Whether the command may be left out depends on whether the
+                // subcommands have a default.
+                format_lit.push_str("{__command_usage}");
+            } else {
+                format_lit.push_str("[<command>]");
             }
             format_lit.push_str(" [<args>]");
         }
@@ -90,16 +96,14 @@ pub(crate) fn help(
     if has_positional {
         format_lit.push_str(SECTION_SEPARATOR);
         format_lit.push_str("Positional Arguments:");
-        for arg in positional {
+        for arg in positional.chain(trailing) {
             positional_description(&mut format_lit, arg);
         }
     }
 
     format_lit.push_str(SECTION_SEPARATOR);
     format_lit.push_str("Options:");
-    for option in options {
-        option_description(errors, &mut format_lit, option);
-    }
+    options_description(errors, &mut format_lit, fields, options, "options");
     option_description_format(
         &mut format_lit,
         None,
@@ -107,14 +111,33 @@ pub(crate) fn help(
         "display usage information",
     );
 
+    // The global options of the parent commands, if this is a subcommand.
+    format_lit.push_str("{__global_options}");
+    let mut format_args = vec![quote! {
+        __global_options = argh::global_options_help(
+            __global_args,
+            &#cmd_name_str_array_ident.join(" "),
+        )
+    }];
+
     let subcommand_calculation;
-    let subcommand_format_arg;
     if let Some(subcommand) = subcommand {
         format_lit.push_str(SECTION_SEPARATOR);
         format_lit.push_str("Commands:{subcommands}");
         let subcommand_ty = subcommand.ty_without_wrapper;
-        subcommand_format_arg = quote! { subcommands = subcommands };
+        let default_subcommand = default_subcommand(subcommand);
+        format_args.push(quote! { subcommands = subcommands });
+        if format_lit.contains("{__command_usage}") {
+            format_args.push(quote! {
+                __command_usage = if __default_subcommand.is_some() {
+                    "[<command>]"
+                } else {
+                    "<command>"
+                }
+            });
+        }
         subcommand_calculation = quote! {
+            let __default_subcommand: Option<&str> = #default_subcommand;
             let subcommands = argh::print_subcommands(
                 <#subcommand_ty as argh::SubCommands>::COMMANDS
                     .iter()
@@ -122,12 +145,12 @@ pub(crate) fn help(
                     .chain(
                         <#subcommand_ty as argh::SubCommands>::dynamic_commands()
                             .iter()
-                            .copied())
+                            .copied()),
+                __default_subcommand,
             );
         };
     } else {
         subcommand_calculation = TokenStream::new();
-        subcommand_format_arg = TokenStream::new()
     }
 
     lits_section(&mut format_lit, "Examples:", &ty_attrs.examples);
@@ -146,12 +169,188 @@ pub(crate) fn help(
 
     format_lit.push('\n');
 
+    let cmd_name = quote! { &#cmd_name_str_array_ident.join(" ") };
+    format_args.extend(flatten_format_args(&format_lit, fields, &cmd_name));
+
     quote! { {
         #subcommand_calculation
-        format!(#format_lit, command_name = #cmd_name_str_array_ident.join(" "), #subcommand_format_arg)
+        format!(
+            #format_lit,
+            command_name = #cmd_name_str_array_ident.join(" ")
+            #(, #format_args)*
+        )
     } }
 }
 
+/// Returns `TokenStream`s generating the `String`s of `argh::Flatten::help_usage`,
+/// `argh::Flatten::help_options` and `argh::Flatten::help_global_options`,
+/// which are formatted with the name of the command as `__command_name`.
+pub(crate) fn flatten_help(ty_attrs: &TypeAttrs, fields: &[StructField<'_>]) -> [TokenStream; 3] {
+    // Missing descriptions are reported by `help`.
+    let errors = &Errors::default();
+
+    let options = fields.iter().filter(|f| has_help_option(f));
+    let has_explicit_usage = options.clone().any(|p| p.attrs.usage);
+    let mut usage = String::new();
+    options_usage(
+        &mut usage,
+        ty_attrs,
+        fields,
+        options.clone().filter(|p| !has_explicit_usage || p.attrs.usage),
+    );
+
+    let mut descriptions = String::new();
+    options_description(errors, &mut descriptions, fields, options.clone(), "options");
+
+    let mut global_descriptions = String::new();
+    let global_options =
+        options.filter(|f| f.attrs.global.is_some() || f.kind == FieldKind::Flatten);
+    options_description(errors, &mut global_descriptions, fields, global_options, "global");
+
+    [usage, descriptions, global_descriptions].map(|format_lit| {
+        let command_name = quote! { __command_name };
+        let mut format_args = flatten_format_args(&format_lit, fields, &command_name);
+        if uses_format_arg(&format_lit, "command_name") {
+            format_args.push(quote! { command_name = #command_name });
+        }
+        quote! { format!(#format_lit #(, #format_args)*) }
+    })
+}
+
+/// Whether a field is listed in the options of the help, as options and
+/// switches are, or has its options listed there, as `#[argh(flatten)]`
+/// fields do.
+fn has_help_option(field: &StructField<'_>) -> bool {
+    (field.long_name.is_some() || field.kind == FieldKind::Flatten) && !field.attrs.hidden_help
+}
+
+/// The name of the format argument holding part of the help of a
+/// `#[argh(flatten)]` field, which is its `usage`, its `options` or its
+/// `global` options.
+fn flatten_format_arg_name(
+    fields: &[StructField<'_>],
+    field: &StructField<'_>,
+    part: &str,
+) -> String {
+    let index = fields
+        .iter()
+        .filter(|field| field.kind == FieldKind::Flatten)
+        .position(|other| std::ptr::eq(other, field))
+        .expect("the field is flattened");
+    format!("__flatten_{}_{}", part, index)
+}
+
+/// The format arguments for the parts of the help of `#[argh(flatten)]`
+/// fields which `format_lit` uses.
+fn flatten_format_args(
+    format_lit: &str,
+    fields: &[StructField<'_>],
+    command_name: &TokenStream,
+) -> Vec<TokenStream> {
+    let mut format_args = vec![];
+    for field in fields.iter().filter(|field| field.kind == FieldKind::Flatten) {
+        let ty = field.ty_without_wrapper;
+        let parts = [
+            ("usage", quote! { help_usage }),
+            ("options", quote! { help_options }),
+            ("global", quote! { help_global_options }),
+        ];
+        for (part, function) in parts {
+            let name = flatten_format_arg_name(fields, field, part);
+            if uses_format_arg(format_lit, &name) {
+                let name = syn::Ident::new(&name, Span::call_site());
+                format_args.push(quote! {
+                    #name = <#ty as argh::Flatten>::#function(#command_name)
+                });
+            }
+        }
+    }
+    format_args
+}
+
+/// Whether `format_lit` uses the format argument `name` as `{name}`, rather
+/// than only containing it within escaped braces, like `{{name}}`.
+fn uses_format_arg(format_lit: &str, name: &str) -> bool {
+    let placeholder = ["{", name, "}"].concat();
+    let mut rest = format_lit;
+    while let Some(start) = rest.find(['{', '}']) {
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            rest = &rest[2..];
+        } else if rest.starts_with(&placeholder) {
+            return true;
+        } else {
+            rest = &rest[1..];
+        }
+    }
+    false
+}
+
+/// Add the options and switches of the usage line, each preceded by a space,
+/// to a help format string.
+fn options_usage<'a>(
+    out: &mut String,
+    ty_attrs: &TypeAttrs,
+    fields: &[StructField<'_>],
+    options: impl Iterator<Item = &'a StructField<'a>> + Clone,
+) {
+    let mut options_in_groups = vec![];
+    for option in options.clone() {
+        if option.kind == FieldKind::Flatten {
+            let name = flatten_format_arg_name(fields, option, "usage");
+            out.push_str(&["{", &name, "}"].concat());
+            continue;
+        }
+        if options_in_groups.contains(&option.name) {
+            continue;
+        }
+        out.push(' ');
+
+        // Options in a group which is `required` or not `multiple` are
+        // shown together, like `(--a | --b)`.
+        let group = option.attrs.group.as_ref().and_then(|name| {
+            ty_attrs.groups.iter().find(|group| group.name.value() == name.value())
+        });
+        match group {
+            Some(group) if group.required || !group.multiple => {
+                let members = options.clone().filter(|option| {
+                    option.attrs.group.as_ref().is_some_and(|g| g.value() == group.name.value())
+                });
+                out.push(if group.required { '(' } else { '[' });
+                for (i, member) in members.enumerate() {
+                    if i > 0 {
+                        out.push_str(" | ");
+                    }
+                    option_usage_inner(out, member);
+                    options_in_groups.push(member.name);
+                }
+                out.push(if group.required { ')' } else { ']' });
+            }
+            _ => option_usage(out, option),
+        }
+    }
+}
+
+/// Add the descriptions of the options and switches, each preceded by a
+/// newline, to a help format string. `#[argh(flatten)]` fields add the part
+/// of their help named `flatten_part`.
+fn options_description<'a>(
+    errors: &Errors,
+    out: &mut String,
+    fields: &[StructField<'_>],
+    options: impl Iterator<Item = &'a StructField<'a>>,
+    flatten_part: &str,
+) {
+    for option in options {
+        if option.kind == FieldKind::Flatten {
+            let name = flatten_format_arg_name(fields, option, flatten_part);
+            out.push_str(&["{", &name, "}"].concat());
+        } else {
+            option_description(errors, out, option);
+        }
+    }
+}
+
 /// A section composed of exactly just the literals provided to the program.
 fn lits_section(out: &mut String, heading: &str, lits: &[syn::LitStr]) {
     if !lits.is_empty() {
@@ -197,19 +396,36 @@ fn option_usage(out: &mut String, field: &StructField<'_>) {
         out.push('[');
     }
 
+    option_usage_inner(out, field);
+
+    if !field.optionality.is_required() {
+        out.push(']');
+    }
+}
+
+/// Add an option like `-f <foo>` to a help format string, without brackets.
+fn option_usage_inner(out: &mut String, field: &StructField<'_>) {
     let long_name = field.long_name.as_ref().expect("missing long name for option");
-    if let Some(short) = field.attrs.short.as_ref() {
+    // Values can only be attached to the long name of optional-value options,
+    // as in `--color=always`.
+    let optional_value = field.attrs.default_missing_value.is_some();
+    if let (Some(short), false) = (field.attrs.short.as_ref(), optional_value) {
         out.push('-');
         out.push(short.value());
     } else {
-        out.push_str(long_name);
+        out.push_str(&long_name_with_negation(field));
     }
 
     match field.kind {
-        FieldKind::SubCommand | FieldKind::Positional => unreachable!(), // don't have long_name
+        // don't have long_name
+        FieldKind::SubCommand
+        | FieldKind::Positional
+        | FieldKind::Flatten
+        | FieldKind::Unknown
+        | FieldKind::Trailing => unreachable!(),
         FieldKind::Switch => {}
         FieldKind::Option => {
-            out.push_str(" <");
+            out.push_str(if optional_value { "[=<" } else { " <" });
             if let Some(arg_name) = &field.attrs.arg_name {
                 out.push_str(&arg_name.value());
             } else {
@@ -221,13 +437,9 @@ fn option_usage(out: &mut String, field: &StructField<'_>) {
             ) {
                 out.push_str("...");
             }
-            out.push('>');
+            out.push_str(if optional_value { ">]" } else { ">" });
         }
     }
-
-    if !field.optionality.is_required() {
-        out.push(']');
-    }
 }
 
 // TODO(cramertj) make it so this is only called at least once per object so
@@ -260,11 +472,19 @@ fn positional_description(out: &mut String, field: &StructField<'_>) {
     if let Some(desc) = &field.attrs.description {
         description = desc.content.value().trim().to_owned();
     }
-    positional_description_format(out, &field_name, &description)
+    if let Some(note) = &field.attrs.deprecated {
+        description.push_str(&escape_braces(&argh_shared::deprecation_marker(&note.value())));
+    }
+    positional_description_format(out, &field_name, description.trim_start())
+}
+
+/// Doubles the braces in `text` to add it to a help format string as is.
+fn escape_braces(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
 }
 
 fn positional_description_format(out: &mut String, name: &str, description: &str) {
-    let info = argh_shared::CommandInfo { name, description, short: &'\0' };
+    let info = argh_shared::CommandInfo { name, description, ..Default::default() };
     argh_shared::write_description(out, &info);
 }
 
@@ -273,11 +493,34 @@ fn positional_description_format(out: &mut String, name: &str, description: &str
 ///                    is so long that it wraps to the next line.
 fn option_description(errors: &Errors, out: &mut String, field: &StructField<'_>) {
     let short = field.attrs.short.as_ref().map(|s| s.value());
-    let long_with_leading_dashes = field.long_name.as_ref().expect("missing long name for option");
-    let description =
+    let mut long_with_leading_dashes = long_name_with_negation(field);
+    for alias in &field.visible_aliases {
+        long_with_leading_dashes.push_str(", ");
+        long_with_leading_dashes.push_str(alias);
+    }
+    let mut description =
         require_description(errors, field.name.span(), &field.attrs.description, "field");
+    if let Some(env) = &field.attrs.env {
+        description.push_str(" [env: ");
+        description.push_str(&escape_braces(&env.value()));
+        description.push(']');
+    }
+    if let Some(note) = &field.attrs.deprecated {
+        description.push_str(&escape_braces(&argh_shared::deprecation_marker(&note.value())));
+    }
+
+    option_description_format(out, short, &long_with_leading_dashes, &description)
+}
 
-    option_description_format(out, short, long_with_leading_dashes, &description)
+/// Returns the long name of an option, or `--[no-]foo` for a `negatable` switch.
+/// This function must only be called on options (things with `long_name.is_some()`)
+fn long_name_with_negation(field: &StructField<'_>) -> String {
+    let long_name = field.long_name.as_ref().expect("missing long name for option");
+    if field.negated_long_name.is_some() {
+        ["--[no-]", long_name.trim_start_matches("--")].concat()
+    } else {
+        long_name.clone()
+    }
 }
 
 fn option_description_format(
@@ -294,6 +537,6 @@ fn option_description_format(
     }
     name.push_str(long_with_leading_dashes);
 
-    let info = argh_shared::CommandInfo { name: &name, description, short: &'\0' };
+    let info = argh_shared::CommandInfo { name: &name, description, ..Default::default() };
     argh_shared::write_description(out, &info);
 }
@@ -140,6 +140,14 @@ struct StructField<'a> {
     optionality: Optionality,
     // The `--`-prefixed name of the option, if one exists.
     long_name: Option<String>,
+    // The `--no-`-prefixed name of a `negatable` switch.
+    negated_long_name: Option<String>,
+    // The `-`- or `--`-prefixed aliases of an option or switch which are not
+    // shown in help.
+    aliases: Vec<String>,
+    // The `-`- or `--`-prefixed aliases of an option or switch which are
+    // shown in help.
+    visible_aliases: Vec<String>,
 }
 
 impl<'a> StructField<'a> {
@@ -156,7 +164,8 @@ impl<'a> StructField<'a> {
                 field,
                 concat!(
                     "Missing `argh` field kind attribute.\n",
-                    "Expected one of: `switch`, `option`, `remaining`, `subcommand`, `positional`",
+                    "Expected one of: `switch`, `option`, `remaining`, `subcommand`, `positional`, ",
+                    "`flatten`, `unknown`, `trailing`",
                 ),
             );
             return None;
@@ -170,26 +179,16 @@ impl<'a> StructField<'a> {
                 if !ty_expect_switch(errors, &field.ty) {
                     return None;
                 }
-                optionality = Optionality::Optional;
+                optionality = if let Some(default) = &attrs.default {
+                    Optionality::Defaulted(default_tokens(errors, default)?)
+                } else {
+                    Optionality::Optional
+                };
                 ty_without_wrapper = &field.ty;
             }
             FieldKind::Option | FieldKind::Positional => {
                 if let Some(default) = &attrs.default {
-                    let tokens = match TokenStream::from_str(&default.value()) {
-                        Ok(tokens) => tokens,
-                        Err(_) => {
-                            errors.err(&default, "Invalid tokens: unable to lex `default` value");
-                            return None;
-                        }
-                    };
-                    // Set the span of the generated tokens to the string literal
-                    let tokens: TokenStream = tokens
-                        .into_iter()
-                        .map(|mut tree| {
-                            tree.set_span(default.span());
-                            tree
-                        })
-                        .collect();
+                    let tokens = default_tokens(errors, default)?;
                     let inner = if let Some(x) = ty_inner(&["Vec"], &field.ty) {
                         optionality = Optionality::DefaultedRepeating(tokens);
                         x
@@ -218,6 +217,32 @@ impl<'a> StructField<'a> {
                     if inner.is_some() { Optionality::Optional } else { Optionality::None };
                 ty_without_wrapper = inner.unwrap_or(&field.ty);
             }
+            FieldKind::Flatten => {
+                optionality = Optionality::None;
+                ty_without_wrapper = &field.ty;
+            }
+            FieldKind::Unknown | FieldKind::Trailing => {
+                let Some(inner) = ty_inner(&["Vec"], &field.ty) else {
+                    let message = if kind == FieldKind::Unknown {
+                        "`#[argh(unknown)]` fields must be a `Vec`"
+                    } else {
+                        "`#[argh(trailing)]` fields must be a `Vec`"
+                    };
+                    errors.err(&field.ty, message);
+                    return None;
+                };
+                optionality = Optionality::Repeating;
+                ty_without_wrapper = inner;
+            }
+        }
+
+        if let (Some(delimiter), FieldKind::Option) = (&attrs.delimiter, kind) {
+            if !matches!(optionality, Optionality::Repeating | Optionality::DefaultedRepeating(_)) {
+                errors.err(
+                    delimiter,
+                    "`delimiter` may only be specified on repeating `#[argh(option)]` fields",
+                );
+            }
         }
 
         // Determine the "long" name of options and switches.
@@ -235,10 +260,43 @@ impl<'a> StructField<'a> {
                 let long_name = format!("--{}", long_name);
                 Some(long_name)
             }
-            FieldKind::SubCommand | FieldKind::Positional => None,
+            FieldKind::SubCommand
+            | FieldKind::Positional
+            | FieldKind::Flatten
+            | FieldKind::Unknown
+            | FieldKind::Trailing => None,
         };
 
-        Some(StructField { field, attrs, kind, optionality, ty_without_wrapper, name, long_name })
+        let negated_long_name = match (&long_name, &attrs.negatable) {
+            (Some(long_name), Some(_)) => {
+                Some(format!("--no-{}", long_name.trim_start_matches("--")))
+            }
+            _ => None,
+        };
+
+        let with_dashes = |alias: &LitStr| {
+            let alias = alias.value();
+            if alias.chars().count() == 1 {
+                format!("-{}", alias)
+            } else {
+                format!("--{}", alias)
+            }
+        };
+        let aliases = attrs.aliases.iter().map(with_dashes).collect();
+        let visible_aliases = attrs.visible_aliases.iter().map(with_dashes).collect();
+
+        Some(StructField {
+            field,
+            attrs,
+            kind,
+            optionality,
+            ty_without_wrapper,
+            name,
+            long_name,
+            negated_long_name,
+            aliases,
+            visible_aliases,
+        })
     }
 
     pub(crate) fn positional_arg_name(&self) -> String {
@@ -250,6 +308,27 @@ impl<'a> StructField<'a> {
     }
 }
 
+/// Lexes the value of a `default` attribute into tokens spanning the string literal.
+fn default_tokens(errors: &Errors, default: &LitStr) -> Option<TokenStream> {
+    let tokens = match TokenStream::from_str(&default.value()) {
+        Ok(tokens) => tokens,
+        Err(_) => {
+            errors.err(default, "Invalid tokens: unable to lex `default` value");
+            return None;
+        }
+    };
+    // Set the span of the generated tokens to the string literal
+    Some(
+        tokens
+            .into_iter()
+            .map(|mut tree| {
+                tree.set_span(default.span());
+                tree
+            })
+            .collect(),
+    )
+}
+
 fn to_kebab_case(s: &str) -> String {
     let words = s.split('_').filter(|word| !word.is_empty());
     let mut res = String::with_capacity(s.len());
@@ -320,8 +399,12 @@ fn impl_from_args_struct(
     let redact_arg_values_method =
         impl_from_args_struct_redact_arg_values(errors, type_attrs, &fields);
 
+    let error_codes_method = impl_from_args_struct_error_codes(type_attrs);
+
     let top_or_sub_cmd_impl = top_or_sub_cmd_impl(errors, name, type_attrs, generic_args);
 
+    let flatten_impl = impl_flatten(name, type_attrs, generic_args, &fields);
+
     let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
     let trait_impl = quote_spanned! { impl_span =>
         #[automatically_derived]
@@ -329,14 +412,133 @@ fn impl_from_args_struct(
             #from_args_method
 
             #redact_arg_values_method
+
+            #error_codes_method
         }
 
         #top_or_sub_cmd_impl
+
+        #flatten_impl
     };
 
     trait_impl
 }
 
+/// Implements `argh::Flatten`, so that the struct can be used as the type of
+/// a `#[argh(flatten)]` field.
+fn impl_flatten(
+    name: &syn::Ident,
+    type_attrs: &TypeAttrs,
+    generic_args: &syn::Generics,
+    fields: &[StructField<'_>],
+) -> TokenStream {
+    let flags = fields.iter().filter(|field| field.long_name.is_some()).flat_map(|field| {
+        let takes_value =
+            field.kind == FieldKind::Option && field.attrs.default_missing_value.is_none();
+        flag_names(field).into_iter().map(move |name| quote! { (#name, #takes_value) })
+    });
+    let globals = fields
+        .iter()
+        .filter(|field| field.attrs.global.is_some())
+        .flat_map(|field| flag_names(field));
+    let hidden_aliases = fields.iter().flat_map(|field| &field.aliases);
+    let flattened = fields.iter().filter(|field| field.kind == FieldKind::Flatten);
+    let flattened_tys: Vec<_> = flattened.clone().map(|field| field.ty_without_wrapper).collect();
+    let flattenable = !fields.iter().any(|field| {
+        matches!(
+            field.kind,
+            FieldKind::Positional
+                | FieldKind::SubCommand
+                | FieldKind::Unknown
+                | FieldKind::Trailing
+        )
+    });
+
+    let [help_usage, help_options, help_global_options] = if cfg!(feature = "help") {
+        help::flatten_help(type_attrs, fields)
+    } else {
+        [quote! { String::new() }, quote! { String::new() }, quote! { String::new() }]
+    };
+
+    // `FLAGS` is only evaluated once the struct's `FromArgs` impl is
+    // generated, so have `cargo check` report duplicate names as well, unless
+    // the struct is generic. The names of global options are checked against
+    // those of the subcommands, as is the name of the default subcommand.
+    let subcommand = fields.iter().find(|field| field.kind == FieldKind::SubCommand);
+    let check_flags = match subcommand {
+        _ if !generic_args.params.is_empty() => TokenStream::new(),
+        Some(subcommand) => {
+            let ty = subcommand.ty_without_wrapper;
+            let check_default = subcommand.attrs.default.as_ref().map(|default| {
+                quote! {
+                    const _: () = argh::check_default_command(
+                        <#ty as argh::SubCommands>::COMMANDS,
+                        #default,
+                    );
+                }
+            });
+            quote! {
+                const _: () = <#name as argh::Flatten>::FLAGS
+                    .check_globals(<#ty as argh::SubCommands>::FLAGS);
+                #check_default
+            }
+        }
+        None if flattened_tys.is_empty() => TokenStream::new(),
+        None => quote! {
+            const _: () = {
+                let _ = <#name as argh::Flatten>::FLAGS;
+            };
+        },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics argh::Flatten for #name #ty_generics #where_clause {
+            const FLAGS: &'static argh::FlattenedFlags = &argh::FlattenedFlags::new(
+                &[ #( #flags ),* ],
+                &[ #( <#flattened_tys as argh::Flatten>::FLAGS ),* ],
+                &[ #( #globals ),* ],
+                &[ #( #hidden_aliases ),* ],
+                #flattenable,
+            );
+
+            #[allow(unused_variables)]
+            fn help_usage(__command_name: &str) -> String {
+                #help_usage
+            }
+
+            #[allow(unused_variables)]
+            fn help_options(__command_name: &str) -> String {
+                #help_options
+            }
+
+            #[allow(unused_variables)]
+            fn help_global_options(__command_name: &str) -> String {
+                #help_global_options
+            }
+        }
+
+        #check_flags
+    }
+}
+
+/// Overrides `FromArgs::error_codes` for the codes declared with
+/// `#[argh(error_code(...))]`, if any.
+fn impl_from_args_struct_error_codes(type_attrs: &TypeAttrs) -> TokenStream {
+    if type_attrs.error_codes.is_empty() {
+        return TokenStream::new();
+    }
+    let error_codes = type_attrs.error_codes.iter().map(|(code, text)| {
+        quote! { argh::ErrorCodeInfo { code: #code, description: #text } }
+    });
+    quote! {
+        fn error_codes() -> &'static [argh::ErrorCodeInfo<'static>] {
+            &[#( #error_codes, )*]
+        }
+    }
+}
+
 fn impl_from_args_struct_from_args<'a>(
     errors: &Errors,
     type_attrs: &TypeAttrs,
@@ -357,16 +559,20 @@ fn impl_from_args_struct_from_args<'a>(
         .map(|field| field.kind == FieldKind::Positional && field.attrs.greedy.is_some())
         .unwrap_or(false);
 
-    let flag_output_table = fields.iter().filter_map(|field| {
-        let field_name = &field.field.ident;
-        match field.kind {
-            FieldKind::Option => Some(quote! { argh::ParseStructOption::Value(&mut #field_name) }),
-            FieldKind::Switch => Some(quote! { argh::ParseStructOption::Flag(&mut #field_name) }),
-            FieldKind::SubCommand | FieldKind::Positional => None,
-        }
-    });
+    let flag_output_table = flag_output_table_entries(fields, true);
 
-    let flag_str_to_output_table_map = flag_str_to_output_table_map_entries(fields);
+    let arg_to_slot = arg_to_slot(fields);
+
+    let env_fallbacks = env_fallbacks(fields);
+    let config_fallbacks = config_fallbacks(fields);
+    let env_prefix = match &type_attrs.env_prefix {
+        Some(env_prefix) => quote! { ::core::option::Option::Some(#env_prefix) },
+        None => quote! { __context.env_prefix() },
+    };
+    let collect_errors = match &type_attrs.collect_errors {
+        Some(_) => quote! { true },
+        None => quote! { __context.collect_errors() },
+    };
 
     let mut subcommands_iter =
         fields.iter().filter(|field| field.kind == FieldKind::SubCommand).fuse();
@@ -376,6 +582,28 @@ fn impl_from_args_struct_from_args<'a>(
         errors.duplicate_attrs("subcommand", subcommand.unwrap().field, dup_subcommand.field);
     }
 
+    let mut unknown_iter = fields.iter().filter(|field| field.kind == FieldKind::Unknown);
+    if let Some(unknown) = unknown_iter.next() {
+        for dup_unknown in unknown_iter {
+            errors.duplicate_attrs("unknown", unknown.field, dup_unknown.field);
+        }
+    }
+    let parse_unknown = parse_unknown(fields);
+
+    let mut trailing_iter = fields.iter().filter(|field| field.kind == FieldKind::Trailing);
+    if let Some(trailing) = trailing_iter.next() {
+        for dup_trailing in trailing_iter {
+            errors.duplicate_attrs("trailing", trailing.field, dup_trailing.field);
+        }
+        if let Some(greedy) = fields.iter().find(|field| field.attrs.greedy.is_some()) {
+            errors.err(
+                greedy.field,
+                "`greedy` positional arguments cannot be combined with a `#[argh(trailing)]` field",
+            );
+        }
+    }
+    let parse_trailing = parse_trailing(fields);
+
     let impl_span = Span::call_site();
 
     let missing_requirements_ident = syn::Ident::new("__missing_requirements", impl_span);
@@ -383,15 +611,49 @@ fn impl_from_args_struct_from_args<'a>(
     let append_missing_requirements =
         append_missing_requirements(&missing_requirements_ident, fields);
 
+    let declare_presence = declare_presence_for_switches(fields);
+    let append_constraint_checks =
+        append_constraint_checks(errors, &missing_requirements_ident, type_attrs, fields);
+    let (record_deprecated_fields, warn_deprecated_fields): (Vec<_>, Vec<_>) =
+        warn_deprecated_fields(fields).unzip();
+    let warn_deprecated_command = warn_deprecated_command(type_attrs);
+    let parse_flattened =
+        fields.iter().filter(|field| field.kind == FieldKind::Flatten).map(|field| {
+            let field_name = field.name;
+            let ty = field.ty_without_wrapper;
+            quote! {
+                let #field_name = #field_name.parse::<#ty>(
+                    __cmd_name,
+                    __context,
+                    __env_prefix,
+                    &mut __errors,
+                    &mut #missing_requirements_ident,
+                )?;
+            }
+        });
+
     let parse_subcommands = if let Some(subcommand) = subcommand {
         let name = subcommand.name;
         let ty = subcommand.ty_without_wrapper;
+        let default = default_subcommand(subcommand);
         quote_spanned! { impl_span =>
             Some(argh::ParseStructSubCommand {
                 subcommands: <#ty as argh::SubCommands>::COMMANDS,
                 dynamic_subcommands: &<#ty as argh::SubCommands>::dynamic_commands(),
-                parse_func: &mut |__command, __remaining_args| {
-                    #name = Some(<#ty as argh::FromArgs>::from_args(__command, __remaining_args)?);
+                default: #default,
+                parse_func: &mut |__command, __remaining_args, __offset, __globals| {
+                    let __context = __context.subcommand(
+                        __env_prefix,
+                        __collect_errors,
+                        __command,
+                        __offset,
+                        __globals,
+                    );
+                    #name = Some(<#ty as argh::FromArgs>::from_args_with_context(
+                        &argh::str_args_to_os(__command),
+                        __remaining_args,
+                        &__context,
+                    )?);
                     ::core::result::Result::Ok(())
                 },
             })
@@ -400,6 +662,9 @@ fn impl_from_args_struct_from_args<'a>(
         quote_spanned! { impl_span => None }
     };
 
+    let expand_response_files =
+        expand_response_files(type_attrs, quote! { __context.expand_response_files(__args) });
+
     let help_triggers = get_help_triggers(type_attrs);
 
     let help = if cfg!(feature = "help") {
@@ -414,17 +679,56 @@ fn impl_from_args_struct_from_args<'a>(
         fn from_args(__cmd_name: &[&str], __args: &[&str])
             -> ::core::result::Result<Self, argh::EarlyExit>
         {
+            Self::from_args_os(&argh::str_args_to_os(__cmd_name), &argh::str_args_to_os(__args))
+        }
+
+        fn from_args_os(__cmd_name: &[&::std::ffi::OsStr], __args: &[&::std::ffi::OsStr])
+            -> ::core::result::Result<Self, argh::EarlyExit>
+        {
+            Self::from_args_with_env(__cmd_name, __args, &argh::ProcessEnvironment)
+        }
+
+        fn from_args_with_env(
+            __cmd_name: &[&::std::ffi::OsStr],
+            __args: &[&::std::ffi::OsStr],
+            __env: &dyn argh::Environment,
+        ) -> ::core::result::Result<Self, argh::EarlyExit> {
+            Self::from_args_with_context(__cmd_name, __args, &argh::ParseContext::new(__env))
+                .map_err(argh::EarlyExit::from)
+        }
+
+        fn from_args_with_context(
+            __cmd_name: &[&::std::ffi::OsStr],
+            __args: &[&::std::ffi::OsStr],
+            __context: &argh::ParseContext<'_>,
+        ) -> ::core::result::Result<Self, argh::ParseError> {
             #![allow(clippy::unwrap_in_result)]
 
+            let __cmd_name = argh::os_args_to_lossy(__cmd_name);
+            let __cmd_name: &[&str] = &__cmd_name.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
+            let __env = __context.env();
+            let __env_prefix: ::core::option::Option<&str> = #env_prefix;
+            let __collect_errors: bool = #collect_errors;
+            let __global_args = __context.global_args();
+            let mut __errors = argh::CollectedErrors::new(__collect_errors);
+            #expand_response_files
+
             #( #init_fields )*
+            #( #declare_presence )*
 
             argh::parse_struct_args(
                 __cmd_name,
                 __args,
                 argh::ParseStructOptions {
-                    arg_to_slot: &[ #( #flag_str_to_output_table_map ,)* ],
+                    arg_to_slot: #arg_to_slot,
                     slots: &mut [ #( #flag_output_table, )* ],
                     help_triggers: &[ #( #help_triggers ),* ],
+                    globals: argh::GlobalArgs::new(
+                        <Self as argh::Flatten>::FLAGS,
+                        <Self as argh::Flatten>::help_global_options,
+                        __global_args,
+                    ),
+                    unknown: #parse_unknown,
                 },
                 argh::ParseStructPositionals {
                     positionals: &mut [
@@ -437,16 +741,31 @@ fn impl_from_args_struct_from_args<'a>(
                     ],
                     last_is_repeating: #last_positional_is_repeating,
                     last_is_greedy: #last_positional_is_greedy,
+                    trailing: #parse_trailing,
                 },
                 #parse_subcommands,
                 &|| #help,
+                &mut __errors,
             )?;
 
+            #( #record_deprecated_fields )*
+
+            #( #env_fallbacks )*
+            #( #config_fallbacks )*
+
             let mut #missing_requirements_ident = argh::MissingRequirements::default();
+            #( #parse_flattened )*
             #(
                 #append_missing_requirements
             )*
-            #missing_requirements_ident.err_on_any()?;
+            #(
+                #append_constraint_checks
+            )*
+            __errors.check(#missing_requirements_ident.err_on_any())?;
+            __errors.finish()?;
+
+            #( #warn_deprecated_fields )*
+            #warn_deprecated_command
 
             ::core::result::Result::Ok(Self {
                 #( #unwrap_fields, )*
@@ -457,6 +776,89 @@ fn impl_from_args_struct_from_args<'a>(
     method_impl
 }
 
+/// Tokens for an `Option<&'static str>` holding the value of `lit`, if any.
+fn optional_lit_str(lit: &Option<LitStr>) -> TokenStream {
+    match lit {
+        Some(lit) => quote! { ::core::option::Option::Some(#lit) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
+
+/// The warning recorded when a `deprecated` argument, command or value is used,
+/// like `Option '--zone' is deprecated: use --region instead`.
+fn deprecation_warning(kind: &str, name: &str, note: &LitStr) -> String {
+    let note = note.value();
+    let mut warning = format!("{} '{}' is deprecated", kind, name);
+    if !note.is_empty() {
+        warning.push_str(": ");
+        warning.push_str(&note);
+    }
+    warning
+}
+
+/// For each `deprecated` field, record whether it was provided on the command
+/// line, before any fallbacks fill it, and then warn if it was, once the
+/// arguments were parsed successfully.
+fn warn_deprecated_fields<'a>(
+    fields: &'a [StructField<'a>],
+) -> impl Iterator<Item = (TokenStream, TokenStream)> + 'a {
+    fields.iter().filter_map(|field| {
+        let note = field.attrs.deprecated.as_ref()?;
+        let field_name = field.name;
+        let (warning, present) = match field.kind {
+            FieldKind::Switch => (
+                deprecation_warning("Switch", field.long_name.as_ref()?, note),
+                presence_ident(field).into_token_stream(),
+            ),
+            FieldKind::Option => (
+                deprecation_warning("Option", field.long_name.as_ref()?, note),
+                quote! { argh::ParseValueSlot::is_filled(&#field_name) },
+            ),
+            FieldKind::Positional | FieldKind::Trailing => (
+                deprecation_warning("Argument", &field.positional_arg_name(), note),
+                quote! { argh::ParseValueSlot::is_filled(&#field_name) },
+            ),
+            FieldKind::SubCommand | FieldKind::Flatten | FieldKind::Unknown => return None,
+        };
+        let used = syn::Ident::new(
+            &format!("__{}_deprecated_used", field_name.unraw()),
+            Span::call_site(),
+        );
+        Some((
+            quote! { let #used = #present; },
+            quote! {
+                if #used {
+                    argh::warn(#warning);
+                }
+            },
+        ))
+    })
+}
+
+/// Record a warning when a `deprecated` subcommand is used.
+fn warn_deprecated_command(type_attrs: &TypeAttrs) -> TokenStream {
+    match (&type_attrs.deprecated, &type_attrs.name) {
+        (Some(note), Some(name)) => {
+            let warning = deprecation_warning("Command", &name.value(), note);
+            quote! { argh::warn(#warning); }
+        }
+        _ => TokenStream::new(),
+    }
+}
+
+/// Shadows `__args` with its response files expanded by `expand`, if the
+/// struct has the `#[argh(response_files)]` attribute.
+fn expand_response_files(type_attrs: &TypeAttrs, expand: TokenStream) -> TokenStream {
+    if type_attrs.response_files.is_none() {
+        return TokenStream::new();
+    }
+    quote! {
+        let __args = #expand?;
+        let __args: &[&::std::ffi::OsStr] =
+            &__args.iter().map(|arg| arg.as_os_str()).collect::<Vec<_>>();
+    }
+}
+
 /// get help triggers vector from type_attrs.help_triggers as a [`Vec<String>`]
 ///
 /// Defaults to vec!["--help", "help"] if type_attrs.help_triggers is None
@@ -501,16 +903,11 @@ fn impl_from_args_struct_redact_arg_values<'a>(
         .map(|field| field.kind == FieldKind::Positional && field.attrs.greedy.is_some())
         .unwrap_or(false);
 
-    let flag_output_table = fields.iter().filter_map(|field| {
-        let field_name = &field.field.ident;
-        match field.kind {
-            FieldKind::Option => Some(quote! { argh::ParseStructOption::Value(&mut #field_name) }),
-            FieldKind::Switch => Some(quote! { argh::ParseStructOption::Flag(&mut #field_name) }),
-            FieldKind::SubCommand | FieldKind::Positional => None,
-        }
-    });
+    let flag_output_table = flag_output_table_entries(fields, false);
 
-    let flag_str_to_output_table_map = flag_str_to_output_table_map_entries(fields);
+    let arg_to_slot = arg_to_slot(fields);
+    let parse_unknown = parse_unknown(fields);
+    let parse_trailing = parse_trailing(fields);
 
     let mut subcommands_iter =
         fields.iter().filter(|field| field.kind == FieldKind::SubCommand).fuse();
@@ -527,15 +924,28 @@ fn impl_from_args_struct_redact_arg_values<'a>(
     let append_missing_requirements =
         append_missing_requirements(&missing_requirements_ident, fields);
 
+    let redact_flattened =
+        fields.iter().filter(|field| field.kind == FieldKind::Flatten).map(|field| {
+            let field_name = field.name;
+            let ty = field.ty_without_wrapper;
+            quote! { let #field_name = #field_name.redact::<#ty>(__cmd_name)?; }
+        });
+
     let redact_subcommands = if let Some(subcommand) = subcommand {
         let name = subcommand.name;
         let ty = subcommand.ty_without_wrapper;
+        let default = default_subcommand(subcommand);
         quote_spanned! { impl_span =>
             Some(argh::ParseStructSubCommand {
                 subcommands: <#ty as argh::SubCommands>::COMMANDS,
                 dynamic_subcommands: &<#ty as argh::SubCommands>::dynamic_commands(),
-                parse_func: &mut |__command, __remaining_args| {
-                    #name = Some(<#ty as argh::FromArgs>::redact_arg_values(__command, __remaining_args)?);
+                default: #default,
+                parse_func: &mut |__command, __remaining_args, _, __globals| {
+                    #name = Some(<#ty as argh::FromArgs>::redact_arg_values_with_globals(
+                        __command,
+                        &argh::os_args_to_str(__remaining_args)?,
+                        Some(__globals),
+                    )?);
                     ::core::result::Result::Ok(())
                 },
             })
@@ -550,6 +960,9 @@ fn impl_from_args_struct_redact_arg_values<'a>(
         quote! { "no subcommand name" }
     };
 
+    let expand_response_files =
+        expand_response_files(type_attrs, quote! { argh::expand_response_files(__args) });
+
     let help_triggers = get_help_triggers(type_attrs);
 
     let help = if cfg!(feature = "help") {
@@ -562,15 +975,34 @@ fn impl_from_args_struct_redact_arg_values<'a>(
 
     let method_impl = quote_spanned! { impl_span =>
         fn redact_arg_values(__cmd_name: &[&str], __args: &[&str]) -> std::result::Result<Vec<String>, argh::EarlyExit> {
+            Self::redact_arg_values_with_globals(__cmd_name, __args, None)
+                .map_err(argh::EarlyExit::from)
+        }
+
+        fn redact_arg_values_with_globals(
+            __cmd_name: &[&str],
+            __args: &[&str],
+            __global_args: Option<&argh::GlobalArgs<'_>>,
+        ) -> std::result::Result<Vec<String>, argh::ParseError> {
+            let __args: &[&::std::ffi::OsStr] = &argh::str_args_to_os(__args);
+            let mut __errors = argh::CollectedErrors::new(false);
+            #expand_response_files
+
             #( #init_fields )*
 
             argh::parse_struct_args(
                 __cmd_name,
                 __args,
                 argh::ParseStructOptions {
-                    arg_to_slot: &[ #( #flag_str_to_output_table_map ,)* ],
+                    arg_to_slot: #arg_to_slot,
                     slots: &mut [ #( #flag_output_table, )* ],
                     help_triggers: &[ #( #help_triggers ),* ],
+                    globals: argh::GlobalArgs::new(
+                        <Self as argh::Flatten>::FLAGS,
+                        <Self as argh::Flatten>::help_global_options,
+                        __global_args,
+                    ),
+                    unknown: #parse_unknown,
                 },
                 argh::ParseStructPositionals {
                     positionals: &mut [
@@ -583,11 +1015,15 @@ fn impl_from_args_struct_redact_arg_values<'a>(
                     ],
                     last_is_repeating: #last_positional_is_repeating,
                     last_is_greedy: #last_positional_is_greedy,
+                    trailing: #parse_trailing,
                 },
                 #redact_subcommands,
                 &|| #help,
+                &mut __errors,
             )?;
 
+            #( #redact_flattened )*
+
             let mut #missing_requirements_ident = argh::MissingRequirements::default();
             #(
                 #append_missing_requirements
@@ -598,7 +1034,7 @@ fn impl_from_args_struct_redact_arg_values<'a>(
                 if let Some(cmd_name) = __cmd_name.last() {
                     (*cmd_name).to_owned()
                 } else {
-                    return ::core::result::Result::Err(argh::EarlyExit::from(#unwrap_cmd_name_err_string.to_owned()));
+                    return ::core::result::Result::Err(argh::ParseError::Message(#unwrap_cmd_name_err_string.to_owned()));
                 }
             ];
 
@@ -611,6 +1047,42 @@ fn impl_from_args_struct_redact_arg_values<'a>(
     method_impl
 }
 
+/// Returns an expression for the `argh::ParseStructUnknown` of the
+/// `#[argh(unknown)]` field, if there is one.
+fn parse_unknown(fields: &[StructField<'_>]) -> TokenStream {
+    let Some(field) = fields.iter().find(|field| field.kind == FieldKind::Unknown) else {
+        return quote! { None };
+    };
+    let field_name = field.name;
+    let name = field.positional_arg_name();
+    let with_values = field.attrs.with_values.is_some();
+    quote! {
+        Some(argh::ParseStructUnknown {
+            field: argh::ParseStructPositional {
+                name: #name,
+                slot: &mut #field_name as &mut argh::ParseValueSlot,
+            },
+            with_values: #with_values,
+        })
+    }
+}
+
+/// Returns an expression for the `argh::ParseStructPositional` of the
+/// `#[argh(trailing)]` field, if there is one.
+fn parse_trailing(fields: &[StructField<'_>]) -> TokenStream {
+    let Some(field) = fields.iter().find(|field| field.kind == FieldKind::Trailing) else {
+        return quote! { None };
+    };
+    let field_name = field.name;
+    let name = field.positional_arg_name();
+    quote! {
+        Some(argh::ParseStructPositional {
+            name: #name,
+            slot: &mut #field_name as &mut argh::ParseValueSlot,
+        })
+    }
+}
+
 /// Ensures that only the last positional arg is non-required.
 fn ensure_only_last_positional_is_optional(errors: &Errors, fields: &[StructField<'_>]) {
     let mut first_non_required_span = None;
@@ -637,20 +1109,25 @@ fn ensure_unique_names(errors: &Errors, fields: &[StructField<'_>]) {
     let mut seen_long_names = HashMap::new();
 
     for field in fields {
-        if let Some(short_name) = &field.attrs.short {
-            let short_name = short_name.value();
-            if let Some(first_use_field) = seen_short_names.get(&short_name) {
+        let short_name = field.attrs.short.as_ref().map(|short| format!("-{}", short.value()));
+        let aliases = field.aliases.iter().chain(&field.visible_aliases);
+        let (short_aliases, long_aliases): (Vec<_>, Vec<_>) =
+            aliases.partition(|alias| !alias.starts_with("--"));
+
+        for short_name in short_name.iter().chain(short_aliases) {
+            if let Some(first_use_field) = seen_short_names.get(short_name) {
                 errors.err_span_tokens(
                     first_use_field,
-                    &format!("The short name of \"-{}\" was already used here.", short_name),
+                    &format!("The short name of \"{}\" was already used here.", short_name),
                 );
                 errors.err_span_tokens(field.field, "Later usage here.");
             }
 
-            seen_short_names.insert(short_name, &field.field);
+            seen_short_names.insert(short_name.clone(), &field.field);
         }
 
-        if let Some(long_name) = &field.long_name {
+        for long_name in field.long_name.iter().chain(&field.negated_long_name).chain(long_aliases)
+        {
             if let Some(first_use_field) = seen_long_names.get(&long_name) {
                 errors.err_span_tokens(
                     *first_use_field,
@@ -691,6 +1168,9 @@ fn top_or_sub_cmd_impl(
         });
         let short_name =
             type_attrs.short.as_ref().map(|c| quote! { &#c }).unwrap_or_else(|| quote! { &'\0' });
+        let aliases = &type_attrs.aliases;
+        let visible_aliases = &type_attrs.visible_aliases;
+        let deprecated = optional_lit_str(&type_attrs.deprecated);
         quote! {
             #[automatically_derived]
             impl #impl_generics argh::SubCommand for #name #ty_generics #where_clause {
@@ -698,7 +1178,12 @@ fn top_or_sub_cmd_impl(
                     name: #subcommand_name,
                     short: #short_name,
                     description: #description,
+                    aliases: &[ #( #aliases ),* ],
+                    visible_aliases: &[ #( #visible_aliases ),* ],
+                    deprecated: #deprecated,
                 };
+
+                const FLAGS: &'static argh::FlattenedFlags = <Self as argh::Flatten>::FLAGS;
             }
         }
     }
@@ -728,29 +1213,41 @@ fn declare_local_storage_for_from_args_fields<'a>(
         };
 
         match field.kind {
-            FieldKind::Option | FieldKind::Positional => {
-                let from_str_fn = match &field.attrs.from_str_fn {
-                    Some(from_str_fn) => from_str_fn.into_token_stream(),
-                    None => {
-                        quote! {
-                            <#field_type as argh::FromArgValue>::from_arg_value
-                        }
-                    }
+            FieldKind::Option
+            | FieldKind::Positional
+            | FieldKind::Unknown
+            | FieldKind::Trailing => {
+                let parse_value = match &field.attrs.from_str_fn {
+                    Some(from_str_fn) => quote! { #from_str_fn(argh::os_value_to_str(value)?) },
+                    None if ty_is_os_value(field_type) => quote! {
+                        <#field_type as argh::FromArgOsValue>::from_arg_os_value(value)
+                    },
+                    None => quote! {
+                        <#field_type as argh::FromArgValue>::from_arg_value(
+                            argh::os_value_to_str(value)?
+                        )
+                    },
                 };
 
                 quote! {
                     let mut #field_name: argh::ParseValueSlotTy<#field_slot_type, #field_type>
                         = argh::ParseValueSlotTy {
                             slot: std::default::Default::default(),
-                            parse_func: |_, value| { #from_str_fn(value) },
+                            parse_func: |_, value| { #parse_value },
                         };
                 }
             }
             FieldKind::SubCommand => {
                 quote! { let mut #field_name: #field_slot_type = None; }
             }
+            FieldKind::Flatten => flattened_args(field),
             FieldKind::Switch => {
-                quote! { let mut #field_name: #field_slot_type = argh::Flag::default(); }
+                let field_type = &field.field.ty;
+                let init = match &field.optionality {
+                    Optionality::Defaulted(tokens) => tokens.clone(),
+                    _ => quote! { argh::Flag::default() },
+                };
+                quote! { let mut #field_name: #field_type = #init; }
             }
         }
     })
@@ -763,7 +1260,10 @@ fn unwrap_from_args_fields<'a>(
     fields.iter().map(|field| {
         let field_name = field.name;
         match field.kind {
-            FieldKind::Option | FieldKind::Positional => match &field.optionality {
+            FieldKind::Option
+            | FieldKind::Positional
+            | FieldKind::Unknown
+            | FieldKind::Trailing => match &field.optionality {
                 Optionality::None => quote! {
                     #field_name: #field_name.slot.unwrap()
                 },
@@ -782,6 +1282,7 @@ fn unwrap_from_args_fields<'a>(
                 Optionality::Optional | Optionality::Repeating => field_name.into_token_stream(),
                 Optionality::Defaulted(_) | Optionality::DefaultedRepeating(_) => unreachable!(),
             },
+            FieldKind::Flatten => quote! { #field_name: #field_name.unwrap() },
         }
     })
 }
@@ -826,7 +1327,7 @@ fn declare_local_storage_for_redacted_fields<'a>(
                     };
                 }
             }
-            FieldKind::Positional => {
+            FieldKind::Positional | FieldKind::Unknown | FieldKind::Trailing => {
                 let field_slot_type = match field.optionality {
                     Optionality::Repeating => {
                         quote! { std::vec::Vec<String> }
@@ -851,6 +1352,7 @@ fn declare_local_storage_for_redacted_fields<'a>(
             FieldKind::SubCommand => {
                 quote! { let mut #field_name: std::option::Option<std::vec::Vec<String>> = None; }
             }
+            FieldKind::Flatten => flattened_args(field),
         }
     })
 }
@@ -891,7 +1393,7 @@ fn unwrap_redacted_fields<'a>(
                     }
                 }
             },
-            FieldKind::Positional => {
+            FieldKind::Positional | FieldKind::Unknown | FieldKind::Trailing => {
                 quote! {
                     __redacted.extend(#field_name.slot.into_iter());
                 }
@@ -903,30 +1405,199 @@ fn unwrap_redacted_fields<'a>(
                     }
                 }
             }
+            FieldKind::Flatten => {
+                quote! {
+                    __redacted.extend(#field_name);
+                }
+            }
         }
     })
 }
 
+/// Entries of tokens like `argh::ParseStructOption::Flag(&mut some_flag)` that make up the
+/// output table, in the same order as the indices of `flag_str_to_output_table_map_entries`.
+///
+/// If `from_args` is set, the entries are for `FromArgs::from_args` rather than
+/// `FromArgs::redact_arg_values`. Options with a `delimiter` are then split
+/// into their elements, and switches which are constrained by `conflicts_with`,
+/// `requires` or `group` attributes, or are `deprecated`, also record whether
+/// they were provided.
+fn flag_output_table_entries<'a>(
+    fields: &'a [StructField<'a>],
+    from_args: bool,
+) -> impl Iterator<Item = TokenStream> + 'a {
+    fields.iter().filter_map(move |field| {
+        let field_name = &field.field.ident;
+        match field.kind {
+            FieldKind::Option => match (&field.attrs.delimiter, &field.attrs.default_missing_value) {
+                (_, Some(default_missing_value)) => Some(quote! {
+                    argh::ParseStructOption::OptionalValue(&mut #field_name, #default_missing_value)
+                }),
+                (Some(delimiter), None) if from_args => {
+                    let delimiter = syn::LitByte::new(delimiter.value() as u8, delimiter.span());
+                    Some(quote! {
+                        argh::ParseStructOption::DelimitedValue(&mut #field_name, #delimiter)
+                    })
+                }
+                _ => Some(quote! { argh::ParseStructOption::Value(&mut #field_name) }),
+            },
+            FieldKind::Switch => {
+                let flag = if from_args && tracks_presence(field, fields) {
+                    let present = presence_ident(field);
+                    quote! {
+                        &mut argh::ParseFlagPresence { flag: &mut #field_name, present: &mut #present }
+                    }
+                } else {
+                    quote! { &mut #field_name }
+                };
+                match &field.negated_long_name {
+                    Some(negated) => {
+                        Some(quote! { argh::ParseStructOption::NegatableFlag(#flag, #negated) })
+                    }
+                    None => Some(quote! { argh::ParseStructOption::Flag(#flag) }),
+                }
+            }
+            FieldKind::Flatten => Some(quote! { argh::ParseStructOption::Flattened(&mut #field_name) }),
+            FieldKind::SubCommand
+            | FieldKind::Positional
+            | FieldKind::Unknown
+            | FieldKind::Trailing => None,
+        }
+    })
+}
+
+/// Fill the options that were not provided on the command line from their
+/// `env` variables, or from the variables named after them under the
+/// `env_prefix`.
+fn env_fallbacks<'a>(fields: &'a [StructField<'a>]) -> impl Iterator<Item = TokenStream> + 'a {
+    fields.iter().filter(|field| field.kind == FieldKind::Option).filter_map(|field| {
+        let field_name = &field.field.ident;
+        let long_name = field.long_name.as_ref()?;
+        let delimiter = delimiter_tokens(field);
+        Some(match &field.attrs.env {
+            Some(env) => quote! {
+                __errors.check(argh::parse_env_fallback(
+                    &mut #field_name,
+                    #long_name,
+                    #env,
+                    #delimiter,
+                    __env,
+                ))?;
+            },
+            None => quote! {
+                __errors.check(argh::parse_prefixed_env_fallback(
+                    &mut #field_name,
+                    #long_name,
+                    __env_prefix,
+                    #delimiter,
+                    __env,
+                ))?;
+            },
+        })
+    })
+}
+
+/// Fill the options that were not provided on the command line or through
+/// the environment from the configuration file, if any.
+fn config_fallbacks<'a>(fields: &'a [StructField<'a>]) -> impl Iterator<Item = TokenStream> + 'a {
+    fields.iter().filter(|field| field.kind == FieldKind::Option && cfg!(feature = "config")).map(
+        |field| {
+            let field_name = &field.field.ident;
+            let long_name = field.long_name.as_ref();
+            let delimiter = delimiter_tokens(field);
+            quote! {
+                __errors.check(argh::parse_config_fallback(
+                    &mut #field_name,
+                    #long_name,
+                    #delimiter,
+                    __cmd_name,
+                    __context,
+                ))?;
+            }
+        },
+    )
+}
+
+/// Tokens for an `Option<u8>` holding the `delimiter` of an option, if any.
+fn delimiter_tokens(field: &StructField<'_>) -> TokenStream {
+    match &field.attrs.delimiter {
+        Some(delimiter) => {
+            let delimiter = syn::LitByte::new(delimiter.value() as u8, delimiter.span());
+            quote! { ::core::option::Option::Some(#delimiter) }
+        }
+        None => quote! { ::core::option::Option::None },
+    }
+}
+
 /// Entries of tokens like `("--some-flag-key", 5)` that map from a flag key string
 /// to an index in the output table.
 fn flag_str_to_output_table_map_entries<'a>(fields: &'a [StructField<'a>]) -> Vec<TokenStream> {
     let mut flag_str_to_output_table_map = vec![];
-    for (i, (field, long_name)) in fields
+    for (i, field) in fields.iter().filter(|field| has_slot(field)).enumerate() {
+        for name in flag_names(field) {
+            flag_str_to_output_table_map.push(quote! { (#name, #i) });
+        }
+    }
+    flag_str_to_output_table_map
+}
+
+/// The `arg_to_slot` of `argh::ParseStructOptions`, which also maps the names
+/// of the options and switches of `#[argh(flatten)]` fields to their slots.
+fn arg_to_slot(fields: &[StructField<'_>]) -> TokenStream {
+    let flag_str_to_output_table_map = flag_str_to_output_table_map_entries(fields);
+    let flattened_slots: Vec<usize> = fields
         .iter()
-        .filter_map(|field| field.long_name.as_ref().map(|long_name| (field, long_name)))
+        .filter(|field| has_slot(field))
         .enumerate()
-    {
-        if let Some(short) = &field.attrs.short {
-            let short = format!("-{}", short.value());
-            flag_str_to_output_table_map.push(quote! { (#short, #i) });
+        .filter(|(_, field)| field.kind == FieldKind::Flatten)
+        .map(|(i, _)| i)
+        .collect();
+    if flattened_slots.is_empty() {
+        quote! { &[ #( #flag_str_to_output_table_map ,)* ] }
+    } else {
+        quote! {
+            &argh::flattened_arg_to_slot(
+                &[ #( #flag_str_to_output_table_map ,)* ],
+                <Self as argh::Flatten>::FLAGS,
+                &[ #( #flattened_slots ),* ],
+            )
         }
+    }
+}
 
-        flag_str_to_output_table_map.push(quote! { (#long_name, #i) });
+/// Whether a field has a slot in the output table, as options, switches and
+/// `#[argh(flatten)]` fields do.
+fn has_slot(field: &StructField<'_>) -> bool {
+    field.long_name.is_some() || field.kind == FieldKind::Flatten
+}
+
+/// The names of an option or switch: its short name, long name, negated long
+/// name and aliases.
+fn flag_names(field: &StructField<'_>) -> Vec<String> {
+    let short = field.attrs.short.as_ref().map(|short| format!("-{}", short.value()));
+    let aliases = field.aliases.iter().chain(&field.visible_aliases).cloned();
+    short
+        .into_iter()
+        .chain(field.long_name.clone())
+        .chain(field.negated_long_name.clone())
+        .chain(aliases)
+        .collect()
+}
+
+/// Declare the `argh::FlattenedArgs` storing the options and switches of a
+/// `#[argh(flatten)]` field during parsing.
+fn flattened_args(field: &StructField<'_>) -> TokenStream {
+    let field_name = &field.field.ident;
+    let ty = field.ty_without_wrapper;
+    quote! {
+        let mut #field_name = argh::FlattenedArgs::new(<#ty as argh::Flatten>::FLAGS);
     }
-    flag_str_to_output_table_map
 }
 
 /// For each non-optional field, add an entry to the `argh::MissingRequirements`.
+///
+/// Fields which were provided but could not be parsed, as recorded in
+/// `__errors`, are not reported as missing.
 fn append_missing_requirements<'a>(
     // missing_requirements_ident
     mri: &syn::Ident,
@@ -937,18 +1608,34 @@ fn append_missing_requirements<'a>(
         let field_name = field.name;
         match field.kind {
             FieldKind::Switch => unreachable!("switches are always optional"),
+            FieldKind::Unknown => unreachable!("unknown fields are always optional"),
+            FieldKind::Trailing => unreachable!("trailing fields are always optional"),
+            // Missing requirements are added when the field is parsed.
+            FieldKind::Flatten => TokenStream::new(),
             FieldKind::Positional => {
                 let name = field.positional_arg_name();
+                let position = fields
+                    .iter()
+                    .filter(|field| field.kind == FieldKind::Positional)
+                    .position(|other| std::ptr::eq(other, field))
+                    .expect("the field is positional");
                 quote! {
-                    if #field_name.slot.is_none() {
+                    if #field_name.slot.is_none() && !__errors.positional_failed(#position) {
                         #mri.missing_positional_arg(#name)
                     }
                 }
             }
             FieldKind::Option => {
                 let name = field.long_name.as_ref().expect("options always have a long name");
+                // The slot of the option, as numbered by
+                // `flag_str_to_output_table_map_entries`.
+                let slot = fields
+                    .iter()
+                    .filter(|field| has_slot(field))
+                    .position(|other| std::ptr::eq(other, field))
+                    .expect("options always have a long name");
                 quote! {
-                    if #field_name.slot.is_none() {
+                    if #field_name.slot.is_none() && !__errors.option_failed(#slot) {
                         #mri.missing_option(#name)
                     }
                 }
@@ -956,7 +1643,7 @@ fn append_missing_requirements<'a>(
             FieldKind::SubCommand => {
                 let ty = field.ty_without_wrapper;
                 quote! {
-                    if #field_name.is_none() {
+                    if #field_name.is_none() && !__errors.subcommand_failed() {
                         #mri.missing_subcommands(
                             <#ty as argh::SubCommands>::COMMANDS
                                 .iter()
@@ -974,6 +1661,179 @@ fn append_missing_requirements<'a>(
     })
 }
 
+/// Whether a field has `conflicts_with`, `requires` or `group` attributes,
+/// or is referred to by another field's `conflicts_with` or `requires`.
+fn is_constrained(field: &StructField<'_>, fields: &[StructField<'_>]) -> bool {
+    let name = field.name.unraw().to_string();
+    field.attrs.group.is_some()
+        || !field.attrs.conflicts_with.is_empty()
+        || !field.attrs.requires.is_empty()
+        || fields.iter().any(|other| {
+            other
+                .attrs
+                .conflicts_with
+                .iter()
+                .chain(&other.attrs.requires)
+                .any(|r| r.value() == name)
+        })
+}
+
+/// Whether a switch records whether it was provided, because it is
+/// constrained or `deprecated`.
+fn tracks_presence(field: &StructField<'_>, fields: &[StructField<'_>]) -> bool {
+    field.attrs.deprecated.is_some() || is_constrained(field, fields)
+}
+
+/// The local which records whether a switch was provided.
+fn presence_ident(field: &StructField<'_>) -> syn::Ident {
+    syn::Ident::new(&format!("__{}_present", field.name.unraw()), Span::call_site())
+}
+
+/// Declare a local for each switch recording whether it was provided, for
+/// those switches which track it.
+fn declare_presence_for_switches<'a>(
+    fields: &'a [StructField<'a>],
+) -> impl Iterator<Item = TokenStream> + 'a {
+    fields
+        .iter()
+        .filter(move |field| field.kind == FieldKind::Switch && tracks_presence(field, fields))
+        .map(|field| {
+            let present = presence_ident(field);
+            quote! { let mut #present = false; }
+        })
+}
+
+/// For each `conflicts_with`, `requires` and `group` constraint, add any
+/// violations to the `argh::MissingRequirements`.
+fn append_constraint_checks(
+    errors: &Errors,
+    // missing_requirements_ident
+    mri: &syn::Ident,
+    type_attrs: &TypeAttrs,
+    fields: &[StructField<'_>],
+) -> Vec<TokenStream> {
+    // The name used to refer to a field in error messages.
+    fn display_name(field: &StructField<'_>) -> String {
+        match &field.long_name {
+            Some(long_name) => long_name.clone(),
+            None => field.positional_arg_name(),
+        }
+    }
+
+    // An expression for whether a field was provided.
+    fn presence(field: &StructField<'_>) -> TokenStream {
+        let field_name = field.name;
+        match field.kind {
+            FieldKind::Switch => presence_ident(field).into_token_stream(),
+            _ => quote! { argh::ParseValueSlot::is_filled(&#field_name) },
+        }
+    }
+
+    let find_field = |attr_name: &str, field: &StructField<'_>, reference: &LitStr| {
+        let found = fields.iter().find(|other| other.name.unraw() == reference.value());
+        match found {
+            Some(other) if other.kind == FieldKind::SubCommand => {
+                errors.err(
+                    reference,
+                    &format!("`{}` may not refer to the `#[argh(subcommand)]` field", attr_name),
+                );
+                None
+            }
+            Some(other) if other.name == field.name => {
+                errors.err(
+                    reference,
+                    &format!("`{}` cannot refer to the field it is specified on", attr_name),
+                );
+                None
+            }
+            Some(other) => Some(other),
+            None => {
+                errors.err(
+                    reference,
+                    &format!("`{}` refers to unknown field `{}`", attr_name, reference.value()),
+                );
+                None
+            }
+        }
+    };
+
+    let mut checks = vec![];
+
+    // Each pair of conflicting fields is only checked once, even if both fields
+    // declare the conflict.
+    let mut conflicts: Vec<(&syn::Ident, &syn::Ident)> = vec![];
+    for field in fields {
+        for reference in &field.attrs.conflicts_with {
+            let Some(other) = find_field("conflicts_with", field, reference) else {
+                continue;
+            };
+            if conflicts.contains(&(other.name, field.name)) {
+                continue;
+            }
+            conflicts.push((field.name, other.name));
+
+            let (name, other_name) = (display_name(field), display_name(other));
+            let (present, other_present) = (presence(field), presence(other));
+            checks.push(quote! {
+                if #present && #other_present {
+                    #mri.conflicting_args(#name, #other_name);
+                }
+            });
+        }
+
+        for reference in &field.attrs.requires {
+            let Some(other) = find_field("requires", field, reference) else {
+                continue;
+            };
+            let (name, other_name) = (display_name(field), display_name(other));
+            let (present, other_present) = (presence(field), presence(other));
+            checks.push(quote! {
+                if #present && !#other_present {
+                    #mri.missing_required_by(#other_name, #name);
+                }
+            });
+        }
+
+        if let Some(group) = &field.attrs.group {
+            if !type_attrs.groups.iter().any(|g| g.name.value() == group.value()) {
+                errors.err(
+                    group,
+                    &format!(
+                        "Unknown group `{0}`. Declare it on the struct with \
+                         `#[argh(group(name = \"{0}\"))]`",
+                        group.value()
+                    ),
+                );
+            }
+        }
+    }
+
+    for group in &type_attrs.groups {
+        let members: Vec<_> = fields
+            .iter()
+            .filter(|field| {
+                field.attrs.group.as_ref().is_some_and(|g| g.value() == group.name.value())
+            })
+            .collect();
+        if members.is_empty() {
+            errors.err(
+                &group.name,
+                "Groups must have at least one field. Add fields to the group with \
+                 `#[argh(group = \"...\")]`",
+            );
+            continue;
+        }
+        let names = members.iter().map(|field| display_name(field));
+        let present = members.iter().map(|field| presence(field));
+        let (required, multiple) = (group.required, group.multiple);
+        checks.push(quote! {
+            #mri.check_group(&[ #( (#names, #present) ),* ], #required, #multiple);
+        });
+    }
+
+    checks
+}
+
 /// Require that a type can be a `switch`.
 /// Throws an error for all types except booleans and integers
 fn ty_expect_switch(errors: &Errors, ty: &syn::Type) -> bool {
@@ -1036,6 +1896,35 @@ fn ty_inner<'a>(wrapper_names: &[&str], ty: &'a syn::Type) -> Option<&'a syn::Ty
     None
 }
 
+/// Returns true if a type should be parsed with `FromArgOsValue`, i.e. it is
+/// `OsString` or `PathBuf`.
+///
+/// As with `ty_inner`, only the last path segment is checked.
+fn ty_is_os_value(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(path) = ty {
+        if path.qself.is_none() {
+            if let Some(last_segment) = path.path.segments.last() {
+                return last_segment.arguments.is_empty()
+                    && (last_segment.ident == "OsString" || last_segment.ident == "PathBuf");
+            }
+        }
+    }
+    false
+}
+
+/// Returns an expression for the name of the subcommand parsed when none is
+/// given: the `default` of the `#[argh(subcommand)]` field if it has one, or
+/// else that of the subcommands.
+fn default_subcommand(subcommand: &StructField<'_>) -> TokenStream {
+    match &subcommand.attrs.default {
+        Some(default) => quote! { Some(#default) },
+        None => {
+            let ty = subcommand.ty_without_wrapper;
+            quote! { <#ty as argh::SubCommands>::DEFAULT }
+        }
+    }
+}
+
 /// Implements `FromArgs` and `SubCommands` for a `#![derive(FromArgs)]` enum.
 fn impl_from_args_enum(
     errors: &Errors,
@@ -1053,6 +1942,7 @@ fn impl_from_args_enum(
     }
 
     let mut dynamic_type_and_variant = None;
+    let mut default_type = None;
 
     let variants: Vec<SubCommandVariant<'_>> = de
         .variants
@@ -1060,7 +1950,14 @@ fn impl_from_args_enum(
         .filter_map(|variant| {
             let name = &variant.ident;
             let ty = enum_only_single_field_unnamed_variants(errors, &variant.fields)?;
-            if parse_attrs::VariantAttrs::parse(errors, variant).is_dynamic.is_some() {
+            let attrs = parse_attrs::VariantAttrs::parse(errors, variant);
+            if attrs.is_default.is_some() && attrs.is_dynamic.is_none() {
+                if default_type.is_some() {
+                    errors.err(variant, "Only one variant can have the `default` attribute");
+                }
+                default_type = Some(ty);
+            }
+            if attrs.is_dynamic.is_some() {
                 if dynamic_type_and_variant.is_some() {
                     errors.err(variant, "Only one variant can have the `dynamic` attribute");
                 }
@@ -1078,9 +1975,10 @@ fn impl_from_args_enum(
     let dynamic_from_args =
         dynamic_type_and_variant.as_ref().map(|(dynamic_type, dynamic_variant)| {
             quote! {
-                if let Some(result) = <#dynamic_type as argh::DynamicSubCommand>::try_from_args(
-                    command_name, args) {
-                    return result.map(#name::#dynamic_variant);
+                if let Some(result) =
+                    <#dynamic_type as argh::DynamicSubCommand>::try_from_args_os(command_name, args)
+                {
+                    return result.map(#name::#dynamic_variant).map_err(argh::ParseError::from);
                 }
             }
         });
@@ -1088,7 +1986,7 @@ fn impl_from_args_enum(
         quote! {
             if let Some(result) = <#dynamic_type as argh::DynamicSubCommand>::try_redact_arg_values(
                 command_name, args) {
-                return result;
+                return result.map_err(argh::ParseError::from);
             }
         }
     });
@@ -1099,6 +1997,21 @@ fn impl_from_args_enum(
             }
         }
     });
+    let default_command = default_type.map(|ty| {
+        quote! {
+            const DEFAULT: Option<&'static str> = Some(<#ty as argh::SubCommand>::COMMAND.name);
+        }
+    });
+
+    // Have `cargo check` report names used by more than one subcommand, unless
+    // the enum is generic.
+    let check_names = if generic_args.params.is_empty() {
+        quote! {
+            const _: () = argh::check_command_names(<#name as argh::SubCommands>::COMMANDS);
+        }
+    } else {
+        TokenStream::new()
+    };
 
     let (impl_generics, ty_generics, where_clause) = generic_args.split_for_impl();
     quote! {
@@ -1106,49 +2019,78 @@ fn impl_from_args_enum(
             fn from_args(command_name: &[&str], args: &[&str])
                 -> std::result::Result<Self, argh::EarlyExit>
             {
+                Self::from_args_os(&argh::str_args_to_os(command_name), &argh::str_args_to_os(args))
+            }
+
+            fn from_args_os(command_name: &[&::std::ffi::OsStr], args: &[&::std::ffi::OsStr])
+                -> std::result::Result<Self, argh::EarlyExit>
+            {
+                Self::from_args_with_env(command_name, args, &argh::ProcessEnvironment)
+            }
+
+            fn from_args_with_env(
+                command_name: &[&::std::ffi::OsStr],
+                args: &[&::std::ffi::OsStr],
+                __env: &dyn argh::Environment,
+            ) -> std::result::Result<Self, argh::EarlyExit> {
+                Self::from_args_with_context(command_name, args, &argh::ParseContext::new(__env))
+                    .map_err(argh::EarlyExit::from)
+            }
+
+            fn from_args_with_context(
+                command_name: &[&::std::ffi::OsStr],
+                args: &[&::std::ffi::OsStr],
+                __context: &argh::ParseContext<'_>,
+            ) -> std::result::Result<Self, argh::ParseError> {
                 let subcommand_name = if let Some(subcommand_name) = command_name.last() {
-                    *subcommand_name
+                    subcommand_name.to_string_lossy()
                 } else {
-                    return ::core::result::Result::Err(argh::EarlyExit::from("no subcommand name".to_owned()));
+                    return ::core::result::Result::Err(argh::ParseError::Message("no subcommand name".to_owned()));
                 };
 
                 #(
-                    if subcommand_name == <#variant_ty as argh::SubCommand>::COMMAND.name
-                        || (*<#variant_ty as argh::SubCommand>::COMMAND.short != '\0'
-                            && subcommand_name.len() == 1
-                            && subcommand_name.starts_with(*<#variant_ty as argh::SubCommand>::COMMAND.short))
-                    {
+                    if <#variant_ty as argh::SubCommand>::COMMAND.is_named(&subcommand_name) {
                         return ::core::result::Result::Ok(#name_repeating::#variant_names(
-                            <#variant_ty as argh::FromArgs>::from_args(command_name, args)?
+                            <#variant_ty as argh::FromArgs>::from_args_with_context(command_name, args, __context)?
                         ));
                     }
                 )*
 
                 #dynamic_from_args
 
-                ::core::result::Result::Err(argh::EarlyExit::from("no subcommand matched".to_owned()))
+                ::core::result::Result::Err(argh::ParseError::Message("no subcommand matched".to_owned()))
             }
 
             fn redact_arg_values(command_name: &[&str], args: &[&str]) -> std::result::Result<Vec<String>, argh::EarlyExit> {
+                Self::redact_arg_values_with_globals(command_name, args, None)
+                    .map_err(argh::EarlyExit::from)
+            }
+
+            #[allow(unused_variables)]
+            fn redact_arg_values_with_globals(
+                command_name: &[&str],
+                args: &[&str],
+                global_args: Option<&argh::GlobalArgs<'_>>,
+            ) -> std::result::Result<Vec<String>, argh::ParseError> {
                 let subcommand_name = if let Some(subcommand_name) = command_name.last() {
                     *subcommand_name
                 } else {
-                    return ::core::result::Result::Err(argh::EarlyExit::from("no subcommand name".to_owned()));
+                    return ::core::result::Result::Err(argh::ParseError::Message("no subcommand name".to_owned()));
                 };
 
                 #(
-                    if subcommand_name == <#variant_ty as argh::SubCommand>::COMMAND.name
-                        || (*<#variant_ty as argh::SubCommand>::COMMAND.short != '\0'
-                            && subcommand_name.len() == 1
-                            && subcommand_name.starts_with(*<#variant_ty as argh::SubCommand>::COMMAND.short))
-                    {
-                        return <#variant_ty as argh::FromArgs>::redact_arg_values(command_name, args);
+                    if <#variant_ty as argh::SubCommand>::COMMAND.is_named(&subcommand_name) {
+                        return <#variant_ty as argh::FromArgs>::redact_arg_values_with_globals(
+                            command_name,
+                            args,
+                            global_args,
+                        );
                     }
                 )*
 
                 #dynamic_redact_arg_values
 
-                ::core::result::Result::Err(argh::EarlyExit::from("no subcommand matched".to_owned()))
+                ::core::result::Result::Err(argh::ParseError::Message("no subcommand matched".to_owned()))
             }
         }
 
@@ -1157,8 +2099,16 @@ fn impl_from_args_enum(
                 <#variant_ty as argh::SubCommand>::COMMAND,
             )*];
 
+            const FLAGS: &'static [&'static argh::FlattenedFlags] = &[#(
+                <#variant_ty as argh::SubCommand>::FLAGS,
+            )*];
+
             #dynamic_commands
+
+            #default_command
         }
+
+        #check_names
     }
 }
 
@@ -1229,6 +2179,7 @@ fn impl_from_arg_value_enum(
     struct ChoiceVariant<'a> {
         ident: &'a syn::Ident,
         name: syn::LitStr,
+        deprecated: Option<syn::LitStr>,
     }
 
     let variants: Vec<ChoiceVariant<'_>> = de
@@ -1245,7 +2196,7 @@ fn impl_from_arg_value_enum(
                     syn::LitStr::new(&name_str, ident.span())
                 }
             };
-            ChoiceVariant { ident, name }
+            ChoiceVariant { ident, name, deprecated: attrs.deprecated }
         })
         .collect();
 
@@ -1256,6 +2207,12 @@ fn impl_from_arg_value_enum(
     let name_repeating = std::iter::repeat(name.clone());
     let variant_idents = variants.iter().map(|x| x.ident);
     let variant_names = variants.iter().map(|x| &x.name).collect::<Vec<_>>();
+    let warn_deprecated = variants.iter().map(|x| {
+        x.deprecated.as_ref().map(|note| {
+            let warning = deprecation_warning("Value", &x.name.value(), note);
+            quote! { argh::warn(#warning); }
+        })
+    });
     let err_literal = {
         let mut err = "expected ".to_string();
         for (i, name) in variant_names.iter().enumerate() {
@@ -1277,10 +2234,19 @@ fn impl_from_arg_value_enum(
             {
                 ::core::result::Result::Ok(match value {
                     #(
-                        #variant_names => #name_repeating::#variant_idents,
+                        #variant_names => {
+                            #warn_deprecated
+                            #name_repeating::#variant_idents
+                        }
                     )*
                     _ => {
-                        return ::core::result::Result::Err(#err_literal.to_owned())
+                        let mut err = #err_literal.to_owned();
+                        if let Some(suggestion) = argh::suggest(value, [#( #variant_names ),*]) {
+                            err.push_str(". Did you mean ");
+                            err.push_str(&format!("{:?}", suggestion));
+                            err.push('?');
+                        }
+                        return ::core::result::Result::Err(err)
                     }
                 })
             }
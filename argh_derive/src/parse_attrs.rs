@@ -13,14 +13,26 @@ use {
 /// Attributes applied to a field of a `#![derive(FromArgs)]` struct.
 #[derive(Default)]
 pub struct FieldAttrs {
+    pub aliases: Vec<syn::LitStr>,
+    pub visible_aliases: Vec<syn::LitStr>,
+    pub conflicts_with: Vec<syn::LitStr>,
     pub default: Option<syn::LitStr>,
+    pub default_missing_value: Option<syn::LitStr>,
+    pub delimiter: Option<syn::LitChar>,
+    pub deprecated: Option<syn::LitStr>,
     pub description: Option<Description>,
+    pub env: Option<syn::LitStr>,
     pub from_str_fn: Option<syn::ExprPath>,
     pub field_type: Option<FieldType>,
+    pub global: Option<syn::Path>,
     pub long: Option<syn::LitStr>,
     pub short: Option<syn::LitChar>,
     pub arg_name: Option<syn::LitStr>,
+    pub group: Option<syn::LitStr>,
+    pub requires: Vec<syn::LitStr>,
     pub greedy: Option<syn::Path>,
+    pub with_values: Option<syn::Path>,
+    pub negatable: Option<syn::Path>,
     pub hidden_help: bool,
     pub usage: bool,
 }
@@ -42,6 +54,16 @@ pub enum FieldKind {
     /// They are parsed in declaration order, and only the last positional
     /// argument in a type may be an `Option`, `Vec`, or have a default value.
     Positional,
+    /// Flattened fields embed the options and switches of another
+    /// `#![derive(FromArgs)]` struct, which may not have positional arguments
+    /// or a subcommand.
+    Flatten,
+    /// Unknown fields collect the options which the command does not
+    /// recognize, in order. They must be a `Vec`.
+    Unknown,
+    /// Trailing fields collect the arguments after the first `--`, apart from
+    /// the positional arguments. They must be a `Vec`.
+    Trailing,
 }
 
 /// The type of a field on a `#![derive(FromArgs)]` struct.
@@ -81,28 +103,71 @@ impl FieldAttrs {
 
             for meta in ml {
                 let name = meta.path();
-                if name.is_ident("arg_name") {
+                if name.is_ident("alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_alias(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("visible_alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_alias(errors, m, &mut this.visible_aliases);
+                    }
+                } else if name.is_ident("arg_name") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_arg_name(errors, m);
                     }
+                } else if name.is_ident("deprecated") {
+                    parse_attr_deprecated(errors, &meta, &mut this.deprecated);
+                } else if name.is_ident("conflicts_with") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.conflicts_with);
+                    }
                 } else if name.is_ident("default") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_default(errors, m);
                     }
+                } else if name.is_ident("default_missing_value") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(
+                            errors,
+                            m,
+                            "default_missing_value",
+                            &mut this.default_missing_value,
+                        );
+                    }
+                } else if name.is_ident("delimiter") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_delimiter(errors, m);
+                    }
                 } else if name.is_ident("description") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_description(errors, m, &mut this.description);
                     }
+                } else if name.is_ident("env") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_env(errors, m);
+                    }
+                } else if name.is_ident("flatten") {
+                    parse_attr_field_type(errors, &meta, FieldKind::Flatten, &mut this.field_type);
                 } else if name.is_ident("from_str_fn") {
                     if let Some(m) = errors.expect_meta_list(&meta) {
                         this.parse_attr_from_str_fn(errors, m);
                     }
+                } else if name.is_ident("global") {
+                    this.global = Some(name.clone());
+                } else if name.is_ident("group") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_single_string(errors, m, "group", &mut this.group);
+                    }
                 } else if name.is_ident("long") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_long(errors, m);
                     }
                 } else if name.is_ident("option") {
                     parse_attr_field_type(errors, &meta, FieldKind::Option, &mut this.field_type);
+                } else if name.is_ident("requires") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_multi_string(errors, m, &mut this.requires);
+                    }
                 } else if name.is_ident("short") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_short(errors, m);
@@ -125,6 +190,14 @@ impl FieldAttrs {
                     );
                 } else if name.is_ident("greedy") {
                     this.greedy = Some(name.clone());
+                } else if name.is_ident("trailing") {
+                    parse_attr_field_type(errors, &meta, FieldKind::Trailing, &mut this.field_type);
+                } else if name.is_ident("unknown") {
+                    parse_attr_field_type(errors, &meta, FieldKind::Unknown, &mut this.field_type);
+                } else if name.is_ident("with_values") {
+                    this.with_values = Some(name.clone());
+                } else if name.is_ident("negatable") {
+                    this.negatable = Some(name.clone());
                 } else if name.is_ident("hidden_help") {
                     this.hidden_help = true;
                 } else if name.is_ident("usage") {
@@ -134,8 +207,11 @@ impl FieldAttrs {
                         &meta,
                         concat!(
                             "Invalid field-level `argh` attribute\n",
-                            "Expected one of: `arg_name`, `default`, `description`, `from_str_fn`, `greedy`, ",
-                            "`long`, `option`, `short`, `subcommand`, `switch`, `hidden_help`, `usage`",
+                            "Expected one of: `alias`, `arg_name`, `conflicts_with`, `default`, `default_missing_value`, ",
+                            "`delimiter`, `deprecated`, `description`, `env`, `flatten`, ",
+                            "`from_str_fn`, `global`, `greedy`, `group`, `long`, `negatable`, `option`, `requires`, ",
+                            "`short`, `subcommand`, `switch`, `trailing`, `unknown`, `visible_alias`, ",
+                            "`with_values`, `hidden_help`, `usage`",
                         ),
                     );
                 }
@@ -144,15 +220,121 @@ impl FieldAttrs {
 
         if let (Some(default), Some(field_type)) = (&this.default, &this.field_type) {
             match field_type.kind {
-                FieldKind::Option | FieldKind::Positional => {}
-                FieldKind::SubCommand | FieldKind::Switch => errors.err(
+                FieldKind::Option | FieldKind::Positional | FieldKind::SubCommand => {}
+                FieldKind::Switch if this.negatable.is_some() => {}
+                FieldKind::Switch
+                | FieldKind::Flatten
+                | FieldKind::Unknown
+                | FieldKind::Trailing => errors.err(
                     default,
-                    "`default` may only be specified on `#[argh(option)]` \
-                     or `#[argh(positional)]` fields",
+                    "`default` may only be specified on `#[argh(option)]`, \
+                     `#[argh(positional)]`, `#[argh(subcommand)]`, or `negatable` \
+                     `#[argh(switch)]` fields",
                 ),
             }
         }
 
+        match (&this.negatable, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Switch)) => {}
+            (Some(negatable), Some(_)) => errors.err(
+                &negatable,
+                "`negatable` may only be specified on `#[argh(switch)]` \
+                    fields",
+            ),
+            _ => {}
+        }
+
+        match (&this.env, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option)) => {}
+            (Some(env), Some(_)) => {
+                errors.err(env, "`env` may only be specified on `#[argh(option)]` fields")
+            }
+            _ => {}
+        }
+
+        if let Some(
+            FieldKind::SubCommand
+            | FieldKind::Positional
+            | FieldKind::Flatten
+            | FieldKind::Unknown
+            | FieldKind::Trailing,
+        ) = this.field_type.as_ref().map(|f| f.kind)
+        {
+            if let Some(alias) = this.aliases.first().or(this.visible_aliases.first()) {
+                errors.err(
+                    alias,
+                    "Aliases may only be specified on `#[argh(option)]` or `#[argh(switch)]` \
+                     fields, or on `#[argh(subcommand)]` types",
+                );
+            }
+        }
+
+        if let (Some(deprecated), Some(FieldKind::SubCommand)) =
+            (&this.deprecated, this.field_type.as_ref().map(|f| f.kind))
+        {
+            errors.err(
+                deprecated,
+                "`deprecated` may not be specified on the `#[argh(subcommand)]` field. \
+                 Deprecate the subcommand type instead",
+            );
+        }
+
+        match (&this.default_missing_value, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option)) => {}
+            (Some(default_missing_value), Some(_)) => errors.err(
+                default_missing_value,
+                "`default_missing_value` may only be specified on `#[argh(option)]` fields",
+            ),
+            _ => {}
+        }
+
+        if let (Some(default_missing_value), Some(delimiter)) =
+            (&this.default_missing_value, &this.delimiter)
+        {
+            errors.err(delimiter, "`delimiter` cannot be combined with `default_missing_value`");
+            errors.err(default_missing_value, "`default_missing_value` specified here");
+        }
+
+        match (&this.delimiter, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option)) => {}
+            (Some(delimiter), Some(_)) => errors
+                .err(delimiter, "`delimiter` may only be specified on `#[argh(option)]` fields"),
+            _ => {}
+        }
+
+        match (&this.group, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option | FieldKind::Switch)) => {}
+            (Some(group), Some(_)) => errors.err(
+                group,
+                "`group` may only be specified on `#[argh(option)]` or `#[argh(switch)]` fields",
+            ),
+            _ => {}
+        }
+
+        if let Some(FieldKind::SubCommand) = this.field_type.as_ref().map(|f| f.kind) {
+            if let Some(conflicts_with) = this.conflicts_with.first() {
+                errors.err(
+                    conflicts_with,
+                    "`conflicts_with` may not be specified on `#[argh(subcommand)]` fields",
+                );
+            }
+            if let Some(requires) = this.requires.first() {
+                errors.err(
+                    requires,
+                    "`requires` may not be specified on `#[argh(subcommand)]` fields",
+                );
+            }
+        }
+
+        match (&this.global, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Option | FieldKind::Switch)) => {}
+            (Some(global), Some(_)) => errors.err(
+                global,
+                "`global` may only be specified on `#[argh(option)]` or `#[argh(switch)]` fields",
+            ),
+            _ => {}
+        }
+
         match (&this.greedy, this.field_type.as_ref().map(|f| f.kind)) {
             (Some(_), Some(FieldKind::Positional)) => {}
             (Some(greedy), Some(_)) => errors.err(
@@ -163,6 +345,58 @@ impl FieldAttrs {
             _ => {}
         }
 
+        match (&this.with_values, this.field_type.as_ref().map(|f| f.kind)) {
+            (Some(_), Some(FieldKind::Unknown)) => {}
+            (Some(with_values), Some(_)) => errors.err(
+                with_values,
+                "`with_values` may only be specified on `#[argh(unknown)]` fields",
+            ),
+            _ => {}
+        }
+
+        if let Some(FieldKind::Unknown) = this.field_type.as_ref().map(|f| f.kind) {
+            let message = "`#[argh(unknown)]` fields do not have names of their own, \
+                           and are not listed in help";
+            if let Some(short) = &this.short {
+                errors.err(short, message);
+            }
+            if let Some(long) = &this.long {
+                errors.err(long, message);
+            }
+            if let Some(deprecated) = &this.deprecated {
+                errors.err(deprecated, message);
+            }
+            if this.hidden_help || this.usage {
+                errors.err(field, message);
+            }
+        }
+
+        if let Some(FieldKind::Flatten) = this.field_type.as_ref().map(|f| f.kind) {
+            let message = "`#[argh(flatten)]` fields only take a description. \
+                           Add attributes to the fields of the flattened struct instead";
+            if let Some(short) = &this.short {
+                errors.err(short, message);
+            }
+            if let Some(long) = &this.long {
+                errors.err(long, message);
+            }
+            if let Some(arg_name) = &this.arg_name {
+                errors.err(arg_name, message);
+            }
+            if let Some(from_str_fn) = &this.from_str_fn {
+                errors.err(from_str_fn, message);
+            }
+            if let Some(deprecated) = &this.deprecated {
+                errors.err(deprecated, message);
+            }
+            if let Some(constraint) = this.requires.first().or(this.conflicts_with.first()) {
+                errors.err(constraint, message);
+            }
+            if this.hidden_help || this.usage {
+                errors.err(field, message);
+            }
+        }
+
         if let Some(d) = &this.description {
             check_option_description(errors, d.content.value().trim(), d.content.span());
         }
@@ -178,6 +412,25 @@ impl FieldAttrs {
         parse_attr_single_string(errors, m, "default", &mut self.default);
     }
 
+    fn parse_attr_delimiter(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        if let Some(first) = &self.delimiter {
+            errors.duplicate_attrs("delimiter", first, m);
+        } else if let Some(lit_char) = errors.expect_lit_char(&m.value) {
+            self.delimiter = Some(lit_char.clone());
+            if !lit_char.value().is_ascii() || lit_char.value() == '\\' {
+                errors.err(lit_char, "Delimiters must be ASCII and not a backslash");
+            }
+        }
+    }
+
+    fn parse_attr_env(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        parse_attr_single_string(errors, m, "env", &mut self.env);
+        let env = self.env.as_ref().unwrap();
+        if env.value().is_empty() || env.value().contains(['=', '\0']) {
+            errors.err(env, "Environment variable names must be non-empty and not contain `=`");
+        }
+    }
+
     fn parse_attr_arg_name(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
         parse_attr_single_string(errors, m, "arg_name", &mut self.arg_name);
     }
@@ -201,6 +454,56 @@ impl FieldAttrs {
     }
 }
 
+/// Parses an `alias` or `visible_alias` attribute. A single character is a
+/// short alias, and anything longer is a long alias.
+fn parse_attr_alias(errors: &Errors, m: &syn::MetaNameValue, list: &mut Vec<syn::LitStr>) {
+    if let Some(lit_str) = errors.expect_lit_str(&m.value) {
+        let value = lit_str.value();
+        match value.chars().count() {
+            0 => errors.err(lit_str, "Aliases must not be empty"),
+            1 if !value.is_ascii() => errors.err(lit_str, "Short names must be ASCII"),
+            1 => {}
+            _ if value == "help" => errors.err(lit_str, "Custom `--help` flags are not supported."),
+            _ => check_long_name(errors, lit_str, &value),
+        }
+        list.push(lit_str.clone());
+    }
+}
+
+/// Parses the `alias` or `visible_alias` attribute of a subcommand, which is
+/// checked as its `name` is.
+fn parse_attr_command_alias(errors: &Errors, m: &syn::MetaNameValue, list: &mut Vec<syn::LitStr>) {
+    if let Some(lit_str) = errors.expect_lit_str(&m.value) {
+        match lit_str.value().as_str() {
+            "" => errors.err(lit_str, "Aliases must not be empty"),
+            "help" => errors.err(lit_str, "Custom `help` commands are not supported."),
+            _ => {}
+        }
+        list.push(lit_str.clone());
+    }
+}
+
+/// Parses a `deprecated` or `deprecated = "..."` attribute. A bare `deprecated`
+/// is recorded with an empty note.
+fn parse_attr_deprecated(errors: &Errors, meta: &syn::Meta, slot: &mut Option<syn::LitStr>) {
+    let note = match meta {
+        syn::Meta::Path(path) => syn::LitStr::new("", syn::spanned::Spanned::span(path)),
+        syn::Meta::NameValue(m) => match errors.expect_lit_str(&m.value) {
+            Some(lit_str) => lit_str.clone(),
+            None => return,
+        },
+        syn::Meta::List(_) => {
+            errors.err(meta, "Expected `deprecated` or `deprecated = \"...\"`");
+            return;
+        }
+    };
+    if let Some(first) = slot {
+        errors.duplicate_attrs("deprecated", first, meta);
+    } else {
+        *slot = Some(note);
+    }
+}
+
 pub(crate) fn check_long_name(errors: &Errors, spanned: &impl syn::spanned::Spanned, value: &str) {
     if !value.is_ascii() {
         errors.err(spanned, "Long names must be ASCII");
@@ -285,6 +588,25 @@ pub struct TypeAttrs {
     /// Arguments that trigger printing of the help message
     pub help_triggers: Option<Vec<syn::LitStr>>,
     pub usage: Option<syn::LitStr>,
+    pub env_prefix: Option<syn::LitStr>,
+    /// Expand `@path` arguments into the contents of the file at `path`.
+    pub response_files: Option<syn::Ident>,
+    /// Report all errors together rather than only the first one.
+    pub collect_errors: Option<syn::Ident>,
+    pub groups: Vec<GroupAttrs>,
+    pub aliases: Vec<syn::LitStr>,
+    pub visible_aliases: Vec<syn::LitStr>,
+    pub deprecated: Option<syn::LitStr>,
+}
+
+/// A `#[argh(group(name = "...", required, multiple = false))]` attribute,
+/// which fields join with `#[argh(group = "...")]`.
+pub struct GroupAttrs {
+    pub name: syn::LitStr,
+    /// At least one of the group's fields must be provided.
+    pub required: bool,
+    /// More than one of the group's fields may be provided.
+    pub multiple: bool,
 }
 
 impl TypeAttrs {
@@ -306,10 +628,29 @@ impl TypeAttrs {
 
             for meta in ml {
                 let name = meta.path();
-                if name.is_ident("description") {
+                if name.is_ident("alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_command_alias(errors, m, &mut this.aliases);
+                    }
+                } else if name.is_ident("visible_alias") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        parse_attr_command_alias(errors, m, &mut this.visible_aliases);
+                    }
+                } else if name.is_ident("collect_errors") {
+                    if let Some(ident) = errors.expect_meta_word(&meta).and_then(|p| p.get_ident())
+                    {
+                        this.parse_attr_collect_errors(errors, ident);
+                    }
+                } else if name.is_ident("deprecated") {
+                    parse_attr_deprecated(errors, &meta, &mut this.deprecated);
+                } else if name.is_ident("description") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_description(errors, m, &mut this.description);
                     }
+                } else if name.is_ident("env_prefix") {
+                    if let Some(m) = errors.expect_meta_name_value(&meta) {
+                        this.parse_attr_env_prefix(errors, m);
+                    }
                 } else if name.is_ident("error_code") {
                     if let Some(m) = errors.expect_meta_list(&meta) {
                         this.parse_attr_error_code(errors, m);
@@ -318,6 +659,10 @@ impl TypeAttrs {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_example(errors, m);
                     }
+                } else if name.is_ident("group") {
+                    if let Some(m) = errors.expect_meta_list(&meta) {
+                        this.parse_attr_group(errors, m);
+                    }
                 } else if name.is_ident("name") {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         this.parse_attr_name(errors, m);
@@ -335,6 +680,11 @@ impl TypeAttrs {
                     {
                         this.parse_attr_subcommand(errors, ident);
                     }
+                } else if name.is_ident("response_files") {
+                    if let Some(ident) = errors.expect_meta_word(&meta).and_then(|p| p.get_ident())
+                    {
+                        this.parse_attr_response_files(errors, ident);
+                    }
                 } else if name.is_ident("help_triggers") {
                     if let Some(m) = errors.expect_meta_list(&meta) {
                         Self::parse_help_triggers(m, errors, &mut this);
@@ -348,14 +698,40 @@ impl TypeAttrs {
                         &meta,
                         concat!(
                             "Invalid type-level `argh` attribute\n",
-                            "Expected one of: `description`, `error_code`, `example`, `name`, ",
-                            "`note`, `short`, `subcommand`, `usage`",
+                            "Expected one of: `alias`, `collect_errors`, `deprecated`, `description`, ",
+                            "`env_prefix`, `error_code`, ",
+                            "`example`, `group`, `name`, `note`, `response_files`, `short`, `subcommand`, ",
+                            "`usage`, `visible_alias`",
                         ),
                     );
                 }
             }
         }
 
+        if this.is_subcommand.is_some() {
+            if let Some(collect_errors) = &this.collect_errors {
+                errors.err(
+                    collect_errors,
+                    concat!(
+                        "`collect_errors` may only be specified on top-level commands. ",
+                        "Subcommands collect their errors when their parent command does.",
+                    ),
+                );
+            }
+        }
+
+        if this.is_subcommand.is_none() {
+            if let Some(alias) = this.aliases.first().or(this.visible_aliases.first()) {
+                errors.err(alias, "Aliases may only be specified on `#[argh(subcommand)]` types");
+            }
+            if let Some(deprecated) = &this.deprecated {
+                errors.err(
+                    deprecated,
+                    "`deprecated` may only be specified on `#[argh(subcommand)]` types",
+                );
+            }
+        }
+
         this.check_error_codes(errors);
         this
     }
@@ -407,6 +783,65 @@ impl TypeAttrs {
         }));
     }
 
+    fn parse_attr_env_prefix(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
+        parse_attr_single_string(errors, m, "env_prefix", &mut self.env_prefix);
+        let env_prefix = self.env_prefix.as_ref().unwrap();
+        if env_prefix.value().contains(['=', '\0']) {
+            errors.err(env_prefix, "Environment variable prefixes must not contain `=`");
+        }
+    }
+
+    fn parse_attr_group(&mut self, errors: &Errors, ml: &syn::MetaList) {
+        let metas = match errors
+            .ok(ml.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated))
+        {
+            Some(metas) => metas,
+            None => return,
+        };
+
+        let mut name = None;
+        let mut required = false;
+        let mut multiple = true;
+        for meta in metas {
+            if meta.path().is_ident("name") {
+                if let Some(m) = errors.expect_meta_name_value(&meta) {
+                    parse_attr_single_string(errors, m, "name", &mut name);
+                }
+            } else if meta.path().is_ident("required") {
+                if errors.expect_meta_word(&meta).is_some() {
+                    required = true;
+                }
+            } else if meta.path().is_ident("multiple") {
+                if let Some(m) = errors.expect_meta_name_value(&meta) {
+                    if let Some(lit_bool) = errors.expect_lit_bool(&m.value) {
+                        multiple = lit_bool.value;
+                    }
+                }
+            } else {
+                errors.err(
+                    &meta,
+                    concat!(
+                        "Invalid `group` attribute\n",
+                        "Expected one of: `name`, `required`, `multiple`",
+                    ),
+                );
+            }
+        }
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                errors.err(ml, "`group` attributes must have a `name`");
+                return;
+            }
+        };
+        if let Some(first) = self.groups.iter().find(|group| group.name.value() == name.value()) {
+            errors.duplicate_attrs("group", &first.name, &name);
+            return;
+        }
+        self.groups.push(GroupAttrs { name, required, multiple });
+    }
+
     fn parse_attr_example(&mut self, errors: &Errors, m: &syn::MetaNameValue) {
         parse_attr_multi_string(errors, m, &mut self.examples)
     }
@@ -443,6 +878,22 @@ impl TypeAttrs {
         }
     }
 
+    fn parse_attr_response_files(&mut self, errors: &Errors, ident: &syn::Ident) {
+        if let Some(first) = &self.response_files {
+            errors.duplicate_attrs("response_files", first, ident);
+        } else {
+            self.response_files = Some(ident.clone());
+        }
+    }
+
+    fn parse_attr_collect_errors(&mut self, errors: &Errors, ident: &syn::Ident) {
+        if let Some(first) = &self.collect_errors {
+            errors.duplicate_attrs("collect_errors", first, ident);
+        } else {
+            self.collect_errors = Some(ident.clone());
+        }
+    }
+
     // get the list of arguments that trigger printing of the help message as a vector of strings (help_arguments("-h", "--help", "help"))
     fn parse_help_triggers(m: &syn::MetaList, errors: &Errors, this: &mut TypeAttrs) {
         let parser = Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
@@ -470,6 +921,7 @@ impl TypeAttrs {
 #[derive(Default)]
 pub struct VariantAttrs {
     pub is_dynamic: Option<syn::Path>,
+    pub is_default: Option<syn::Path>,
 }
 
 impl VariantAttrs {
@@ -506,16 +958,27 @@ impl VariantAttrs {
                     } else {
                         this.is_dynamic = errors.expect_meta_word(&meta).cloned();
                     }
+                } else if name.is_ident("default") {
+                    if let Some(prev) = this.is_default.as_ref() {
+                        errors.duplicate_attrs("default", prev, &meta);
+                    } else {
+                        this.is_default = errors.expect_meta_word(&meta).cloned();
+                    }
                 } else {
                     errors.err(
                         &meta,
                         "Invalid variant-level `argh` attribute\n\
-                         Subcommand variants can only have the #[argh(dynamic)] attribute.",
+                         Subcommand variants can only have the #[argh(dynamic)] and \
+                         #[argh(default)] attributes.",
                     );
                 }
             }
         }
 
+        if let (Some(_), Some(default)) = (&this.is_dynamic, &this.is_default) {
+            errors.err(default, "`default` cannot be specified on a `dynamic` variant");
+        }
+
         this
     }
 }
@@ -524,6 +987,7 @@ impl VariantAttrs {
 #[derive(Default)]
 pub struct ChoiceVariantAttrs {
     pub name_override: Option<syn::LitStr>,
+    pub deprecated: Option<syn::LitStr>,
 }
 
 impl ChoiceVariantAttrs {
@@ -544,11 +1008,13 @@ impl ChoiceVariantAttrs {
                     if let Some(m) = errors.expect_meta_name_value(&meta) {
                         parse_attr_single_string(errors, m, "name", &mut this.name_override);
                     }
+                } else if name.is_ident("deprecated") {
+                    parse_attr_deprecated(errors, &meta, &mut this.deprecated);
                 } else {
                     errors.err(
                         &meta,
                         "Invalid variant-level `argh` attribute\n\
-                         Choice variants can only have the `name` attribute.",
+                         Choice variants can only have the `name` and `deprecated` attributes.",
                     );
                 }
             }
@@ -714,6 +1180,13 @@ pub fn check_enum_type_attrs(errors: &Errors, type_attrs: &TypeAttrs, type_span:
         error_codes,
         help_triggers,
         usage,
+        env_prefix,
+        response_files,
+        collect_errors,
+        groups,
+        aliases,
+        visible_aliases,
+        deprecated,
     } = type_attrs;
 
     // Ensure that `#[argh(subcommand)]` is present.
@@ -757,6 +1230,24 @@ pub fn check_enum_type_attrs(errors: &Errors, type_attrs: &TypeAttrs, type_span:
     if let Some(usage) = usage {
         err_unused_enum_attr(errors, usage);
     }
+    if let Some(env_prefix) = env_prefix {
+        err_unused_enum_attr(errors, env_prefix);
+    }
+    if let Some(response_files) = response_files {
+        err_unused_enum_attr(errors, response_files);
+    }
+    if let Some(collect_errors) = collect_errors {
+        err_unused_enum_attr(errors, collect_errors);
+    }
+    if let Some(group) = groups.first() {
+        err_unused_enum_attr(errors, &group.name);
+    }
+    if let Some(alias) = aliases.first().or(visible_aliases.first()) {
+        err_unused_enum_attr(errors, alias);
+    }
+    if let Some(deprecated) = deprecated {
+        err_unused_enum_attr(errors, deprecated);
+    }
 }
 
 fn err_unused_enum_attr(errors: &Errors, location: &impl syn::spanned::Spanned) {
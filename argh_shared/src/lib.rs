@@ -6,6 +6,8 @@
 //!
 //! This library is intended only for internal use by these two crates.
 
+use std::borrow::Cow;
+
 /// Information about a particular command used for output.
 pub struct CommandInfo<'a> {
     /// The name of the command.
@@ -14,11 +16,34 @@ pub struct CommandInfo<'a> {
     pub short: &'a char,
     /// A short description of the command's functionality.
     pub description: &'a str,
+    /// Other names for the command which are not shown in help.
+    pub aliases: &'a [&'a str],
+    /// Other names for the command which are shown in help.
+    pub visible_aliases: &'a [&'a str],
+    /// Why the command is deprecated, if it is. The note may be empty.
+    pub deprecated: Option<&'a str>,
 }
 
 impl<'a> Default for CommandInfo<'a> {
     fn default() -> Self {
-        Self { name: Default::default(), short: &'\0', description: Default::default() }
+        Self {
+            name: Default::default(),
+            short: &'\0',
+            description: Default::default(),
+            aliases: &[],
+            visible_aliases: &[],
+            deprecated: None,
+        }
+    }
+}
+
+impl CommandInfo<'_> {
+    /// Whether `name` is the name, short name or one of the aliases of this command.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name
+            || (*self.short != '\0' && name.chars().eq(std::iter::once(*self.short)))
+            || self.aliases.contains(&name)
+            || self.visible_aliases.contains(&name)
     }
 }
 
@@ -32,6 +57,12 @@ pub struct CommandInfoWithArgs<'a> {
     pub short: &'a char,
     /// A short description of the command's functionality.
     pub description: &'a str,
+    /// Other names for the command which are not shown in help.
+    pub aliases: &'a [&'a str],
+    /// Other names for the command which are shown in help.
+    pub visible_aliases: &'a [&'a str],
+    /// Why the command is deprecated, if it is. The note may be empty.
+    pub deprecated: Option<&'a str>,
     /// Examples of usage
     pub examples: &'a [&'a str],
     /// Flags
@@ -44,6 +75,26 @@ pub struct CommandInfoWithArgs<'a> {
     pub positionals: &'a [PositionalInfo<'a>],
     /// Error code information
     pub error_codes: &'a [ErrorCodeInfo<'a>],
+    /// The prefix of the environment variables that options without an
+    /// explicit `env` fall back to. This is either set with `env_prefix`
+    /// or inherited from a parent command.
+    pub env_prefix: Option<Cow<'a, str>>,
+}
+
+impl CommandInfoWithArgs<'_> {
+    /// Returns the environment variable that `flag`, one of this command's
+    /// flags, falls back to when it is not provided.
+    pub fn flag_env_var(&self, flag: &FlagInfo<'_>) -> Option<String> {
+        if let Some(env) = flag.env {
+            return Some(env.to_owned());
+        }
+        match (&flag.kind, &self.env_prefix) {
+            (FlagInfoKind::Option { .. } | FlagInfoKind::OptionalValue { .. }, Some(prefix)) => {
+                Some(env_var_name(prefix, flag.long))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a> Default for CommandInfoWithArgs<'a> {
@@ -52,16 +103,27 @@ impl<'a> Default for CommandInfoWithArgs<'a> {
             name: Default::default(),
             short: &'\0',
             description: Default::default(),
+            aliases: Default::default(),
+            visible_aliases: Default::default(),
+            deprecated: Default::default(),
             examples: Default::default(),
             flags: Default::default(),
             notes: Default::default(),
             commands: Default::default(),
             positionals: Default::default(),
             error_codes: Default::default(),
+            env_prefix: Default::default(),
         }
     }
 }
 
+/// Returns the name of the environment variable for an option or subcommand
+/// `name` under `prefix`, e.g. `MYTOOL_DRY_RUN` for `MYTOOL_` and `--dry-run`.
+pub fn env_var_name(prefix: &str, name: &str) -> String {
+    let name = name.trim_start_matches('-').to_ascii_uppercase().replace('-', "_");
+    [prefix, &name].concat()
+}
+
 /// Information about a documented error code.
 #[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -82,6 +144,8 @@ pub struct PositionalInfo<'a> {
     pub description: &'a str,
     /// Optionality of the argument.
     pub optionality: Optionality,
+    /// Why the argument is deprecated, if it is. The note may be empty.
+    pub deprecated: Option<&'a str>,
     /// Visibility in the help for this argument.
     /// `false` indicates this argument will not appear
     /// in the help message.
@@ -102,7 +166,7 @@ pub struct SubCommandInfo<'a> {
 }
 
 /// Information about a flag or option.
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FlagInfo<'a> {
     /// The kind of flag.
@@ -114,6 +178,19 @@ pub struct FlagInfo<'a> {
     /// The single character short indicator
     /// for this flag.
     pub short: Option<char>,
+    /// The long string of the negated form of a
+    /// `negatable` switch, e.g. `--no-color`.
+    pub negated_long: Option<&'a str>,
+    /// The environment variable this flag falls back to
+    /// when it is not provided.
+    pub env: Option<&'a str>,
+    /// Other spellings of the flag which are not shown in help,
+    /// like `--old-name` or `-x`.
+    pub aliases: &'a [&'a str],
+    /// Other spellings of the flag which are shown in help.
+    pub visible_aliases: &'a [&'a str],
+    /// Why the flag is deprecated, if it is. The note may be empty.
+    pub deprecated: Option<&'a str>,
     /// The description of the flag.
     pub description: &'a str,
     /// Visibility in the help for this argument.
@@ -123,7 +200,7 @@ pub struct FlagInfo<'a> {
 }
 
 /// The kind of flags.
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum FlagInfoKind<'a> {
     /// switch represents a boolean flag,
@@ -132,11 +209,15 @@ pub enum FlagInfoKind<'a> {
     /// option is a flag that also has an associated
     /// value. This value is named `arg_name`.
     Option { arg_name: &'a str },
+    /// an option whose value named `arg_name` may only be attached with `=`,
+    /// like `--color=always`. When given without a value, the option takes
+    /// `default_missing_value` instead.
+    OptionalValue { arg_name: &'a str, default_missing_value: &'a str },
 }
 
 /// The optionality defines the requirements related
 /// to the presence of the argument on the command line.
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Optionality {
     /// Required indicates the argument is required
@@ -153,22 +234,47 @@ pub enum Optionality {
     /// capture the all command line input up to the next flag or
     /// the end of the input.
     Greedy,
+    /// Trailing is used for the arguments after `--`, which
+    /// are collected separately from the other positional arguments.
+    Trailing,
 }
 
 pub const INDENT: &str = "  ";
 const DESCRIPTION_INDENT: usize = 20;
 const WRAP_WIDTH: usize = 80;
 
+/// Returns the marker appended to the description of a deprecated argument
+/// or command in help, like ` [deprecated: use --region instead]`.
+pub fn deprecation_marker(note: &str) -> String {
+    if note.is_empty() {
+        " [deprecated]".to_owned()
+    } else {
+        [" [deprecated: ", note, "]"].concat()
+    }
+}
+
 /// Write command names and descriptions to an output string.
 pub fn write_description(out: &mut String, cmd: &CommandInfo<'_>) {
     let mut current_line = INDENT.to_string();
     current_line.push_str(cmd.name);
+    for alias in cmd.visible_aliases {
+        current_line.push_str(", ");
+        current_line.push_str(alias);
+    }
 
     if *cmd.short != '\0' {
         current_line.push_str(&format!("  {}", cmd.short));
     }
 
-    if cmd.description.is_empty() {
+    let description = match cmd.deprecated {
+        Some(note) => {
+            let marker = deprecation_marker(note);
+            let description = [cmd.description, &marker].concat();
+            Cow::Owned(description.trim_start().to_owned())
+        }
+        None => Cow::Borrowed(cmd.description),
+    };
+    if description.is_empty() {
         new_line(&mut current_line, out);
         return;
     }
@@ -179,7 +285,7 @@ pub fn write_description(out: &mut String, cmd: &CommandInfo<'_>) {
         new_line(&mut current_line, out);
     }
 
-    let mut words = cmd.description.split(' ').peekable();
+    let mut words = description.split(' ').peekable();
     while let Some(first_word) = words.next() {
         indent_description(&mut current_line);
         current_line.push_str(first_word);